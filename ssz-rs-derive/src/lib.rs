@@ -1,46 +1,280 @@
 //! `SimpleSerialize` provides a macro to derive SSZ containers and union types from
 //! native Rust structs and enums.
 //! Refer to the `examples` in the `ssz_rs` crate for a better idea on how to use this derive macro.
-use proc_macro2::TokenStream;
-use quote::{format_ident, quote, quote_spanned};
+mod versioned;
+
+use proc_macro2::{Span, TokenStream, TokenTree};
+use quote::{format_ident, quote, quote_spanned, ToTokens};
+use std::{collections::BTreeSet, fmt::Display};
 use syn::{
-    parse_macro_input, spanned::Spanned, Data, DeriveInput, Fields, Ident, ImplGenerics,
-    TypeGenerics,
+    parse_macro_input, parse_quote, punctuated::Punctuated, spanned::Spanned, Attribute,
+    AttributeArgs, Data, DataEnum, DataStruct, DeriveInput, Expr, ExprLit, Field, Fields, Generics,
+    Ident, ImplGenerics, Index, Lit, LitInt, Member, Meta, NestedMeta, Path, Token, Type,
+    TypeGenerics, WhereClause, WherePredicate,
 };
 
 // NOTE: copied here from `ssz_rs` crate as it is unlikely to change
 // and can keep it out of the crate's public interface.
 const BYTES_PER_CHUNK: usize = 32;
+const MAX_UNION_SELECTOR: u64 = 127;
+
+// How the generated code decodes the input: with `Deserialize`, or with `DeserializeWithContext`
+// for a context of type `__C`, forwarding the `context` given by the caller to each field or
+// variant.
+#[derive(Clone, Copy)]
+enum Decoding {
+    Stateless,
+    WithContext,
+}
+
+impl Decoding {
+    fn is_variable_size(self, krate: &Path, ty: impl ToTokens, span: Span) -> TokenStream {
+        match self {
+            Self::Stateless => quote_spanned! { span =>
+                <#ty as #krate::Serializable>::is_variable_size()
+            },
+            Self::WithContext => quote_spanned! { span =>
+                <#ty as #krate::DeserializeWithContext<__C>>::is_variable_size_with_context(context)
+            },
+        }
+    }
+
+    fn size_hint(self, krate: &Path, ty: impl ToTokens, span: Span) -> TokenStream {
+        match self {
+            Self::Stateless => quote_spanned! { span =>
+                <#ty as #krate::Serializable>::size_hint()
+            },
+            Self::WithContext => quote_spanned! { span =>
+                <#ty as #krate::DeserializeWithContext<__C>>::size_hint_with_context(context)
+            },
+        }
+    }
+
+    // `encoding` is an expression for the slice holding the encoding of the value.
+    fn deserialize(
+        self,
+        krate: &Path,
+        ty: impl ToTokens,
+        encoding: TokenStream,
+        span: Span,
+    ) -> TokenStream {
+        match self {
+            Self::Stateless => quote_spanned! { span =>
+                <#ty as #krate::Deserialize>::deserialize(#encoding)
+            },
+            Self::WithContext => quote_spanned! { span =>
+                <#ty as #krate::DeserializeWithContext<__C>>::deserialize_with_context(#encoding, context)
+            },
+        }
+    }
+
+    // The signature of the function decoding the input, which returns `Result<Self, _>`.
+    fn signature(self, krate: &Path) -> TokenStream {
+        match self {
+            Self::Stateless => quote! {
+                fn deserialize(encoding: &[u8]) -> Result<Self, #krate::DeserializeError>
+            },
+            Self::WithContext => quote! {
+                fn deserialize_with_context(encoding: &[u8], context: &__C) -> Result<Self, #krate::DeserializeError>
+            },
+        }
+    }
+}
+
+// A field of a struct along with its `#[ssz(...)]` attributes.
+//
+// Each method returns the code for one operation on the field. A field with a `with` module uses
+// the functions of that module in place of the SSZ traits of its type.
+struct SszField<'a> {
+    member: Member,
+    field: &'a Field,
+    attrs: FieldAttributes,
+}
+
+impl<'a> SszField<'a> {
+    fn is_variable_size(&self, krate: &Path, decoding: Decoding) -> TokenStream {
+        match &self.attrs.with {
+            Some(with) => quote_spanned! { self.field.span() => #with::is_variable_size() },
+            None => decoding.is_variable_size(krate, &self.field.ty, self.field.span()),
+        }
+    }
+
+    fn size_hint(&self, krate: &Path, decoding: Decoding) -> TokenStream {
+        match &self.attrs.with {
+            Some(with) => quote_spanned! { self.field.span() => #with::size_hint() },
+            None => decoding.size_hint(krate, &self.field.ty, self.field.span()),
+        }
+    }
+
+    fn serialize(&self, krate: &Path) -> TokenStream {
+        let member = &self.member;
+        match &self.attrs.with {
+            Some(with) => quote_spanned! { self.field.span() =>
+                #with::serialize(&self.#member, buffer)
+            },
+            None => quote_spanned! { self.field.span() =>
+                #krate::Serialize::serialize(&self.#member, buffer)
+            },
+        }
+    }
+
+    fn serialize_element(&self) -> TokenStream {
+        let member = &self.member;
+        match &self.attrs.with {
+            Some(with) => quote_spanned! { self.field.span() =>
+                let mut element_buffer = Vec::with_capacity(#with::size_hint());
+                #with::serialize(&self.#member, &mut element_buffer)?;
+                serializer.with_encoding(element_buffer, #with::is_variable_size());
+            },
+            None => quote_spanned! { self.field.span() =>
+                serializer.with_element(&self.#member)?;
+            },
+        }
+    }
+
+    fn parse_element(&self, krate: &Path, decoding: Decoding) -> TokenStream {
+        let field_type = &self.field.ty;
+        match (&self.attrs.with, decoding) {
+            (None, Decoding::Stateless) => quote_spanned! { self.field.span() =>
+                deserializer.parse::<#field_type>(encoding)?;
+            },
+            _ => {
+                let is_variable_size = self.is_variable_size(krate, decoding);
+                let size_hint = self.size_hint(krate, decoding);
+                quote_spanned! { self.field.span() =>
+                    deserializer.parse_segment(encoding, #is_variable_size, #size_hint)?;
+                }
+            }
+        }
+    }
+
+    // `encoding` is an expression for the slice holding the encoding of this field.
+    fn deserialize(&self, krate: &Path, encoding: TokenStream, decoding: Decoding) -> TokenStream {
+        match &self.attrs.with {
+            Some(with) => quote_spanned! { self.field.span() => #with::deserialize(#encoding) },
+            None => decoding.deserialize(krate, &self.field.ty, encoding, self.field.span()),
+        }
+    }
+
+    fn hash_tree_root(&self, krate: &Path) -> TokenStream {
+        let member = &self.member;
+        match &self.attrs.with {
+            Some(with) => quote_spanned! { self.field.span() =>
+                #with::hash_tree_root(&mut self.#member)
+            },
+            None => quote_spanned! { self.field.span() =>
+                #krate::Merkleized::hash_tree_root(&mut self.#member)
+            },
+        }
+    }
+
+    fn is_composite_type(&self, krate: &Path) -> TokenStream {
+        let field_type = &self.field.ty;
+        match &self.attrs.with {
+            Some(with) => quote_spanned! { self.field.span() => !#with::schema().is_basic() },
+            None => quote_spanned! { self.field.span() =>
+                <#field_type as #krate::Merkleized>::is_composite_type()
+            },
+        }
+    }
+
+    fn schema(&self, krate: &Path) -> TokenStream {
+        let field_type = &self.field.ty;
+        match &self.attrs.with {
+            Some(with) => quote_spanned! { self.field.span() => #with::schema() },
+            None => quote_spanned! { self.field.span() =>
+                <#field_type as #krate::HasSchema>::schema()
+            },
+        }
+    }
+}
+
+fn ssz_fields(fields: &Fields) -> syn::Result<Vec<SszField<'_>>> {
+    fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| {
+            let member = match &field.ident {
+                Some(ident) => Member::Named(ident.clone()),
+                None => Member::Unnamed(Index::from(i)),
+            };
+            Ok(SszField { member, field, attrs: FieldAttributes::parse(&field.attrs)? })
+        })
+        .collect()
+}
+
+fn validated_fields(fields: &Fields) -> Vec<SszField<'_>> {
+    ssz_fields(fields).expect("field attributes were already validated")
+}
+
+// Fields marked with `#[ssz(skip)]` are not part of the SSZ type.
+fn active_fields<'a, 'b>(fields: &'b [SszField<'a>]) -> Vec<&'b SszField<'a>> {
+    fields.iter().filter(|f| !f.attrs.skip).collect()
+}
+
+// A struct is "transparent" if it is marked as such or if it follows the "newtype" pattern of a
+// tuple struct with one field; the SSZ type is then the type of the one field which is not skipped.
+fn is_transparent(data: &DataStruct, attrs: &ContainerAttributes) -> bool {
+    attrs.transparent ||
+        matches!(data.fields, Fields::Unnamed(ref fields) if fields.unnamed.len() == 1)
+}
+
+// Builds `Self` from the expression given for each field not skipped, taking the `Default` of
+// skipped fields.
+fn construct_self<'a>(
+    fields: &[SszField<'a>],
+    mut value_of: impl FnMut(&SszField<'a>) -> TokenStream,
+) -> TokenStream {
+    let initialization_by_field = fields.iter().map(|f| {
+        let member = &f.member;
+        if f.attrs.skip {
+            quote_spanned! { f.field.span() => #member: Default::default(), }
+        } else {
+            let value = value_of(f);
+            quote_spanned! { f.field.span() => #member: #value, }
+        }
+    });
+    quote! {
+        Self {
+            #(#initialization_by_field)*
+        }
+    }
+}
+
+// An expression for the tag of `self`, for an enum with `#[ssz(enum_behaviour = "tag")]`.
+fn derive_tag_impl(data: &DataEnum) -> TokenStream {
+    let tag_by_variant = data.variants.iter().zip(validated_tags(data)).map(|(variant, tag)| {
+        let variant_name = &variant.ident;
+        quote_spanned! { variant.span() =>
+            Self::#variant_name => #tag,
+        }
+    });
+    quote! {
+        match self {
+            #(#tag_by_variant)*
+        }
+    }
+}
 
-fn derive_serialize_impl(data: &Data) -> TokenStream {
+fn derive_serialize_impl(data: &Data, attrs: &ContainerAttributes) -> TokenStream {
+    let krate = &attrs.krate;
     match data {
         Data::Struct(ref data) => {
-            let fields = match data.fields {
-                // "regular" struct with 1+ fields
-                Fields::Named(ref fields) => &fields.named,
-                // "tuple" struct
-                // only support the case with one unnamed field, to support "newtype" pattern
-                Fields::Unnamed(..) => {
-                    return quote! {
-                        fn serialize(&self, buffer: &mut Vec<u8>) -> Result<usize, ssz_rs::SerializeError> {
-                                self.0.serialize(buffer)
-                        }
+            let fields = validated_fields(&data.fields);
+            let fields = active_fields(&fields);
+            if is_transparent(data, attrs) {
+                let serialize = fields[0].serialize(krate);
+                return quote! {
+                    fn serialize(&self, buffer: &mut Vec<u8>) -> Result<usize, #krate::SerializeError> {
+                        #serialize
                     }
                 }
-                _ => unimplemented!(
-                    "this type of struct is currently not supported by this derive macro"
-                ),
-            };
-            let serialization_by_field = fields.iter().map(|f| match &f.ident {
-                Some(field_name) => quote_spanned! { f.span() =>
-                    serializer.with_element(&self.#field_name)?;
-                },
-                None => panic!("should have already returned an impl"),
-            });
+            }
+            let serialization_by_field = fields.iter().map(|f| f.serialize_element());
 
             quote! {
-                fn serialize(&self, buffer: &mut Vec<u8>) -> Result<usize, ssz_rs::SerializeError> {
-                    let mut serializer = ssz_rs::__internal::Serializer::default();
+                fn serialize(&self, buffer: &mut Vec<u8>) -> Result<usize, #krate::SerializeError> {
+                    let mut serializer = #krate::__internal::Serializer::default();
 
                     #(#serialization_by_field)*
 
@@ -48,33 +282,41 @@ fn derive_serialize_impl(data: &Data) -> TokenStream {
                 }
             }
         }
+        Data::Enum(ref data) if attrs.is_tag() => {
+            let tag = derive_tag_impl(data);
+            quote! {
+                fn serialize(&self, buffer: &mut Vec<u8>) -> Result<usize, #krate::SerializeError> {
+                    #krate::Serialize::serialize(&#tag, buffer)
+                }
+            }
+        }
         Data::Enum(ref data) => {
-            let serialization_by_variant = data.variants.iter().enumerate().map(|(i, variant)| {
+            let selectors = validated_selectors(data);
+            let serialization_by_variant = data.variants.iter().zip(selectors).map(|(variant, selector)| {
                 let variant_name = &variant.ident;
                 match &variant.fields {
                     Fields::Unnamed(..) => {
                         quote_spanned! { variant.span() =>
                             Self::#variant_name(value) => {
-                                let selector = #i as u8;
-                                let selector_bytes = selector.serialize(buffer)?;
-                                let value_bytes  = value.serialize(buffer)?;
+                                let selector_bytes = #krate::Serialize::serialize(&#selector, buffer)?;
+                                let value_bytes  = #krate::Serialize::serialize(value, buffer)?;
                                 Ok(selector_bytes + value_bytes)
                             }
                         }
                     }
                     Fields::Unit => {
                         quote_spanned! { variant.span() =>
-                            Self::None => {
-                                0u8.serialize(buffer)
+                            Self::#variant_name => {
+                                #krate::Serialize::serialize(&#selector, buffer)
                             }
                         }
                     }
-                    _ => unreachable!(),
+                    _ => unreachable!("variants were already validated"),
                 }
             });
 
             quote! {
-                fn serialize(&self, buffer: &mut Vec<u8>) -> Result<usize, ssz_rs::SerializeError> {
+                fn serialize(&self, buffer: &mut Vec<u8>) -> Result<usize, #krate::SerializeError> {
                     match self {
                         #(#serialization_by_variant)*
                     }
@@ -85,103 +327,112 @@ fn derive_serialize_impl(data: &Data) -> TokenStream {
     }
 }
 
-fn derive_deserialize_impl(data: &Data) -> TokenStream {
+fn derive_deserialize_impl(
+    data: &Data,
+    attrs: &ContainerAttributes,
+    decoding: Decoding,
+) -> TokenStream {
+    let krate = &attrs.krate;
+    let signature = decoding.signature(krate);
     match data {
         Data::Struct(ref data) => {
-            let fields = match data.fields {
-                // "regular" struct with 1+ fields
-                Fields::Named(ref fields) => &fields.named,
-                // "tuple" struct
-                // only support the case with one unnamed field, to support "newtype" pattern
-                Fields::Unnamed(ref fields) => {
-                    // SAFETY: index is safe because Punctuated always has a first element; qed
-                    let f = &fields.unnamed[0];
-                    let field_type = &f.ty;
-                    return quote! {
-                        fn deserialize(encoding: &[u8]) -> Result<Self, ssz_rs::DeserializeError> {
-                            let result = <#field_type>::deserialize(&encoding)?;
-                            Ok(Self(result))
-                        }
+            let fields = validated_fields(&data.fields);
+            if is_transparent(data, attrs) {
+                let result = construct_self(&fields, |f| {
+                    let deserialize = f.deserialize(krate, quote!(encoding), decoding);
+                    quote! { #deserialize? }
+                });
+                return quote! {
+                    #signature {
+                        Ok(#result)
                     }
                 }
-                _ => unimplemented!(
-                    "this type of struct is currently not supported by this derive macro"
-                ),
-            };
-            let deserialization_by_field = fields.iter().map(|f| {
-                let field_type = &f.ty;
-                match &f.ident {
-                    Some(_) => quote_spanned! { f.span() =>
-                        deserializer.parse::<#field_type>(encoding)?;
-                    },
-                    None => panic!("should have already returned an impl"),
-                }
-            });
+            }
+            let deserialization_by_field =
+                active_fields(&fields).into_iter().map(|f| f.parse_element(krate, decoding));
 
-            let initialization_by_field = fields.iter().enumerate().map(|(i, f)| {
-                let field_type = &f.ty;
-                match &f.ident {
-                    Some(field_name) => quote_spanned! { f.span() =>
-                        #field_name: <#field_type>::deserialize(&encoding[spans[2*#i]..spans[2*#i+1]])?,
-                    },
-                    None => panic!("should have already returned an impl"),
-                }
+            let mut i = 0usize;
+            let result = construct_self(&fields, |f| {
+                let deserialize =
+                    f.deserialize(krate, quote!(&encoding[spans[2*#i]..spans[2*#i+1]]), decoding);
+                i += 1;
+                quote! { #deserialize? }
             });
 
             quote! {
-                fn deserialize(encoding: &[u8]) -> Result<Self, ssz_rs::DeserializeError> {
-                    let mut deserializer = ssz_rs::__internal::ContainerDeserializer::default();
+                #signature {
+                    let mut deserializer = #krate::__internal::ContainerDeserializer::default();
 
                     #(#deserialization_by_field)*
 
                     let spans = deserializer.finalize(encoding)?;
 
-                    Ok(Self {
-                        #(#initialization_by_field)*
-                    })
+                    Ok(#result)
+                }
+            }
+        }
+        Data::Enum(ref data) if attrs.is_tag() => {
+            let variant_by_tag =
+                data.variants.iter().zip(validated_tags(data)).map(|(variant, tag)| {
+                    let variant_name = &variant.ident;
+                    quote_spanned! { variant.span() =>
+                        #tag => Ok(Self::#variant_name),
+                    }
+                });
+            quote! {
+                #signature {
+                    match <u8 as #krate::Deserialize>::deserialize(encoding)? {
+                        #(#variant_by_tag)*
+                        b => Err(#krate::DeserializeError::InvalidByte(b)),
+                    }
                 }
             }
         }
         Data::Enum(ref data) => {
+            let selectors = validated_selectors(data);
             let deserialization_by_variant =
-                data.variants.iter().enumerate().map(|(i, variant)| {
-                    // NOTE: this is "safe" as the number of legal variants fits into `u8`
-                    let i = i as u8;
+                data.variants.iter().zip(selectors).map(|(variant, selector)| {
                     let variant_name = &variant.ident;
                     match &variant.fields {
                         Fields::Unnamed(inner) => {
                             // SAFETY: index is safe because Punctuated always has a first element;
                             // qed
-                            let variant_type = &inner.unnamed[0];
+                            let variant_type = &inner.unnamed[0].ty;
+                            let deserialize = decoding.deserialize(
+                                krate,
+                                variant_type,
+                                quote!(&encoding[1..]),
+                                variant.span(),
+                            );
                             quote_spanned! { variant.span() =>
-                                #i => {
+                                #selector => {
                                     // SAFETY: index is safe because encoding isn't empty; qed
-                                    let value = <#variant_type>::deserialize(&encoding[1..])?;
+                                    let value = #deserialize?;
                                     Ok(Self::#variant_name(value))
                                 }
                             }
                         }
                         Fields::Unit => {
                             quote_spanned! { variant.span() =>
-                                0 => {
+                                #selector => {
                                     if encoding.len() != 1 {
-                                        return Err(DeserializeError::AdditionalInput {
+                                        return Err(#krate::DeserializeError::AdditionalInput {
                                             provided: encoding.len(),
                                             expected: 1,
                                         })
                                     }
-                                    Ok(Self::None)
+                                    Ok(Self::#variant_name)
                                 },
                             }
                         }
-                        _ => unreachable!(),
+                        _ => unreachable!("variants were already validated"),
                     }
                 });
 
             quote! {
-                fn deserialize(encoding: &[u8]) -> Result<Self, ssz_rs::DeserializeError> {
+                #signature {
                     if encoding.is_empty() {
-                        return Err(ssz_rs::DeserializeError::ExpectedFurtherInput {
+                        return Err(#krate::DeserializeError::ExpectedFurtherInput {
                             provided: 0,
                             expected: 1,
                         });
@@ -190,7 +441,7 @@ fn derive_deserialize_impl(data: &Data) -> TokenStream {
                     // SAFETY: index is safe because encoding isn't empty; qed
                     match encoding[0] {
                         #(#deserialization_by_variant)*
-                        b => Err(ssz_rs::DeserializeError::InvalidByte(b)),
+                        b => Err(#krate::DeserializeError::InvalidByte(b)),
                     }
                 }
             }
@@ -199,27 +450,25 @@ fn derive_deserialize_impl(data: &Data) -> TokenStream {
     }
 }
 
-fn derive_variable_size_impl(data: &Data) -> TokenStream {
+fn derive_variable_size_impl(
+    data: &Data,
+    attrs: &ContainerAttributes,
+    decoding: Decoding,
+) -> TokenStream {
     match data {
         Data::Struct(ref data) => {
-            let fields = match data.fields {
-                Fields::Named(ref fields) => &fields.named,
-                Fields::Unnamed(ref fields) => &fields.unnamed,
-                _ => unimplemented!(
-                    "this type of struct is currently not supported by this derive macro"
-                ),
-            };
-            let impl_by_field = fields.iter().map(|f| {
-                let field_type = &f.ty;
-                quote_spanned! { f.span() =>
-                    <#field_type>::is_variable_size()
-                }
-            });
+            let fields = validated_fields(&data.fields);
+            let impl_by_field = active_fields(&fields)
+                .into_iter()
+                .map(|f| f.is_variable_size(&attrs.krate, decoding));
 
             quote! {
                 #(#impl_by_field)|| *
             }
         }
+        Data::Enum(..) if attrs.is_tag() => {
+            quote! { false }
+        }
         Data::Enum(..) => {
             quote! { true }
         }
@@ -227,31 +476,31 @@ fn derive_variable_size_impl(data: &Data) -> TokenStream {
     }
 }
 
-fn derive_size_hint_impl(data: &Data) -> TokenStream {
+fn derive_size_hint_impl(
+    data: &Data,
+    attrs: &ContainerAttributes,
+    decoding: Decoding,
+) -> TokenStream {
+    let krate = &attrs.krate;
     match data {
         Data::Struct(ref data) => {
-            let fields = match data.fields {
-                Fields::Named(ref fields) => &fields.named,
-                Fields::Unnamed(ref fields) => &fields.unnamed,
-                _ => unimplemented!(
-                    "this type of struct is currently not supported by this derive macro"
-                ),
-            };
-            let impl_by_field = fields.iter().map(|f| {
-                let field_type = &f.ty;
-                quote_spanned! { f.span() =>
-                    <#field_type>::size_hint()
-                }
-            });
+            let fields = validated_fields(&data.fields);
+            let impl_by_field =
+                active_fields(&fields).into_iter().map(|f| f.size_hint(krate, decoding));
+            let is_variable_size =
+                decoding.is_variable_size(krate, quote!(Self), Span::call_site());
 
             quote! {
-                if Self::is_variable_size() {
+                if #is_variable_size {
                     0
                 } else {
                     #(#impl_by_field)+ *
                 }
             }
         }
+        Data::Enum(..) if attrs.is_tag() => {
+            quote! { 1 }
+        }
         Data::Enum(..) => {
             quote! { 0 }
         }
@@ -259,63 +508,89 @@ fn derive_size_hint_impl(data: &Data) -> TokenStream {
     }
 }
 
-fn derive_merkleization_impl(data: &Data) -> TokenStream {
+fn derive_merkleization_impl(data: &Data, attrs: &ContainerAttributes) -> TokenStream {
+    let krate = &attrs.krate;
     match data {
         Data::Struct(ref data) => {
-            let fields = match data.fields {
-                Fields::Named(ref fields) => &fields.named,
-                Fields::Unnamed(ref fields) => &fields.unnamed,
-                _ => unimplemented!(
-                    "this type of struct is currently not supported by this derive macro"
-                ),
-            };
-            let field_count = fields.iter().len();
-            let impl_by_field = fields.iter().enumerate().map(|(i, f)| match &f.ident {
-                Some(field_name) => quote_spanned! { f.span() =>
-                    let chunk = self.#field_name.hash_tree_root()?;
-                    let range = #i*#BYTES_PER_CHUNK..(#i+1)*#BYTES_PER_CHUNK;
-                    chunks[range].copy_from_slice(chunk.as_ref());
-                },
-                None => quote_spanned! { f.span() =>
-                    let chunk = self.0.hash_tree_root()?;
+            let fields = validated_fields(&data.fields);
+            let fields = active_fields(&fields);
+            if is_transparent(data, attrs) {
+                let hash_tree_root = fields[0].hash_tree_root(krate);
+                // NOTE: only types marked `transparent` are basic types when their field is, so
+                // the roots of existing "newtype" structs are unchanged
+                let is_composite_type = attrs.transparent.then(|| {
+                    let is_composite_type = fields[0].is_composite_type(krate);
+                    quote! {
+                        fn is_composite_type() -> bool {
+                            #is_composite_type
+                        }
+                    }
+                });
+                return quote! {
+                    fn hash_tree_root(&mut self) -> Result<#krate::Node, #krate::MerkleizationError> {
+                        #hash_tree_root
+                    }
+
+                    #is_composite_type
+                }
+            }
+            let field_count = fields.len();
+            let impl_by_field = fields.iter().enumerate().map(|(i, f)| {
+                let hash_tree_root = f.hash_tree_root(krate);
+                quote_spanned! { f.field.span() =>
+                    let chunk = #hash_tree_root?;
                     let range = #i*#BYTES_PER_CHUNK..(#i+1)*#BYTES_PER_CHUNK;
                     chunks[range].copy_from_slice(chunk.as_ref());
-                },
+                }
             });
             quote! {
-                fn hash_tree_root(&mut self) -> Result<ssz_rs::Node, ssz_rs::MerkleizationError> {
-                    let mut chunks = vec![0u8; #field_count * #BYTES_PER_CHUNK];
+                fn hash_tree_root(&mut self) -> Result<#krate::Node, #krate::MerkleizationError> {
+                    let mut chunks = [0u8; #field_count * #BYTES_PER_CHUNK];
                     #(#impl_by_field)*
-                    ssz_rs::__internal::merkleize(&chunks, None)
+                    #krate::__internal::merkleize(&chunks, None)
+                }
+            }
+        }
+        Data::Enum(ref data) if attrs.is_tag() => {
+            let tag = derive_tag_impl(data);
+            quote! {
+                fn hash_tree_root(&mut self) -> Result<#krate::Node, #krate::MerkleizationError> {
+                    #krate::Merkleized::hash_tree_root(&mut #tag)
+                }
+
+                fn is_composite_type() -> bool {
+                    false
                 }
             }
         }
         Data::Enum(ref data) => {
-            let hash_tree_root_by_variant = data.variants.iter().enumerate().map(|(i, variant)| {
-                let variant_name = &variant.ident;
-                match &variant.fields {
-                    Fields::Unnamed(..) => {
-                        quote_spanned! { variant.span() =>
-                            Self::#variant_name(value) => {
-                                let selector = #i;
-                                let data_root  = value.hash_tree_root()?;
-                                Ok(ssz_rs::__internal::mix_in_selector(&data_root, selector))
+            let selectors = validated_selectors(data);
+            let hash_tree_root_by_variant =
+                data.variants.iter().zip(selectors).map(|(variant, selector)| {
+                    let variant_name = &variant.ident;
+                    let selector = selector as usize;
+                    match &variant.fields {
+                        Fields::Unnamed(..) => {
+                            quote_spanned! { variant.span() =>
+                                Self::#variant_name(value) => {
+                                    let data_root  = #krate::Merkleized::hash_tree_root(value)?;
+                                    Ok(#krate::__internal::mix_in_selector(&data_root, #selector))
+                                }
                             }
                         }
-                    }
-                    Fields::Unit => {
-                        quote_spanned! { variant.span() =>
-                            Self::None => Ok(ssz_rs::__internal::mix_in_selector(
-                                &ssz_rs::Node::default(),
-                                0,
-                            )),
+                        Fields::Unit => {
+                            quote_spanned! { variant.span() =>
+                                Self::#variant_name => Ok(#krate::__internal::mix_in_selector(
+                                    &#krate::Node::default(),
+                                    #selector,
+                                )),
+                            }
                         }
+                        _ => unreachable!("variants were already validated"),
                     }
-                    _ => unreachable!(),
-                }
-            });
+                });
             quote! {
-                fn hash_tree_root(&mut self) -> Result<ssz_rs::Node, ssz_rs::MerkleizationError> {
+                fn hash_tree_root(&mut self) -> Result<#krate::Node, #krate::MerkleizationError> {
                     match self {
                             #(#hash_tree_root_by_variant)*
                     }
@@ -326,96 +601,845 @@ fn derive_merkleization_impl(data: &Data) -> TokenStream {
     }
 }
 
-fn is_valid_none_identifier(ident: &Ident) -> bool {
-    *ident == format_ident!("None")
+fn derive_schema_impl(data: &Data, name: &Ident, attrs: &ContainerAttributes) -> TokenStream {
+    let krate = &attrs.krate;
+    match data {
+        Data::Struct(ref data) => {
+            let fields = validated_fields(&data.fields);
+            let fields = active_fields(&fields);
+            // a transparent struct is described by the type of its field
+            if is_transparent(data, attrs) {
+                return fields[0].schema(krate)
+            }
+            let field_by_field = fields.iter().map(|f| {
+                let schema = f.schema(krate);
+                let field_name = field_name(&f.member);
+                quote_spanned! { f.field.span() =>
+                    #krate::Field {
+                        name: #field_name,
+                        schema: #schema,
+                    },
+                }
+            });
+            let name = name.to_string();
+            quote! {
+                #krate::Schema::Container {
+                    name: #name,
+                    fields: vec![#(#field_by_field)*],
+                }
+            }
+        }
+        Data::Enum(..) if attrs.is_tag() => {
+            quote! { #krate::Schema::Uint(1) }
+        }
+        Data::Enum(ref data) => {
+            let selectors = validated_selectors(data);
+            let variant_by_variant =
+                data.variants.iter().zip(selectors).map(|(variant, selector)| {
+                    let variant_name = variant.ident.to_string();
+                    let schema = match &variant.fields {
+                        Fields::Unnamed(inner) => {
+                            // SAFETY: index is safe because Punctuated always has a first element;
+                            // qed
+                            let variant_type = &inner.unnamed[0].ty;
+                            quote! { Some(<#variant_type as #krate::HasSchema>::schema()) }
+                        }
+                        Fields::Unit => quote! { None },
+                        _ => unreachable!("variants were already validated"),
+                    };
+                    quote_spanned! { variant.span() =>
+                        #krate::Variant {
+                            name: #variant_name,
+                            selector: #selector,
+                            schema: #schema,
+                        },
+                    }
+                });
+            let name = name.to_string();
+            quote! {
+                #krate::Schema::Union {
+                    name: #name,
+                    variants: vec![#(#variant_by_variant)*],
+                }
+            }
+        }
+        Data::Union(..) => unreachable!("data was already validated to exclude union types"),
+    }
+}
+
+// Associated items describing the layout of a container: the number and names of its fields, and
+// for each field its chunk index and generalized index in the merkle tree of the container and
+// its byte offset in the encoding of the container, if that does not depend on the value.
+//
+// Returns `None` unless the container asks for them with `#[ssz(field_metadata)]`.
+fn derive_field_metadata_impl(
+    data: &Data,
+    name: &Ident,
+    attrs: &ContainerAttributes,
+) -> Option<TokenStream> {
+    let krate = &attrs.krate;
+    let data = match data {
+        Data::Struct(ref data) if attrs.field_metadata && !is_transparent(data, attrs) => data,
+        _ => return None,
+    };
+    let fields = validated_fields(&data.fields);
+    let fields = active_fields(&fields);
+    let field_count = fields.len();
+    let field_names = fields.iter().map(|f| field_name(&f.member));
+    let leaf_count = field_count.next_power_of_two();
+
+    let items_by_field = fields.iter().enumerate().map(|(i, f)| {
+        let field_name = field_name(&f.member);
+        // the fields of a tuple struct are `FIELD_0`, `FIELD_1`, etc.
+        let prefix = match &f.member {
+            Member::Named(..) => field_name.clone(),
+            Member::Unnamed(..) => format!("field_{field_name}"),
+        };
+        let chunk_index = format_ident!("{}_CHUNK_INDEX", prefix.to_uppercase());
+        let generalized_index = format_ident!("{}_GENERALIZED_INDEX", prefix.to_uppercase());
+        let offset = format_ident!("{}_offset", prefix);
+        let chunk_index_doc = format!("Index of the chunk of `{field_name}` in the merkleization of `{name}`.");
+        let generalized_index_doc =
+            format!("Generalized index of `{field_name}` in the merkle tree of `{name}`.");
+        let offset_doc = format!(
+            "Byte offset of `{field_name}` in the encoding of `{name}`, if it and the fields before it are fixed size."
+        );
+        let generalized_index_value = leaf_count + i;
+        let fields_up_to =
+            fields[..=i].iter().map(|f| f.is_variable_size(krate, Decoding::Stateless));
+        let sizes_before = fields[..i].iter().map(|f| f.size_hint(krate, Decoding::Stateless));
+        quote_spanned! { f.field.span() =>
+            #[doc = #chunk_index_doc]
+            pub const #chunk_index: usize = #i;
+            #[doc = #generalized_index_doc]
+            pub const #generalized_index: usize = #generalized_index_value;
+            #[doc = #offset_doc]
+            pub fn #offset() -> Option<usize> {
+                let is_variable_size = [#(#fields_up_to),*];
+                if is_variable_size.contains(&true) {
+                    return None
+                }
+                let sizes: [usize; #i] = [#(#sizes_before),*];
+                Some(sizes.iter().sum())
+            }
+        }
+    });
+
+    let field_count_doc = format!("Number of fields of the SSZ container `{name}`.");
+    let field_names_doc = format!("Names of the fields of the SSZ container `{name}`, in order.");
+    Some(quote! {
+        #[doc = #field_count_doc]
+        pub const FIELD_COUNT: usize = #field_count;
+        #[doc = #field_names_doc]
+        pub const FIELD_NAMES: [&'static str; #field_count] = [#(#field_names),*];
+
+        #(#items_by_field)*
+    })
+}
+
+// Implements `DeserializeWithContext` for any context which the types of the fields (or variants)
+// of the input accept, forwarding the context to each of them.
+fn derive_deserialize_with_context_impl(
+    input: &DeriveInput,
+    attrs: &ContainerAttributes,
+) -> TokenStream {
+    let krate = &attrs.krate;
+    let name = &input.ident;
+    let data = &input.data;
+    let deserialize_impl = derive_deserialize_impl(data, attrs, Decoding::WithContext);
+    let is_variable_size_impl = derive_variable_size_impl(data, attrs, Decoding::WithContext);
+    let size_hint_impl = derive_size_hint_impl(data, attrs, Decoding::WithContext);
+
+    let mut generics = input.generics.clone();
+    generics.params.push(parse_quote!(__C: ?Sized));
+    generics.make_where_clause().predicates.extend(
+        field_types(data)
+            .into_iter()
+            .map(|ty| -> WherePredicate { parse_quote!(#ty: #krate::DeserializeWithContext<__C>) }),
+    );
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+
+    quote! {
+        #[allow(unused_variables)]
+        impl #impl_generics #krate::DeserializeWithContext<__C> for #name #ty_generics #where_clause {
+            #deserialize_impl
+
+            fn is_variable_size_with_context(context: &__C) -> bool {
+                #is_variable_size_impl
+            }
+
+            fn size_hint_with_context(context: &__C) -> usize {
+                #size_hint_impl
+            }
+        }
+    }
+}
+
+// Implements the `serde` traits for an SSZ union, by way of a local enum with a type parameter
+// for the value of each variant (and `()` for the unit variant) which derives them with the
+// requested representation.
+//
+// Returns `None` unless the input asks for a `serde` representation.
+fn derive_serde_impl(input: &DeriveInput, attrs: &ContainerAttributes) -> Option<TokenStream> {
+    let representation = attrs.serde.as_ref()?;
+    let data = match &input.data {
+        Data::Enum(ref data) => data,
+        _ => unreachable!("data was already validated to be an enum"),
+    };
+    let krate = &attrs.krate;
+    let name = &input.ident;
+    let serde = quote!(#krate::__internal::serde);
+    let serde_crate = syn::LitStr::new(&serde.to_string(), name.span());
+    let representation = match representation {
+        SerdeRepresentation::Tagged => quote!(tag = "selector", content = "data"),
+        SerdeRepresentation::Untagged => quote!(untagged),
+    };
+
+    let selectors = validated_selectors(data);
+    let mut type_params = vec![];
+    let mut value_types = vec![];
+    let mut helper_variants = vec![];
+    let mut to_helper = vec![];
+    let mut from_helper = vec![];
+    for (variant, selector) in data.variants.iter().zip(selectors) {
+        let variant_name = &variant.ident;
+        let rename = selector.to_string();
+        match &variant.fields {
+            Fields::Unnamed(inner) => {
+                // SAFETY: index is safe because Punctuated::len == 1; qed
+                let ty = &inner.unnamed[0].ty;
+                let type_param = format_ident!("__T{}", type_params.len());
+                helper_variants.push(quote! {
+                    #[serde(rename = #rename)]
+                    #variant_name(#type_param)
+                });
+                to_helper.push(quote!(Self::#variant_name(value) => __Union::#variant_name(value)));
+                from_helper
+                    .push(quote!(__Union::#variant_name(value) => Self::#variant_name(value)));
+                type_params.push(type_param);
+                value_types.push(ty);
+            }
+            Fields::Unit => {
+                helper_variants.push(quote! {
+                    #[serde(rename = #rename)]
+                    #variant_name(())
+                });
+                to_helper.push(quote!(Self::#variant_name => __Union::#variant_name(())));
+                from_helper.push(quote!(__Union::#variant_name(()) => Self::#variant_name));
+            }
+            Fields::Named(..) => unreachable!("data was already validated to exclude named fields"),
+        }
+    }
+    let helper = |derive: TokenStream| {
+        quote! {
+            #[derive(#serde::#derive)]
+            #[serde(crate = #serde_crate, #representation)]
+            enum __Union<#(#type_params),*> {
+                #(#helper_variants),*
+            }
+        }
+    };
+    let serialize_helper = helper(quote!(Serialize));
+    let deserialize_helper = helper(quote!(Deserialize));
+
+    let mut generics = input.generics.clone();
+    generics.make_where_clause().predicates.extend(
+        value_types.iter().map(|ty| -> WherePredicate { parse_quote!(#ty: #serde::Serialize) }),
+    );
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let serialize_impl = quote! {
+        impl #impl_generics #serde::Serialize for #name #ty_generics #where_clause {
+            fn serialize<__S>(&self, serializer: __S) -> Result<__S::Ok, __S::Error>
+            where
+                __S: #serde::Serializer,
+            {
+                #serialize_helper
+
+                let value: __Union<#(&#value_types),*> = match self {
+                    #(#to_helper,)*
+                };
+                #serde::Serialize::serialize(&value, serializer)
+            }
+        }
+    };
+
+    let mut generics = input.generics.clone();
+    generics.params.insert(0, parse_quote!('__de));
+    generics.make_where_clause().predicates.extend(
+        value_types
+            .iter()
+            .map(|ty| -> WherePredicate { parse_quote!(#ty: #serde::Deserialize<'__de>) }),
+    );
+    let (impl_generics, _, where_clause) = generics.split_for_impl();
+    let (_, ty_generics, _) = input.generics.split_for_impl();
+    let deserialize_impl = quote! {
+        impl #impl_generics #serde::Deserialize<'__de> for #name #ty_generics #where_clause {
+            fn deserialize<__D>(deserializer: __D) -> Result<Self, __D::Error>
+            where
+                __D: #serde::Deserializer<'__de>,
+            {
+                #deserialize_helper
+
+                let value: __Union<#(#value_types),*> = #serde::Deserialize::deserialize(deserializer)?;
+                Ok(match value {
+                    #(#from_helper,)*
+                })
+            }
+        }
+    };
+
+    Some(quote! {
+        #serialize_impl
+
+        #deserialize_impl
+    })
+}
+
+// The name of a field in the schema of a container: the fields of a tuple struct are named by their
+// position.
+fn field_name(member: &Member) -> String {
+    match member {
+        Member::Named(ident) => ident.to_string().trim_start_matches("r#").to_string(),
+        Member::Unnamed(index) => index.index.to_string(),
+    }
+}
+
+// Collects the items of each `#[ssz(...)]` attribute in `attrs`.
+fn ssz_attribute_items(attrs: &[Attribute]) -> syn::Result<Vec<NestedMeta>> {
+    let mut items = vec![];
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident("ssz")) {
+        match attr.parse_meta()? {
+            Meta::List(list) => items.extend(list.nested),
+            meta => {
+                return Err(syn::Error::new_spanned(
+                    meta,
+                    "expected an attribute of the form `#[ssz(...)]`",
+                ))
+            }
+        }
+    }
+    Ok(items)
+}
+
+fn parse_str_attribute<T: syn::parse::Parse>(lit: &Lit, name: &str) -> syn::Result<T> {
+    match lit {
+        Lit::Str(value) => value.parse(),
+        _ => Err(syn::Error::new_spanned(lit, format!("expected `#[ssz({name} = \"...\")]`"))),
+    }
+}
+
+// Attributes given to the input type with `#[ssz(...)]`.
+struct ContainerAttributes {
+    // Replaces the inferred bounds on the type parameters, if present.
+    bound: Option<Vec<WherePredicate>>,
+    // Path to the `ssz_rs` crate used in the generated code.
+    krate: Path,
+    // Encode the type exactly as its one field which is not skipped.
+    transparent: bool,
+    // How an enum maps to an SSZ type, if given.
+    enum_behaviour: Option<EnumBehaviour>,
+    // Implement the `serde` traits for an SSZ union with this representation, if given.
+    serde: Option<SerdeRepresentation>,
+    // Add associated items describing the layout of the container.
+    field_metadata: bool,
+}
+
+impl Default for ContainerAttributes {
+    fn default() -> Self {
+        Self {
+            bound: None,
+            krate: parse_quote!(ssz_rs),
+            transparent: false,
+            enum_behaviour: None,
+            serde: None,
+            field_metadata: false,
+        }
+    }
+}
+
+#[derive(PartialEq, Eq)]
+enum EnumBehaviour {
+    // An SSZ union, with the variant's selector followed by the encoding of its value.
+    Union,
+    // A `uint8` holding the discriminant of a unit variant.
+    Tag,
+}
+
+#[derive(PartialEq, Eq)]
+enum SerdeRepresentation {
+    // `{"selector": "1", "data": ...}`, with the selector as a string like other integers in the
+    // consensus APIs.
+    Tagged,
+    // The value of the variant alone, taking the first variant which accepts the input when
+    // deserializing.
+    Untagged,
+}
+
+impl ContainerAttributes {
+    fn is_tag(&self) -> bool {
+        self.enum_behaviour == Some(EnumBehaviour::Tag)
+    }
+
+    fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut result = Self::default();
+        for item in ssz_attribute_items(attrs)? {
+            match item {
+                NestedMeta::Meta(Meta::NameValue(item)) if item.path.is_ident("bound") => {
+                    let bound = match &item.lit {
+                        Lit::Str(bound) => bound,
+                        lit => {
+                            return Err(syn::Error::new_spanned(
+                                lit,
+                                "expected `#[ssz(bound = \"...\")]`",
+                            ))
+                        }
+                    };
+                    let predicates = bound
+                        .parse_with(Punctuated::<WherePredicate, Token![,]>::parse_terminated)?;
+                    result.bound = Some(predicates.into_iter().collect());
+                }
+                NestedMeta::Meta(Meta::NameValue(item)) if item.path.is_ident("crate") => {
+                    result.krate = parse_str_attribute(&item.lit, "crate")?;
+                }
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("transparent") => {
+                    result.transparent = true;
+                }
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("field_metadata") => {
+                    result.field_metadata = true;
+                }
+                NestedMeta::Meta(Meta::NameValue(item)) if item.path.is_ident("enum_behaviour") => {
+                    result.enum_behaviour = match &item.lit {
+                        Lit::Str(value) if value.value() == "union" => Some(EnumBehaviour::Union),
+                        Lit::Str(value) if value.value() == "tag" => Some(EnumBehaviour::Tag),
+                        lit => {
+                            return Err(syn::Error::new_spanned(
+                                lit,
+                                "expected `#[ssz(enum_behaviour = \"union\")]` or `#[ssz(enum_behaviour = \"tag\")]`",
+                            ))
+                        }
+                    };
+                }
+                NestedMeta::Meta(Meta::NameValue(item)) if item.path.is_ident("serde") => {
+                    result.serde = match &item.lit {
+                        Lit::Str(value) if value.value() == "tagged" => {
+                            Some(SerdeRepresentation::Tagged)
+                        }
+                        Lit::Str(value) if value.value() == "untagged" => {
+                            Some(SerdeRepresentation::Untagged)
+                        }
+                        lit => {
+                            return Err(syn::Error::new_spanned(
+                                lit,
+                                "expected `#[ssz(serde = \"tagged\")]` or `#[ssz(serde = \"untagged\")]`",
+                            ))
+                        }
+                    };
+                }
+                item => return Err(syn::Error::new_spanned(item, "unknown `ssz` attribute")),
+            }
+        }
+        Ok(result)
+    }
+}
+
+// Attributes given to a field of a struct with `#[ssz(...)]`.
+#[derive(Default)]
+struct FieldAttributes {
+    // Leave the field out of the SSZ type, taking its `Default` when decoding.
+    skip: bool,
+    // Module providing the SSZ routines for the field in place of the traits of its type.
+    with: Option<Path>,
+}
+
+impl FieldAttributes {
+    fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut result = Self::default();
+        for item in ssz_attribute_items(attrs)? {
+            match item {
+                NestedMeta::Meta(Meta::Path(path)) if path.is_ident("skip") => {
+                    if result.with.is_some() {
+                        return Err(syn::Error::new_spanned(
+                            path,
+                            "a skipped field cannot also have a `with` module",
+                        ))
+                    }
+                    result.skip = true;
+                }
+                NestedMeta::Meta(Meta::NameValue(item)) if item.path.is_ident("with") => {
+                    if result.skip {
+                        return Err(syn::Error::new_spanned(
+                            item,
+                            "a skipped field cannot also have a `with` module",
+                        ))
+                    }
+                    result.with = Some(parse_str_attribute(&item.lit, "with")?);
+                }
+                item => return Err(syn::Error::new_spanned(item, "unknown `ssz` field attribute")),
+            }
+        }
+        Ok(result)
+    }
+}
+
+// The types which need bounds for the type parameters they use; skipped fields and fields with a
+// `with` module do not use the SSZ traits of their type.
+fn field_types(data: &Data) -> Vec<&Type> {
+    match data {
+        Data::Struct(ref data) => validated_fields(&data.fields)
+            .into_iter()
+            .filter(|f| !f.attrs.skip && f.attrs.with.is_none())
+            .map(|f| &f.field.ty)
+            .collect(),
+        Data::Enum(ref data) => {
+            data.variants.iter().flat_map(|variant| variant.fields.iter().map(|f| &f.ty)).collect()
+        }
+        Data::Union(..) => unreachable!("data was already validated to exclude union types"),
+    }
+}
+
+fn mentions_ident(tokens: TokenStream, ident: &Ident) -> bool {
+    tokens.into_iter().any(|token| match token {
+        TokenTree::Ident(other) => other == *ident,
+        TokenTree::Group(group) => mentions_ident(group.stream(), ident),
+        _ => false,
+    })
+}
+
+// Adds `bound` to every type parameter used in the types of the fields of the input, unless the
+// input overrides the bounds with `#[ssz(bound = "...")]`. The bounds declared on the input
+// are kept in either case.
+fn bounded_generics(
+    input: &DeriveInput,
+    attrs: &ContainerAttributes,
+    bound: TokenStream,
+) -> Generics {
+    let mut generics = input.generics.clone();
+    let predicates = match &attrs.bound {
+        Some(predicates) => predicates.clone(),
+        None => {
+            let field_types = field_types(&input.data);
+            input
+                .generics
+                .type_params()
+                .filter(|param| {
+                    field_types.iter().any(|ty| mentions_ident(ty.to_token_stream(), &param.ident))
+                })
+                .map(|param| {
+                    let ident = &param.ident;
+                    parse_quote!(#ident: #bound)
+                })
+                .collect()
+        }
+    };
+    generics.make_where_clause().predicates.extend(predicates);
+    generics
+}
+
+// The generics of the `HasSchema` impl, which needs the type of each field to have a schema.
+// `HasSchema` is not implied by `SimpleSerialize`, so the bounds are stated for the types of the
+// fields. They are put behind a binder so that a field type without a schema, e.g. one with a
+// manual `SimpleSerialize` impl, leaves the derived type without a schema instead of failing to
+// compile.
+fn schema_generics(input: &DeriveInput, attrs: &ContainerAttributes) -> Generics {
+    let krate = &attrs.krate;
+    let mut generics = input.generics.clone();
+    let where_clause = generics.make_where_clause();
+    if let Some(bound) = &attrs.bound {
+        where_clause.predicates.extend(bound.iter().cloned());
+    }
+    where_clause.predicates.extend(
+        field_types(&input.data)
+            .into_iter()
+            .map(|ty| -> WherePredicate { parse_quote!(for<'__a> #ty: #krate::HasSchema) }),
+    );
+    generics
+}
+
+// Attributes given to a variant of an enum with `#[ssz(...)]`.
+#[derive(Default)]
+struct VariantAttributes {
+    // The selector of the variant in place of the one following the selector of the previous
+    // variant.
+    selector: Option<LitInt>,
+}
+
+impl VariantAttributes {
+    fn parse(attrs: &[Attribute]) -> syn::Result<Self> {
+        let mut result = Self::default();
+        for item in ssz_attribute_items(attrs)? {
+            match item {
+                NestedMeta::Meta(Meta::NameValue(item)) if item.path.is_ident("selector") => {
+                    match item.lit {
+                        Lit::Int(selector) => result.selector = Some(selector),
+                        lit => {
+                            return Err(syn::Error::new_spanned(
+                                lit,
+                                "expected `#[ssz(selector = N)]`",
+                            ))
+                        }
+                    }
+                }
+                item => {
+                    return Err(syn::Error::new_spanned(item, "unknown `ssz` variant attribute"))
+                }
+            }
+        }
+        Ok(result)
+    }
+}
+
+// The selector of each variant, in order. Like the discriminants of a Rust enum, a variant
+// without an explicit selector takes the selector following that of the previous variant.
+fn selectors(data: &DataEnum) -> syn::Result<Vec<u64>> {
+    let mut next_selector = 0u64;
+    data.variants
+        .iter()
+        .map(|variant| {
+            let selector = match VariantAttributes::parse(&variant.attrs)?.selector {
+                Some(selector) => selector.base10_parse()?,
+                None => next_selector,
+            };
+            next_selector = selector.saturating_add(1);
+            Ok(selector)
+        })
+        .collect()
+}
+
+// The tag of each variant, in order, taken from the discriminants of the enum.
+fn tags(data: &DataEnum) -> syn::Result<Vec<u64>> {
+    let mut next_tag = 0u64;
+    data.variants
+        .iter()
+        .map(|variant| {
+            let tag = match &variant.discriminant {
+                Some((_, Expr::Lit(ExprLit { lit: Lit::Int(tag), .. }))) => tag.base10_parse()?,
+                Some((_, discriminant)) => {
+                    return Err(syn::Error::new_spanned(
+                        discriminant,
+                        "the discriminant of a tag must be an integer literal",
+                    ))
+                }
+                None => next_tag,
+            };
+            next_tag = tag.saturating_add(1);
+            Ok(tag)
+        })
+        .collect()
+}
+
+fn validated_tags(data: &DataEnum) -> Vec<u8> {
+    tags(data)
+        .expect("discriminants were already validated")
+        .into_iter()
+        // NOTE: this is "safe" as tags were validated to fit into `u8`
+        .map(|tag| tag as u8)
+        .collect()
+}
+
+fn validated_selectors(data: &DataEnum) -> Vec<u8> {
+    selectors(data)
+        .expect("variant attributes were already validated")
+        .into_iter()
+        // NOTE: this is "safe" as selectors were validated to fit into `u8`
+        .map(|selector| selector as u8)
+        .collect()
+}
+
+// Collects the errors found in the input so they can all be reported at once.
+#[derive(Default)]
+struct Errors(Option<syn::Error>);
+
+impl Errors {
+    fn push(&mut self, tokens: impl ToTokens, message: impl Display) {
+        let err = syn::Error::new_spanned(tokens, message);
+        match &mut self.0 {
+            Some(errors) => errors.combine(err),
+            None => self.0 = Some(err),
+        }
+    }
+
+    fn finish(self) -> syn::Result<()> {
+        self.0.map_or(Ok(()), Err)
+    }
 }
 
 // Validates the incoming data follows the rules
 // for mapping the Rust term to something that can
 // implement the `SimpleSerialize` trait.
 //
-// Panics if validation fails which aborts the macro derivation.
-fn validate_derive_data(data: &Data) {
-    match data {
-        Data::Struct(ref data) => match data.fields {
-            Fields::Named(ref fields) => {
-                if fields.named.is_empty() {
-                    panic!("ssz_rs containers with no fields are illegal")
+// Returns an error pointing at each part of the input which breaks the rules.
+fn validate_derive_data(input: &DeriveInput, attrs: &ContainerAttributes) -> syn::Result<()> {
+    let mut errors = Errors::default();
+    match &input.data {
+        Data::Enum(ref data) if attrs.transparent => {
+            errors.push(data.enum_token, "only structs can be `transparent`");
+        }
+        Data::Struct(..) | Data::Union(..) if attrs.enum_behaviour.is_some() => {
+            errors.push(&input.ident, "only enums can have an `enum_behaviour`");
+        }
+        _ => {}
+    }
+    if attrs.serde.is_some() && (!matches!(input.data, Data::Enum(..)) || attrs.is_tag()) {
+        errors.push(&input.ident, "only SSZ unions can have a `serde` representation");
+    }
+    if attrs.field_metadata &&
+        !matches!(&input.data, Data::Struct(data) if !is_transparent(data, attrs))
+    {
+        errors.push(&input.ident, "only containers can have `field_metadata`");
+    }
+    match &input.data {
+        Data::Struct(ref data) => {
+            let fields = ssz_fields(&data.fields)?;
+            let active_fields = active_fields(&fields);
+            match data.fields {
+                Fields::Named(..) | Fields::Unnamed(..) if attrs.transparent => {
+                    if active_fields.len() != 1 {
+                        errors.push(
+                            &input.ident,
+                            "a `transparent` struct must have exactly one field which is not skipped",
+                        );
+                    }
+                }
+                Fields::Unnamed(ref fields) if fields.unnamed.len() == 1 => {
+                    if active_fields.is_empty() {
+                        errors.push(fields, "the field of a \"newtype\" struct cannot be skipped");
+                    }
+                }
+                Fields::Named(..) | Fields::Unnamed(..) => {
+                    if active_fields.is_empty() {
+                        errors.push(&data.fields, "ssz_rs containers with no fields are illegal");
+                    }
+                }
+                Fields::Unit => {
+                    errors.push(&input.ident, "unit structs cannot produce valid SSZ types");
                 }
             }
-            Fields::Unnamed(ref fields) if fields.unnamed.len() == 1 => {}
-            _ => panic!("Structs with unit or multiple unnnamed fields are not supported"),
-        },
-        Data::Enum(ref data) => {
+        }
+        Data::Enum(ref data) if attrs.is_tag() => {
             if data.variants.is_empty() {
-                panic!("SSZ unions must have at least 1 variant; this enum has none");
+                errors.push(
+                    &input.ident,
+                    "an enum with `enum_behaviour = \"tag\"` must have at least 1 variant",
+                );
             }
 
-            if data.variants.len() > 127 {
-                panic!("SSZ unions cannot have more than 127 variants; this enum has more");
+            let tags = tags(data)?;
+            for (variant, &tag) in data.variants.iter().zip(&tags) {
+                if VariantAttributes::parse(&variant.attrs)?.selector.is_some() {
+                    errors.push(
+                        &variant.ident,
+                        "selectors are only used by enums with `enum_behaviour = \"union\"`",
+                    );
+                }
+                if !matches!(variant.fields, Fields::Unit) {
+                    errors.push(
+                        &variant.fields,
+                        "an enum with `enum_behaviour = \"tag\"` can only have unit variants",
+                    );
+                }
+                if tag > u8::MAX as u64 {
+                    errors.push(
+                        &variant.ident,
+                        format!("tags must fit into a `u8`; this variant has tag {tag}"),
+                    );
+                }
+            }
+        }
+        Data::Enum(ref data) => {
+            if data.variants.is_empty() {
+                errors.push(
+                    &input.ident,
+                    "SSZ unions must have at least 1 variant; this enum has none",
+                );
             }
 
-            let mut none_forbidden = false;
-            let mut already_has_none = false;
-            for (i, variant) in data.variants.iter().enumerate() {
+            let selectors = selectors(data)?;
+            let mut seen_selectors = BTreeSet::new();
+            for (variant, &selector) in data.variants.iter().zip(&selectors) {
+                if selector > MAX_UNION_SELECTOR {
+                    errors.push(
+                        &variant.ident,
+                        format!("SSZ union selectors must be at most {MAX_UNION_SELECTOR}; this variant has selector {selector}"),
+                    );
+                }
+                if !seen_selectors.insert(selector) {
+                    errors.push(
+                        &variant.ident,
+                        format!("selector {selector} is used by another variant"),
+                    );
+                }
+                for field in &variant.fields {
+                    if !ssz_attribute_items(&field.attrs)?.is_empty() {
+                        errors.push(
+                            field,
+                            "`ssz` field attributes are not supported in enum variants",
+                        );
+                    }
+                }
                 match &variant.fields {
                     Fields::Unnamed(inner) => {
-                        if i == 0 {
-                            none_forbidden = true;
-                        }
                         if inner.unnamed.len() != 1 {
-                            panic!("Enums can only have 1 type per variant");
+                            errors.push(inner, "Enums can only have 1 type per variant");
                         }
                     }
+                    // a unit variant is the `None` of the SSZ union, with an empty payload
                     Fields::Unit => {
-                        if none_forbidden {
-                            panic!("only the first variant can be `None`");
-                        }
-                        if already_has_none {
-                            panic!("cannot duplicate a unit variant (as only `None` is allowed)");
-                        }
-                        if !is_valid_none_identifier(&variant.ident) {
-                            panic!("Variant identifier is invalid: must be `None`");
-                        }
-                        assert!(i == 0);
-                        if data.variants.len() < 2 {
-                            panic!(
-                                "SSZ unions must have more than 1 selector if the first is `None`"
+                        if selector != 0 {
+                            errors.push(
+                                &variant.ident,
+                                format!("a unit variant must have selector 0; this variant has selector {selector}"),
+                            );
+                        } else if data.variants.len() < 2 {
+                            errors.push(
+                                &variant.ident,
+                                "SSZ unions must have more than 1 selector if the first is `None`",
                             );
                         }
-                        already_has_none = true;
                     }
-                    Fields::Named(..) => {
-                        panic!("Enums with named fields in variants are not supported");
+                    Fields::Named(inner) => {
+                        errors.push(inner, "Enums with named fields in variants are not supported");
                     }
                 };
             }
         }
-        Data::Union(..) => panic!("Rust unions cannot produce valid SSZ types"),
+        Data::Union(ref data) => {
+            errors.push(data.union_token, "Rust unions cannot produce valid SSZ types");
+        }
     }
+    errors.finish()
+}
+
+fn parse_attributes(input: &DeriveInput) -> syn::Result<ContainerAttributes> {
+    let attrs = ContainerAttributes::parse(&input.attrs)?;
+    validate_derive_data(input, &attrs)?;
+    Ok(attrs)
 }
 
 fn derive_serializable_impl(
     data: &Data,
     name: &Ident,
+    attrs: &ContainerAttributes,
     impl_generics: &ImplGenerics,
     ty_generics: &TypeGenerics,
+    where_clause: Option<&WhereClause>,
 ) -> proc_macro2::TokenStream {
-    let serialize_impl = derive_serialize_impl(data);
-    let deserialize_impl = derive_deserialize_impl(data);
-    let is_variable_size_impl = derive_variable_size_impl(data);
-    let size_hint_impl = derive_size_hint_impl(data);
+    let krate = &attrs.krate;
+    let serialize_impl = derive_serialize_impl(data, attrs);
+    let deserialize_impl = derive_deserialize_impl(data, attrs, Decoding::Stateless);
+    let is_variable_size_impl = derive_variable_size_impl(data, attrs, Decoding::Stateless);
+    let size_hint_impl = derive_size_hint_impl(data, attrs, Decoding::Stateless);
 
     quote! {
-        impl #impl_generics ssz_rs::Serialize for #name #ty_generics {
+        impl #impl_generics #krate::Serialize for #name #ty_generics #where_clause {
             #serialize_impl
         }
 
-        impl #impl_generics ssz_rs::Deserialize for #name #ty_generics {
+        impl #impl_generics #krate::Deserialize for #name #ty_generics #where_clause {
             #deserialize_impl
         }
 
-        impl #impl_generics ssz_rs::Serializable for #name #ty_generics {
+        impl #impl_generics #krate::Serializable for #name #ty_generics #where_clause {
             fn is_variable_size() -> bool {
                 #is_variable_size_impl
             }
@@ -427,45 +1451,104 @@ fn derive_serializable_impl(
     }
 }
 
-#[proc_macro_derive(Serializable)]
+#[proc_macro_derive(Serializable, attributes(ssz))]
 pub fn derive_serializable(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
 
     let data = &input.data;
-    validate_derive_data(data);
+    let attrs = match parse_attributes(&input) {
+        Ok(attrs) => attrs,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let krate = &attrs.krate;
 
     let name = &input.ident;
-    let generics = &input.generics;
+    let generics = bounded_generics(&input, &attrs, quote!(#krate::Serializable));
+
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let serializable_impl =
+        derive_serializable_impl(data, name, &attrs, &impl_generics, &ty_generics, where_clause);
+    let deserialize_with_context_impl = derive_deserialize_with_context_impl(&input, &attrs);
+    let serde_impl = derive_serde_impl(&input, &attrs);
+
+    let expansion = quote! {
+        #serializable_impl
+
+        #deserialize_with_context_impl
 
-    let (impl_generics, ty_generics, _) = generics.split_for_impl();
-    let expansion = derive_serializable_impl(data, name, &impl_generics, &ty_generics);
+        #serde_impl
+    };
     proc_macro::TokenStream::from(expansion)
 }
 
-#[proc_macro_derive(SimpleSerialize)]
+#[proc_macro_derive(SimpleSerialize, attributes(ssz))]
 pub fn derive(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
 
     let data = &input.data;
-    validate_derive_data(data);
+    let attrs = match parse_attributes(&input) {
+        Ok(attrs) => attrs,
+        Err(err) => return err.to_compile_error().into(),
+    };
+    let krate = &attrs.krate;
 
     let name = &input.ident;
-    let generics = &input.generics;
-    let merkleization_impl = derive_merkleization_impl(data);
+    let merkleization_impl = derive_merkleization_impl(data, &attrs);
+    let schema_impl = derive_schema_impl(data, name, &attrs);
 
-    let (impl_generics, ty_generics, _) = generics.split_for_impl();
+    let serializable_generics = bounded_generics(&input, &attrs, quote!(#krate::Serializable));
+    let (impl_generics, ty_generics, where_clause) = serializable_generics.split_for_impl();
+    let serializable_impl =
+        derive_serializable_impl(data, name, &attrs, &impl_generics, &ty_generics, where_clause);
 
-    let serializable_impl = derive_serializable_impl(data, name, &impl_generics, &ty_generics);
+    let generics = bounded_generics(&input, &attrs, quote!(#krate::SimpleSerialize));
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    let schema_generics = schema_generics(&input, &attrs);
+    let (schema_impl_generics, _, schema_where_clause) = schema_generics.split_for_impl();
+    let deserialize_with_context_impl = derive_deserialize_with_context_impl(&input, &attrs);
+    let serde_impl = derive_serde_impl(&input, &attrs);
+    let field_metadata_impl = derive_field_metadata_impl(data, name, &attrs).map(|items| {
+        quote! {
+            #[allow(dead_code)]
+            impl #impl_generics #name #ty_generics #where_clause {
+                #items
+            }
+        }
+    });
 
     let expansion = quote! {
         #serializable_impl
 
-        impl #impl_generics ssz_rs::Merkleized for #name #ty_generics {
+        #deserialize_with_context_impl
+
+        impl #impl_generics #krate::Merkleized for #name #ty_generics #where_clause {
             #merkleization_impl
         }
 
-        impl #impl_generics ssz_rs::SimpleSerialize for #name #ty_generics {}
+        impl #schema_impl_generics #krate::HasSchema for #name #ty_generics #schema_where_clause {
+            fn schema() -> #krate::Schema {
+                #schema_impl
+            }
+        }
+
+        impl #impl_generics #krate::SimpleSerialize for #name #ty_generics #where_clause {}
+
+        #field_metadata_impl
+
+        #serde_impl
     };
 
     proc_macro::TokenStream::from(expansion)
 }
+
+#[proc_macro_attribute]
+pub fn versioned(
+    args: proc_macro::TokenStream,
+    input: proc_macro::TokenStream,
+) -> proc_macro::TokenStream {
+    let args = parse_macro_input!(args as AttributeArgs);
+    let input = parse_macro_input!(input as DeriveInput);
+
+    let expansion = versioned::expand(args, input).unwrap_or_else(|err| err.to_compile_error());
+    proc_macro::TokenStream::from(expansion)
+}
@@ -0,0 +1,495 @@
+// `#[versioned(...)]` expands one declaration of a container into a family of containers, one for
+// each version of the type (e.g. each fork of the consensus protocol), along with an enum over
+// the family.
+use crate::{mentions_ident, parse_str_attribute, ContainerAttributes, Errors};
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote, ToTokens};
+use syn::{
+    Attribute, AttributeArgs, Data, DeriveInput, Field, Fields, Ident, Lit, Meta, NestedMeta, Path,
+    WherePredicate,
+};
+
+// Arguments of the `#[versioned(...)]` attribute on the struct.
+struct VersionedArgs {
+    // The versions of the type, in order.
+    variants: Vec<Ident>,
+    // Derives for the enum over the family.
+    derives: Vec<Path>,
+    // Type with a unit variant named after each version, in place of a generated
+    // `<Name>Version`.
+    version: Option<Path>,
+}
+
+fn parse_paths(items: impl IntoIterator<Item = NestedMeta>) -> syn::Result<Vec<Path>> {
+    items
+        .into_iter()
+        .map(|item| match item {
+            NestedMeta::Meta(Meta::Path(path)) => Ok(path),
+            item => Err(syn::Error::new_spanned(item, "expected a path")),
+        })
+        .collect()
+}
+
+fn parse_idents(items: impl IntoIterator<Item = NestedMeta>) -> syn::Result<Vec<Ident>> {
+    parse_paths(items)?
+        .into_iter()
+        .map(|path| match path.get_ident() {
+            Some(ident) => Ok(ident.clone()),
+            None => Err(syn::Error::new_spanned(path, "expected the name of a version")),
+        })
+        .collect()
+}
+
+impl VersionedArgs {
+    fn parse(args: AttributeArgs) -> syn::Result<Self> {
+        let mut variants = None;
+        let mut derives = vec![];
+        let mut version = None;
+        for item in args {
+            match item {
+                NestedMeta::Meta(Meta::List(list)) if list.path.is_ident("variants") => {
+                    variants = Some(parse_idents(list.nested)?);
+                }
+                NestedMeta::Meta(Meta::List(list)) if list.path.is_ident("derive") => {
+                    derives.extend(parse_paths(list.nested)?);
+                }
+                NestedMeta::Meta(Meta::NameValue(item)) if item.path.is_ident("version") => {
+                    version = Some(parse_str_attribute(&item.lit, "version")?);
+                }
+                item => return Err(syn::Error::new_spanned(item, "unknown `versioned` attribute")),
+            }
+        }
+        let variants = match variants {
+            Some(variants) if !variants.is_empty() => variants,
+            _ => {
+                return Err(syn::Error::new(
+                    proc_macro2::Span::call_site(),
+                    "expected the versions of the type with `#[versioned(variants(...))]`",
+                ))
+            }
+        };
+        let mut errors = Errors::default();
+        for (i, variant) in variants.iter().enumerate() {
+            if variants[..i].contains(variant) {
+                errors.push(variant, format!("the version `{variant}` is given more than once"));
+            }
+        }
+        errors.finish()?;
+        Ok(Self { variants, derives, version })
+    }
+}
+
+// A field of the struct along with the versions it is part of.
+struct VersionedField<'a> {
+    field: &'a Field,
+    // The name of the accessors for the field.
+    ident: &'a Ident,
+    // The name of the field in the structs of the family.
+    name: Ident,
+    // The attributes of the field other than `#[versioned(...)]`.
+    attrs: Vec<&'a Attribute>,
+    // Whether the field is part of each version, in order.
+    in_variant: Vec<bool>,
+}
+
+impl<'a> VersionedField<'a> {
+    fn parse(field: &'a Field, variants: &[Ident]) -> syn::Result<Self> {
+        // SAFETY: the struct was validated to have named fields; qed
+        let ident = field.ident.as_ref().expect("fields are named");
+        let mut name = ident.clone();
+        let mut in_variant = vec![true; variants.len()];
+        let mut attrs = vec![];
+        for attr in &field.attrs {
+            if !attr.path.is_ident("versioned") {
+                attrs.push(attr);
+                continue
+            }
+            let items = match attr.parse_meta()? {
+                Meta::List(list) => list.nested,
+                meta => return Err(syn::Error::new_spanned(meta, "expected `#[versioned(...)]`")),
+            };
+            for item in items {
+                match item {
+                    NestedMeta::Meta(Meta::List(list)) if list.path.is_ident("only") => {
+                        let only = parse_idents(list.nested.clone())?;
+                        if only.is_empty() {
+                            return Err(syn::Error::new_spanned(
+                                list,
+                                "a field must be part of at least one version",
+                            ))
+                        }
+                        for version in &only {
+                            if !variants.contains(version) {
+                                return Err(syn::Error::new_spanned(
+                                    version,
+                                    format!("`{version}` is not a version of this type"),
+                                ))
+                            }
+                        }
+                        in_variant = variants.iter().map(|v| only.contains(v)).collect();
+                    }
+                    NestedMeta::Meta(Meta::NameValue(item)) if item.path.is_ident("rename") => {
+                        name = match &item.lit {
+                            Lit::Str(value) => value.parse()?,
+                            lit => {
+                                return Err(syn::Error::new_spanned(
+                                    lit,
+                                    "expected `#[versioned(rename = \"...\")]`",
+                                ))
+                            }
+                        };
+                    }
+                    item => {
+                        return Err(syn::Error::new_spanned(
+                            item,
+                            "unknown `versioned` field attribute",
+                        ))
+                    }
+                }
+            }
+        }
+        Ok(Self { field, ident, name, attrs, in_variant })
+    }
+
+    fn is_shared(&self) -> bool {
+        self.in_variant.iter().all(|&in_variant| in_variant)
+    }
+}
+
+// The methods of the enum over the family besides the accessors of the fields.
+const METHODS: [&str; 3] = ["version", "deserialize_version", "from"];
+
+// Checks that the family can be declared from the parsed fields: each version must use every
+// generic parameter in its fields, and no accessor can share the name of another method of the
+// enum.
+fn validate_fields(
+    input: &DeriveInput,
+    variants: &[Ident],
+    fields: &[VersionedField<'_>],
+) -> syn::Result<()> {
+    let mut errors = Errors::default();
+    let params = input
+        .generics
+        .lifetimes()
+        .map(|param| {
+            let lifetime = &param.lifetime;
+            (&lifetime.ident, lifetime.to_string(), lifetime.to_token_stream())
+        })
+        .chain(
+            input
+                .generics
+                .type_params()
+                .map(|param| (&param.ident, param.ident.to_string(), param.to_token_stream())),
+        );
+    for (ident, name, param) in params {
+        let without_param = variants
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| {
+                !fields
+                    .iter()
+                    .filter(|f| f.in_variant[*i])
+                    .any(|f| mentions_ident(f.field.ty.to_token_stream(), ident))
+            })
+            .map(|(_, variant)| format!("`{variant}`"))
+            .collect::<Vec<_>>();
+        if !without_param.is_empty() {
+            errors.push(
+                param,
+                format!(
+                    "`{name}` must be used by a field of each version, but is not used by {}",
+                    without_param.join(", ")
+                ),
+            );
+        }
+    }
+    for f in fields {
+        let accessor = f.ident.to_string();
+        let accessor = accessor.trim_start_matches("r#");
+        if METHODS.contains(&accessor) {
+            errors.push(
+                f.ident,
+                format!("the accessor of this field would clash with the method `{accessor}` of the versioned type"),
+            );
+        }
+    }
+    errors.finish()
+}
+
+// Which of the derives of `ssz_rs` the struct has, so the enum over the family can delegate to
+// the impls they generate.
+fn ssz_derives(attrs: &[Attribute]) -> syn::Result<(bool, bool)> {
+    let mut serializable = false;
+    let mut merkleized = false;
+    for attr in attrs.iter().filter(|attr| attr.path.is_ident("derive")) {
+        let items = match attr.parse_meta()? {
+            Meta::List(list) => list.nested,
+            _ => continue,
+        };
+        for path in parse_paths(items)? {
+            match path.segments.last().map(|segment| segment.ident.to_string()).as_deref() {
+                Some("SimpleSerialize") => {
+                    serializable = true;
+                    merkleized = true;
+                }
+                Some("Serializable") => serializable = true,
+                _ => {}
+            }
+        }
+    }
+    Ok((serializable, merkleized))
+}
+
+pub(crate) fn expand(args: AttributeArgs, input: DeriveInput) -> syn::Result<TokenStream> {
+    let args = VersionedArgs::parse(args)?;
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &input.ident,
+                    "only structs with named fields can be `versioned`",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "only structs with named fields can be `versioned`",
+            ))
+        }
+    };
+    let fields = fields
+        .iter()
+        .map(|field| VersionedField::parse(field, &args.variants))
+        .collect::<syn::Result<Vec<_>>>()?;
+    validate_fields(&input, &args.variants, &fields)?;
+    let krate = ContainerAttributes::parse(&input.attrs)?.krate;
+    let (serializable, merkleized) = ssz_derives(&input.attrs)?;
+
+    let name = &input.ident;
+    let vis = &input.vis;
+    let attrs = &input.attrs;
+    let docs = attrs.iter().filter(|attr| attr.path.is_ident("doc"));
+    let generics = &input.generics;
+    let where_clause = &generics.where_clause;
+    let (impl_generics, ty_generics, _) = generics.split_for_impl();
+    let variants = &args.variants;
+    let structs =
+        variants.iter().map(|variant| format_ident!("{}{}", name, variant)).collect::<Vec<_>>();
+
+    let struct_defs = structs.iter().enumerate().map(|(i, struct_name)| {
+        let fields = fields.iter().filter(|f| f.in_variant[i]).map(|f| {
+            let attrs = &f.attrs;
+            let vis = &f.field.vis;
+            let name = &f.name;
+            let ty = &f.field.ty;
+            quote! {
+                #(#attrs)*
+                #vis #name: #ty
+            }
+        });
+        quote! {
+            #(#attrs)*
+            #vis struct #struct_name #generics #where_clause {
+                #(#fields),*
+            }
+        }
+    });
+
+    let enum_derives = (!args.derives.is_empty()).then(|| {
+        let derives = &args.derives;
+        quote!(#[derive(#(#derives),*)])
+    });
+    let enum_def = quote! {
+        #(#docs)*
+        #enum_derives
+        #vis enum #name #generics #where_clause {
+            #(#variants(#structs #ty_generics)),*
+        }
+    };
+
+    let version_def = match &args.version {
+        Some(..) => None,
+        None => {
+            let version = format_ident!("{}Version", name);
+            let doc = format!("The versions of `{name}`.");
+            Some(quote! {
+                #[doc = #doc]
+                #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+                #vis enum #version {
+                    #(#variants),*
+                }
+            })
+        }
+    };
+    let version = match &args.version {
+        Some(version) => quote!(#version),
+        None => {
+            let version = format_ident!("{}Version", name);
+            quote!(#version)
+        }
+    };
+
+    let accessors = fields.iter().map(|f| {
+        let vis = &f.field.vis;
+        let ident = f.ident;
+        let ident_mut = format_ident!("{}_mut", ident.to_string().trim_start_matches("r#"));
+        let name = &f.name;
+        let ty = &f.field.ty;
+        let with_field = variants
+            .iter()
+            .zip(&f.in_variant)
+            .filter(|(_, &in_variant)| in_variant)
+            .map(|(variant, _)| variant)
+            .collect::<Vec<_>>();
+        if f.is_shared() {
+            let doc = format!("The `{name}` of each version.");
+            quote! {
+                #[doc = #doc]
+                #vis fn #ident(&self) -> &#ty {
+                    match self {
+                        #(Self::#with_field(inner) => &inner.#name),*
+                    }
+                }
+
+                #[doc = #doc]
+                #vis fn #ident_mut(&mut self) -> &mut #ty {
+                    match self {
+                        #(Self::#with_field(inner) => &mut inner.#name),*
+                    }
+                }
+            }
+        } else {
+            let doc = format!("The `{name}` of the versions which have it.");
+            quote! {
+                #[doc = #doc]
+                #vis fn #ident(&self) -> Option<&#ty> {
+                    match self {
+                        #(Self::#with_field(inner) => Some(&inner.#name),)*
+                        _ => None,
+                    }
+                }
+
+                #[doc = #doc]
+                #vis fn #ident_mut(&mut self) -> Option<&mut #ty> {
+                    match self {
+                        #(Self::#with_field(inner) => Some(&mut inner.#name),)*
+                        _ => None,
+                    }
+                }
+            }
+        }
+    });
+
+    let from_impls = variants.iter().zip(&structs).map(|(variant, struct_name)| {
+        quote! {
+            impl #impl_generics From<#struct_name #ty_generics> for #name #ty_generics #where_clause {
+                fn from(value: #struct_name #ty_generics) -> Self {
+                    Self::#variant(value)
+                }
+            }
+        }
+    });
+
+    // the generics of an impl which needs `bound` on the struct of each version
+    let bounded_generics = |bound: TokenStream| {
+        let mut generics = generics.clone();
+        generics.make_where_clause().predicates.extend(structs.iter().map(
+            |struct_name| -> WherePredicate {
+                syn::parse_quote!(#struct_name #ty_generics: #bound)
+            },
+        ));
+        generics
+    };
+    // the version is the context to decode the family with, which is forwarded to the fields
+    let deserialize_with_context_impl = {
+        let generics = bounded_generics(quote!(#krate::DeserializeWithContext<#version>));
+        let (impl_generics, _, where_clause) = generics.split_for_impl();
+        quote! {
+            impl #impl_generics #krate::DeserializeWithContext<#version> for #name #ty_generics #where_clause {
+                fn deserialize_with_context(encoding: &[u8], version: &#version) -> Result<Self, #krate::DeserializeError> {
+                    match version {
+                        #(#version::#variants => <#structs #ty_generics as #krate::DeserializeWithContext<#version>>::deserialize_with_context(encoding, version).map(Self::#variants)),*
+                    }
+                }
+
+                fn is_variable_size_with_context(version: &#version) -> bool {
+                    match version {
+                        #(#version::#variants => <#structs #ty_generics as #krate::DeserializeWithContext<#version>>::is_variable_size_with_context(version)),*
+                    }
+                }
+
+                fn size_hint_with_context(version: &#version) -> usize {
+                    match version {
+                        #(#version::#variants => <#structs #ty_generics as #krate::DeserializeWithContext<#version>>::size_hint_with_context(version)),*
+                    }
+                }
+            }
+        }
+    };
+    let serializable_impls = serializable.then(|| {
+        let generics = bounded_generics(quote!(#krate::Serializable));
+        let (impl_generics, _, where_clause) = generics.split_for_impl();
+        let doc = format!("Decode the `{name}` of the given `version` from `encoding`.");
+        quote! {
+            impl #impl_generics #name #ty_generics #where_clause {
+                #[doc = #doc]
+                pub fn deserialize_version(version: #version, encoding: &[u8]) -> Result<Self, #krate::DeserializeError> {
+                    match version {
+                        #(#version::#variants => #krate::Deserialize::deserialize(encoding).map(Self::#variants)),*
+                    }
+                }
+            }
+
+            impl #impl_generics #krate::Serialize for #name #ty_generics #where_clause {
+                fn serialize(&self, buffer: &mut Vec<u8>) -> Result<usize, #krate::SerializeError> {
+                    match self {
+                        #(Self::#variants(inner) => #krate::Serialize::serialize(inner, buffer)),*
+                    }
+                }
+            }
+
+            #deserialize_with_context_impl
+        }
+    });
+    let merkleization_impl = merkleized.then(|| {
+        let generics = bounded_generics(quote!(#krate::SimpleSerialize));
+        let (impl_generics, _, where_clause) = generics.split_for_impl();
+        quote! {
+            impl #impl_generics #krate::Merkleized for #name #ty_generics #where_clause {
+                fn hash_tree_root(&mut self) -> Result<#krate::Node, #krate::MerkleizationError> {
+                    match self {
+                        #(Self::#variants(inner) => #krate::Merkleized::hash_tree_root(inner)),*
+                    }
+                }
+            }
+        }
+    });
+
+    let version_doc = format!("The version of this `{name}`.");
+    Ok(quote! {
+        #(#struct_defs)*
+
+        #enum_def
+
+        #version_def
+
+        impl #impl_generics #name #ty_generics #where_clause {
+            #[doc = #version_doc]
+            #vis fn version(&self) -> #version {
+                match self {
+                    #(Self::#variants(..) => #version::#variants),*
+                }
+            }
+
+            #(#accessors)*
+        }
+
+        #(#from_impls)*
+
+        #serializable_impls
+
+        #merkleization_impl
+    })
+}
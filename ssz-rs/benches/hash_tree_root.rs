@@ -0,0 +1,63 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use ssz_rs::prelude::*;
+
+#[derive(Debug, Default, Clone, SimpleSerialize)]
+struct Checkpoint {
+    epoch: u64,
+    root: Node,
+}
+
+#[derive(Debug, Default, Clone, SimpleSerialize)]
+struct AttestationData {
+    slot: u64,
+    index: u64,
+    beacon_block_root: Node,
+    source: Checkpoint,
+    target: Checkpoint,
+}
+
+#[derive(Debug, Default, Clone, SimpleSerialize)]
+struct Attestation {
+    aggregation_bits: Bitlist<2048>,
+    data: AttestationData,
+    signature: Vector<u8, 96>,
+}
+
+#[derive(Debug, Default, Clone, SimpleSerialize)]
+struct Block {
+    slot: u64,
+    attestations: List<Attestation, 128>,
+    balances: List<u64, 1099511627776>,
+}
+
+fn block() -> Block {
+    let attestation = Attestation {
+        aggregation_bits: Bitlist::try_from(vec![true; 512].as_slice()).unwrap(),
+        data: AttestationData { slot: 1, index: 2, ..Default::default() },
+        signature: Vector::try_from(vec![7u8; 96]).unwrap(),
+    };
+    Block {
+        slot: 23,
+        attestations: List::try_from(vec![attestation; 128]).unwrap(),
+        balances: List::try_from((0..10_000).collect::<Vec<u64>>()).unwrap(),
+    }
+}
+
+fn bench_hash_tree_root_from_bytes(c: &mut Criterion) {
+    let encoding = serialize(&block()).unwrap();
+
+    let mut group = c.benchmark_group("hash_tree_root_of_encoding");
+    group.bench_function("hash_tree_root_from_bytes", |b| {
+        b.iter(|| hash_tree_root_from_bytes::<Block>(black_box(&encoding)).unwrap())
+    });
+    group.bench_function("deserialize_then_hash_tree_root", |b| {
+        b.iter(|| {
+            let mut block = Block::deserialize(black_box(&encoding)).unwrap();
+            block.hash_tree_root().unwrap()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, bench_hash_tree_root_from_bytes);
+criterion_main!(benches);
@@ -128,4 +128,16 @@ fn main() {
     let recovered_value: SerializableStruct =
         deserialize(&expected_encoding).expect("can deserialize");
     assert_eq!(recovered_value, value);
+
+    let value = SingleFieldTestStruct { a: 171 };
+    let encoding = serialize(&value).expect("can serialize");
+    assert_eq!(encoding, [171]);
+    let recovered_value: SingleFieldTestStruct = deserialize(&encoding).expect("can deserialize");
+    assert_eq!(recovered_value, value);
+
+    let value = SmallTestStruct { a: 0x4567, b: 0x0123 };
+    let encoding = serialize(&value).expect("can serialize");
+    assert_eq!(encoding, [0x67, 0x45, 0x23, 0x01]);
+    let recovered_value: SmallTestStruct = deserialize(&encoding).expect("can deserialize");
+    assert_eq!(recovered_value, value);
 }
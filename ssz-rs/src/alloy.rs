@@ -0,0 +1,237 @@
+//! `SimpleSerialize` for the primitive types of the `alloy-primitives` crate.
+//!
+//! `alloy_primitives::U256` is an alias of `ruint::Uint<256, 4>`, which already implements
+//! `SimpleSerialize` and converts to and from `ssz_rs::U256`, so it needs no special support here.
+use crate::{
+    de::{impl_context_free, Deserialize, DeserializeError},
+    error::InstanceError,
+    lib::*,
+    merkleization::{
+        mix_in_length, MerkleizationError, Merkleized, Merkleizer, Node, BYTES_PER_CHUNK,
+    },
+    schema::{HasSchema, Schema},
+    ser::{Serialize, SerializeError},
+    Serializable, SimpleSerialize,
+};
+use alloy_primitives::{Address, Bloom, Bytes, FixedBytes};
+
+// `FixedBytes<N>` is encoded as a `Vector<u8, N>`, by way of the inner array
+impl<const N: usize> Serializable for FixedBytes<N> {
+    fn is_variable_size() -> bool {
+        false
+    }
+
+    fn size_hint() -> usize {
+        N
+    }
+}
+
+impl<const N: usize> Serialize for FixedBytes<N> {
+    fn serialize(&self, buffer: &mut Vec<u8>) -> Result<usize, SerializeError> {
+        self.0.serialize(buffer)
+    }
+}
+
+impl<const N: usize> Deserialize for FixedBytes<N> {
+    fn deserialize(encoding: &[u8]) -> Result<Self, DeserializeError> {
+        <[u8; N]>::deserialize(encoding).map(Self)
+    }
+}
+
+impl_context_free!([const N: usize] FixedBytes<N>);
+
+impl<const N: usize> Merkleized for FixedBytes<N> {
+    fn hash_tree_root(&mut self) -> Result<Node, MerkleizationError> {
+        self.0.hash_tree_root()
+    }
+}
+
+impl<const N: usize> HasSchema for FixedBytes<N> {
+    fn schema() -> Schema {
+        <[u8; N]>::schema()
+    }
+}
+
+impl<const N: usize> SimpleSerialize for FixedBytes<N> {}
+
+// Implement `SimpleSerialize` for a newtype around `FixedBytes`, by way of the inner value
+macro_rules! define_wrapped_fixed_bytes {
+    ($name:ty, $inner:ty) => {
+        impl Serializable for $name {
+            fn is_variable_size() -> bool {
+                false
+            }
+
+            fn size_hint() -> usize {
+                <$inner>::size_hint()
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize(&self, buffer: &mut Vec<u8>) -> Result<usize, SerializeError> {
+                self.0.serialize(buffer)
+            }
+        }
+
+        impl Deserialize for $name {
+            fn deserialize(encoding: &[u8]) -> Result<Self, DeserializeError> {
+                <$inner>::deserialize(encoding).map(<$name>::from)
+            }
+        }
+
+        impl_context_free!([] $name);
+
+        impl Merkleized for $name {
+            fn hash_tree_root(&mut self) -> Result<Node, MerkleizationError> {
+                self.0.hash_tree_root()
+            }
+        }
+
+        impl HasSchema for $name {
+            fn schema() -> Schema {
+                <$inner>::schema()
+            }
+        }
+
+        impl SimpleSerialize for $name {}
+    };
+}
+
+define_wrapped_fixed_bytes!(Address, FixedBytes<20>);
+define_wrapped_fixed_bytes!(Bloom, FixedBytes<256>);
+
+/// `Bytes` encoded as a `List<u8, N>`.
+///
+/// `Bytes` carries no bound of its own, so the caller specifies one with this wrapper, e.g.
+/// `BoundedBytes<32>` for the `extra_data` of an execution payload.
+#[derive(Default, Debug, Clone, PartialEq, Eq, Hash)]
+pub struct BoundedBytes<const N: usize>(pub Bytes);
+
+impl<const N: usize> From<BoundedBytes<N>> for Bytes {
+    fn from(value: BoundedBytes<N>) -> Self {
+        value.0
+    }
+}
+
+impl<const N: usize> TryFrom<Bytes> for BoundedBytes<N> {
+    type Error = (Bytes, InstanceError);
+
+    fn try_from(value: Bytes) -> Result<Self, Self::Error> {
+        if value.len() > N {
+            let len = value.len();
+            return Err((value, InstanceError::Bounded { bound: N, provided: len }))
+        }
+        Ok(Self(value))
+    }
+}
+
+impl<const N: usize> Deref for BoundedBytes<N> {
+    type Target = Bytes;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<const N: usize> Serializable for BoundedBytes<N> {
+    fn is_variable_size() -> bool {
+        true
+    }
+
+    fn size_hint() -> usize {
+        0
+    }
+}
+
+impl<const N: usize> Serialize for BoundedBytes<N> {
+    fn serialize(&self, buffer: &mut Vec<u8>) -> Result<usize, SerializeError> {
+        if self.len() > N {
+            return Err(InstanceError::Bounded { bound: N, provided: self.len() }.into())
+        }
+        buffer.extend_from_slice(&self.0);
+        Ok(self.len())
+    }
+}
+
+impl<const N: usize> Deserialize for BoundedBytes<N> {
+    fn deserialize(encoding: &[u8]) -> Result<Self, DeserializeError> {
+        if encoding.len() > N {
+            return Err(InstanceError::Bounded { bound: N, provided: encoding.len() }.into())
+        }
+        Ok(Self(Bytes::copy_from_slice(encoding)))
+    }
+}
+
+impl_context_free!([const N: usize] BoundedBytes<N>);
+
+impl<const N: usize> Merkleized for BoundedBytes<N> {
+    fn hash_tree_root(&mut self) -> Result<Node, MerkleizationError> {
+        if self.len() > N {
+            return Err(MerkleizationError::InputExceedsLimit(N))
+        }
+        let mut merkleizer = Merkleizer::new(Some(N.div_ceil(BYTES_PER_CHUNK)));
+        merkleizer.write_bytes(&self.0)?;
+        let data_root = merkleizer.finish()?;
+        Ok(mix_in_length(&data_root, self.len()))
+    }
+}
+
+impl<const N: usize> HasSchema for BoundedBytes<N> {
+    fn schema() -> Schema {
+        Schema::List { element: Box::new(Schema::Uint(1)), limit: N }
+    }
+}
+
+impl<const N: usize> SimpleSerialize for BoundedBytes<N> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        list::List,
+        utils::{deserialize, serialize},
+        vector::Vector,
+        U256,
+    };
+    use alloy_primitives::B256;
+
+    #[test]
+    fn test_fixed_bytes() {
+        let mut hash = B256::repeat_byte(0xab);
+        let mut expected = Vector::<u8, 32>::from([0xab; 32]);
+        assert_eq!(serialize(&hash).unwrap(), serialize(&expected).unwrap());
+        assert_eq!(hash.hash_tree_root().unwrap(), expected.hash_tree_root().unwrap());
+        assert_eq!(deserialize::<B256>(&[0xab; 32]).unwrap(), hash);
+        assert!(deserialize::<B256>(&[0xab; 31]).is_err());
+
+        let mut address = Address::repeat_byte(0x11);
+        let mut expected = Vector::<u8, 20>::from([0x11; 20]);
+        assert_eq!(serialize(&address).unwrap(), [0x11; 20]);
+        assert_eq!(address.hash_tree_root().unwrap(), expected.hash_tree_root().unwrap());
+        assert_eq!(Address::schema(), Vector::<u8, 20>::schema());
+
+        let mut bloom = Bloom::repeat_byte(0x01);
+        let mut expected = Vector::<u8, 256>::from([0x01; 256]);
+        assert_eq!(bloom.hash_tree_root().unwrap(), expected.hash_tree_root().unwrap());
+        assert_eq!(deserialize::<Bloom>(&[0x01; 256]).unwrap(), bloom);
+    }
+
+    #[test]
+    fn test_u256() {
+        let mut value = alloy_primitives::U256::from(1234u64);
+        let mut expected = U256::from(value);
+        assert_eq!(value.hash_tree_root().unwrap(), expected.hash_tree_root().unwrap());
+    }
+
+    #[test]
+    fn test_bounded_bytes() {
+        let mut extra_data = BoundedBytes::<32>::try_from(Bytes::from_static(b"ssz")).unwrap();
+        let mut expected = List::<u8, 32>::try_from(b"ssz".to_vec()).unwrap();
+        assert_eq!(serialize(&extra_data).unwrap(), b"ssz");
+        assert_eq!(extra_data.hash_tree_root().unwrap(), expected.hash_tree_root().unwrap());
+        assert_eq!(deserialize::<BoundedBytes<32>>(b"ssz").unwrap(), extra_data);
+
+        assert!(BoundedBytes::<2>::try_from(Bytes::from_static(b"ssz")).is_err());
+        assert!(deserialize::<BoundedBytes<2>>(b"ssz").is_err());
+    }
+}
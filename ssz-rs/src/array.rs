@@ -1,9 +1,13 @@
 use crate::{
-    de::{deserialize_homogeneous_composite, Deserialize, DeserializeError},
+    de::{
+        deserialize_homogeneous_composite, deserialize_homogeneous_composite_with_context,
+        Deserialize, DeserializeError, DeserializeWithContext,
+    },
     error::{InstanceError, TypeError},
     lib::*,
-    merkleization::{elements_to_chunks, merkleize, pack, MerkleizationError, Merkleized, Node},
-    ser::{Serialize, SerializeError, Serializer},
+    merkleization::{merkleize_elements, MerkleizationError, Merkleized, Node},
+    schema::{HasSchema, Schema},
+    ser::{serialize_homogeneous_composite, Serialize, SerializeError},
     Serializable, SimpleSerialize,
 };
 
@@ -28,11 +32,7 @@ where
         if N == 0 {
             return Err(TypeError::InvalidBound(N).into())
         }
-        let mut serializer = Serializer::default();
-        for element in self {
-            serializer.with_element(element)?;
-        }
-        serializer.serialize(buffer)
+        serialize_homogeneous_composite(self, buffer)
     }
 }
 
@@ -41,30 +41,69 @@ where
     T: Serializable,
 {
     fn deserialize(encoding: &[u8]) -> Result<Self, DeserializeError> {
-        if N == 0 {
-            return Err(TypeError::InvalidBound(N).into())
-        }
+        check_length::<N>(encoding, T::is_variable_size(), T::size_hint())?;
+        let elements = deserialize_homogeneous_composite(encoding)?;
+        from_elements(elements)
+    }
+}
+
+impl<T, C, const N: usize> DeserializeWithContext<C> for [T; N]
+where
+    T: Serializable + DeserializeWithContext<C>,
+    C: ?Sized,
+{
+    fn deserialize_with_context(encoding: &[u8], context: &C) -> Result<Self, DeserializeError> {
+        check_length::<N>(
+            encoding,
+            T::is_variable_size_with_context(context),
+            T::size_hint_with_context(context),
+        )?;
+        let elements = deserialize_homogeneous_composite_with_context(encoding, context)?;
+        from_elements(elements)
+    }
+
+    fn is_variable_size_with_context(context: &C) -> bool {
+        T::is_variable_size_with_context(context)
+    }
+
+    fn size_hint_with_context(context: &C) -> usize {
+        T::size_hint_with_context(context) * N
+    }
+}
+
+// Checks the bound `N` is valid and that `encoding` holds exactly `N` elements if they are of a
+// fixed size.
+fn check_length<const N: usize>(
+    encoding: &[u8],
+    is_variable_size: bool,
+    size_hint: usize,
+) -> Result<(), DeserializeError> {
+    if N == 0 {
+        return Err(TypeError::InvalidBound(N).into())
+    }
 
-        if !T::is_variable_size() {
-            let expected_length = N * T::size_hint();
-            if encoding.len() < expected_length {
-                return Err(DeserializeError::ExpectedFurtherInput {
-                    provided: encoding.len(),
-                    expected: expected_length,
-                })
-            }
-            if encoding.len() > expected_length {
-                return Err(DeserializeError::AdditionalInput {
-                    provided: encoding.len(),
-                    expected: expected_length,
-                })
-            }
+    if !is_variable_size {
+        let expected_length = N * size_hint;
+        if encoding.len() < expected_length {
+            return Err(DeserializeError::ExpectedFurtherInput {
+                provided: encoding.len(),
+                expected: expected_length,
+            })
+        }
+        if encoding.len() > expected_length {
+            return Err(DeserializeError::AdditionalInput {
+                provided: encoding.len(),
+                expected: expected_length,
+            })
         }
-        let elements = deserialize_homogeneous_composite(encoding)?;
-        elements.try_into().map_err(|elements: Vec<T>| {
-            InstanceError::Exact { required: N, provided: elements.len() }.into()
-        })
     }
+    Ok(())
+}
+
+fn from_elements<T, const N: usize>(elements: Vec<T>) -> Result<[T; N], DeserializeError> {
+    elements.try_into().map_err(|elements: Vec<T>| {
+        InstanceError::Exact { required: N, provided: elements.len() }.into()
+    })
 }
 
 impl<T, const N: usize> Merkleized for [T; N]
@@ -72,14 +111,7 @@ where
     T: SimpleSerialize,
 {
     fn hash_tree_root(&mut self) -> Result<Node, MerkleizationError> {
-        if T::is_composite_type() {
-            let count = self.len();
-            let chunks = elements_to_chunks(self.iter_mut().enumerate(), count)?;
-            merkleize(&chunks, None)
-        } else {
-            let chunks = pack(self)?;
-            merkleize(&chunks, None)
-        }
+        merkleize_elements(self.iter_mut(), None)
     }
 
     fn is_composite_type() -> bool {
@@ -87,6 +119,15 @@ where
     }
 }
 
+impl<T, const N: usize> HasSchema for [T; N]
+where
+    T: HasSchema,
+{
+    fn schema() -> Schema {
+        Schema::Vector { element: Box::new(T::schema()), length: N }
+    }
+}
+
 impl<T, const N: usize> SimpleSerialize for [T; N] where T: SimpleSerialize {}
 
 #[cfg(test)]
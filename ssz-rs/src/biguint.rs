@@ -0,0 +1,178 @@
+use crate::{
+    de::{impl_context_free, Deserialize, DeserializeError},
+    error::{Error, InstanceError},
+    lib::*,
+    merkleization::{MerkleizationError, Merkleized, Node},
+    schema::{HasSchema, Schema},
+    ser::{Serialize, SerializeError},
+    Serializable, SimpleSerialize, U256,
+};
+use num_bigint::BigUint;
+use ruint::Uint;
+
+/// An unsigned integer represented by 256 bits, backed by a `num_bigint::BigUint`.
+///
+/// NOTE: this type offers no arithmetic; prefer `U256` and use the conversions provided here
+/// at the boundary with code that still uses `BigUint`.
+#[derive(Default, Debug, PartialEq, Eq, Clone, PartialOrd, Ord, Hash)]
+pub struct BigU256(BigUint);
+
+impl BigU256 {
+    pub fn new() -> Self {
+        Self(BigUint::default())
+    }
+
+    pub fn zero() -> Self {
+        Self::default()
+    }
+
+    pub fn try_from_bytes_le(bytes: &[u8]) -> Result<Self, DeserializeError> {
+        Self::deserialize(bytes)
+    }
+
+    pub fn from_bytes_le(bytes: [u8; 32]) -> Self {
+        Self::deserialize(&bytes).unwrap()
+    }
+
+    pub fn to_bytes_le(&self) -> Vec<u8> {
+        let mut bytes = self.0.to_bytes_le();
+        bytes.resize(Self::size_hint(), 0u8);
+        bytes
+    }
+
+    pub fn from_hex(data: &str) -> Option<Self> {
+        let data = data.strip_prefix("0x").unwrap_or(data);
+        BigUint::parse_bytes(data.as_bytes(), 16).map(Self)
+    }
+}
+
+impl From<u64> for BigU256 {
+    fn from(x: u64) -> Self {
+        Self(x.into())
+    }
+}
+
+impl Serializable for BigU256 {
+    fn is_variable_size() -> bool {
+        false
+    }
+
+    fn size_hint() -> usize {
+        32
+    }
+}
+
+impl Serialize for BigU256 {
+    fn serialize(&self, buffer: &mut Vec<u8>) -> Result<usize, SerializeError> {
+        buffer.extend_from_slice(&self.to_bytes_le());
+        Ok(Self::size_hint())
+    }
+}
+
+impl Deserialize for BigU256 {
+    fn deserialize(encoding: &[u8]) -> Result<Self, DeserializeError> {
+        let byte_size = Self::size_hint();
+        if encoding.len() < byte_size {
+            return Err(DeserializeError::ExpectedFurtherInput {
+                provided: encoding.len(),
+                expected: byte_size,
+            })
+        }
+        if encoding.len() > byte_size {
+            return Err(DeserializeError::AdditionalInput {
+                provided: encoding.len(),
+                expected: byte_size,
+            })
+        }
+
+        // SAFETY: index is safe because encoding.len() == byte_size; qed
+        let value = BigUint::from_bytes_le(&encoding[..byte_size]);
+        Ok(Self(value))
+    }
+}
+
+impl_context_free!([] BigU256);
+
+impl Merkleized for BigU256 {
+    fn hash_tree_root(&mut self) -> Result<Node, MerkleizationError> {
+        let data = self.to_bytes_le();
+        let node = Node::try_from(data.as_ref()).expect("is right size");
+        Ok(node)
+    }
+
+    fn is_composite_type() -> bool {
+        false
+    }
+}
+
+impl HasSchema for BigU256 {
+    fn schema() -> Schema {
+        Schema::Uint(Self::size_hint())
+    }
+}
+
+impl SimpleSerialize for BigU256 {}
+
+impl From<U256> for BigU256 {
+    fn from(value: U256) -> Self {
+        Self(BigUint::from_bytes_le(value.as_le_slice()))
+    }
+}
+
+impl TryFrom<BigU256> for U256 {
+    type Error = Error;
+
+    fn try_from(value: BigU256) -> Result<Self, Self::Error> {
+        let bytes = value.0.to_bytes_le();
+        Uint::try_from_le_slice(&bytes).map(Self::from).ok_or_else(|| {
+            // NOTE: the bound is given in bits
+            let provided = value.0.bits() as usize;
+            Error::Instance(InstanceError::Bounded { bound: Uint::<256, 4>::BITS, provided })
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for BigU256 {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let output = format!("{}", self.0);
+        serializer.collect_str(&output)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for BigU256 {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = <String>::deserialize(deserializer)?;
+        let value = s.parse::<BigUint>().map_err(serde::de::Error::custom)?;
+        Ok(Self(value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::serialize;
+
+    #[test]
+    fn convert_to_ruint() {
+        let value = BigU256::from_bytes_le([12u8; 32]);
+        let converted = U256::try_from(value.clone()).unwrap();
+        assert_eq!(converted, U256::from_bytes_le([12u8; 32]));
+        assert_eq!(serialize(&converted).unwrap(), serialize(&value).unwrap());
+        assert_eq!(BigU256::from(converted), value);
+
+        let too_large = BigU256::from_hex(&format!("0x1{}", "0".repeat(64))).unwrap();
+        let err = U256::try_from(too_large).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Instance(InstanceError::Bounded { bound: 256, provided: 257 })
+        ));
+    }
+}
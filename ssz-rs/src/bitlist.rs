@@ -1,10 +1,11 @@
 use crate::{
-    de::{Deserialize, DeserializeError},
+    de::{impl_context_free, Deserialize, DeserializeError},
     error::{Error, InstanceError},
     lib::*,
     merkleization::{
-        merkleize, mix_in_length, pack_bytes, MerkleizationError, Merkleized, Node, BITS_PER_CHUNK,
+        mix_in_length, MerkleizationError, Merkleized, Merkleizer, Node, BITS_PER_CHUNK,
     },
+    schema::{HasSchema, Schema},
     ser::{Serialize, SerializeError},
     Serializable, SimpleSerialize,
 };
@@ -50,7 +51,7 @@ impl<const N: usize> Default for Bitlist<N> {
 
 impl<const N: usize> Bitlist<N> {
     /// Return the bit at `index`. `None` if index is out-of-bounds.
-    pub fn get(&mut self, index: usize) -> Option<bool> {
+    pub fn get(&self, index: usize) -> Option<bool> {
         self.0.get(index).map(|value| *value)
     }
 
@@ -64,11 +65,61 @@ impl<const N: usize> Bitlist<N> {
         })
     }
 
-    fn pack_bits(&self) -> Result<Vec<u8>, MerkleizationError> {
-        let mut data = vec![];
-        let _ = self.serialize_with_length(&mut data, false)?;
-        pack_bytes(&mut data);
-        Ok(data)
+    /// Return the number of bits set to `true`.
+    pub fn count_ones(&self) -> usize {
+        self.0.count_ones()
+    }
+
+    /// Return `true` if no bit is set.
+    pub fn is_zero(&self) -> bool {
+        self.0.not_any()
+    }
+
+    /// Return an iterator over the indices of the bits set to `true`, in increasing order.
+    pub fn iter_ones(&self) -> impl Iterator<Item = usize> + '_ {
+        self.0.iter_ones()
+    }
+
+    /// Return the bits set in either `self` or `other`.
+    /// Return an error if the lists have different lengths.
+    pub fn union(&self, other: &Self) -> Result<Self, Error> {
+        self.check_length(other)?;
+        let mut result = self.clone();
+        *result.0.as_mut_bitslice() |= other.0.as_bitslice();
+        Ok(result)
+    }
+
+    /// Return the bits set in both `self` and `other`.
+    /// Return an error if the lists have different lengths.
+    pub fn intersection(&self, other: &Self) -> Result<Self, Error> {
+        self.check_length(other)?;
+        let mut result = self.clone();
+        *result.0.as_mut_bitslice() &= other.0.as_bitslice();
+        Ok(result)
+    }
+
+    /// Return `true` if no bit is set in both `self` and `other`.
+    /// Return an error if the lists have different lengths.
+    pub fn is_disjoint(&self, other: &Self) -> Result<bool, Error> {
+        self.check_length(other)?;
+        Ok(self.iter_ones().all(|index| !other.0[index]))
+    }
+
+    /// Return `true` if every bit set in `self` is also set in `other`.
+    /// Return an error if the lists have different lengths.
+    pub fn is_subset(&self, other: &Self) -> Result<bool, Error> {
+        self.check_length(other)?;
+        Ok(self.iter_ones().all(|index| other.0[index]))
+    }
+
+    fn check_length(&self, other: &Self) -> Result<(), Error> {
+        if self.len() != other.len() {
+            return Err(Error::Instance(InstanceError::Exact {
+                required: self.len(),
+                provided: other.len(),
+            }))
+        }
+        Ok(())
     }
 
     fn serialize_with_length(
@@ -97,7 +148,7 @@ impl<const N: usize> Bitlist<N> {
     }
 
     fn chunk_count() -> usize {
-        (N + BITS_PER_CHUNK - 1) / BITS_PER_CHUNK
+        N.div_ceil(BITS_PER_CHUNK)
     }
 }
 
@@ -174,14 +225,23 @@ impl<const N: usize> Deserialize for Bitlist<N> {
     }
 }
 
+impl_context_free!([const N: usize] Bitlist<N>);
+
 impl<const N: usize> Merkleized for Bitlist<N> {
     fn hash_tree_root(&mut self) -> Result<Node, MerkleizationError> {
-        let chunks = self.pack_bits()?;
-        let data_root = merkleize(&chunks, Some(Self::chunk_count()))?;
+        let mut merkleizer = Merkleizer::new(Some(Self::chunk_count()));
+        merkleizer.write_bytes(self.as_raw_slice())?;
+        let data_root = merkleizer.finish()?;
         Ok(mix_in_length(&data_root, self.len()))
     }
 }
 
+impl<const N: usize> HasSchema for Bitlist<N> {
+    fn schema() -> Schema {
+        Schema::Bitlist(N)
+    }
+}
+
 impl<const N: usize> SimpleSerialize for Bitlist<N> {}
 
 impl<const N: usize> TryFrom<&[u8]> for Bitlist<N> {
@@ -322,4 +382,27 @@ mod tests {
         let recovered = Bitlist::<COUNT>::deserialize(&buffer).expect("can decode");
         assert_eq!(input, recovered);
     }
+
+    #[test]
+    fn bitlist_operations() {
+        let a = Bitlist::<COUNT>::try_from([true, true, false, false].as_ref()).unwrap();
+        let b = Bitlist::<COUNT>::try_from([false, false, true, false].as_ref()).unwrap();
+        let c = Bitlist::<COUNT>::try_from([true, false, false, false].as_ref()).unwrap();
+
+        let union = a.union(&b).unwrap();
+        assert_eq!(union.count_ones(), 3);
+        assert_eq!(union.iter_ones().collect::<Vec<_>>(), [0, 1, 2]);
+        assert_eq!(union.len(), 4);
+        assert_eq!(a.intersection(&c).unwrap(), c);
+        assert!(a.intersection(&b).unwrap().is_zero());
+        assert!(a.is_disjoint(&b).unwrap());
+        assert!(c.is_subset(&a).unwrap());
+        assert!(!a.is_subset(&c).unwrap());
+        assert!(Bitlist::<COUNT>::default().get(0).is_none());
+
+        let shorter = Bitlist::<COUNT>::try_from([true, true].as_ref()).unwrap();
+        let err = a.union(&shorter).unwrap_err();
+        assert!(matches!(err, Error::Instance(InstanceError::Exact { required: 4, provided: 2 })));
+        assert!(a.is_subset(&shorter).is_err());
+    }
 }
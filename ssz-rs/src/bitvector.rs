@@ -1,35 +1,46 @@
 use crate::{
-    de::{Deserialize, DeserializeError},
+    de::{impl_context_free, Deserialize, DeserializeError},
     error::{Error, InstanceError, TypeError},
     lib::*,
-    merkleization::{merkleize, pack_bytes, MerkleizationError, Merkleized, Node, BITS_PER_CHUNK},
+    merkleization::{
+        MerkleizationError, Merkleized, Merkleizer, Node, BITS_PER_CHUNK, BYTES_PER_CHUNK,
+    },
+    schema::{HasSchema, Schema},
     ser::{Serialize, SerializeError},
     Serializable, SimpleSerialize,
 };
-use bitvec::{
-    field::BitField,
-    prelude::{BitVec, Lsb0},
-};
+use bitvec::prelude::{BitSlice, Lsb0};
 
 const BITS_PER_BYTE: usize = crate::BITS_PER_BYTE as usize;
 
 fn byte_length(bound: usize) -> usize {
-    (bound + BITS_PER_BYTE - 1) / BITS_PER_BYTE
+    bound.div_ceil(BITS_PER_BYTE)
 }
 
-type BitvectorInner = BitVec<u8, Lsb0>;
+// NOTE: the bits of every `Bitvector` are stored inline in a buffer of this many bytes
+const CAPACITY: usize = 128;
+
+type BitvectorInner = BitSlice<u8, Lsb0>;
 
 /// A homogenous collection of a fixed number of boolean values.
 ///
+/// The bits are packed into the first `(N + 7) / 8` bytes of an inline buffer of 128 bytes,
+/// so a `Bitvector` is `Copy`, never allocates and holds at most 1024 bits. A longer
+/// `Bitvector` fails to compile:
+///
+/// ```compile_fail
+/// let bits = ssz_rs::Bitvector::<1025>::default();
+/// ```
+///
 /// NOTE: a `Bitvector` of length `0` is illegal.
 ///
 /// NOTE: once `const_generics` and `const_evaluatable_checked` features stabilize,
-/// this type can use something like
-/// bitvec::array::BitArray<T, {N / 8}> where T: BitRegister, [T; {N / 8}]: BitViewSized
+/// this type can use something like `[u8; {(N + 7) / 8}]` to drop the unused bytes and
+/// the bound on `N`.
 ///
 /// Refer: <https://stackoverflow.com/a/65462213>
-#[derive(PartialEq, Eq, Clone)]
-pub struct Bitvector<const N: usize>(BitvectorInner);
+#[derive(PartialEq, Eq, Clone, Copy, Hash)]
+pub struct Bitvector<const N: usize>([u8; CAPACITY]);
 
 impl<const N: usize> fmt::Debug for Bitvector<N> {
     fn fmt(&self, f: &mut fmt::Formatter) -> Result<(), fmt::Error> {
@@ -55,15 +66,20 @@ impl<const N: usize> Default for Bitvector<N> {
         // SAFETY: there is currently no way to enforce statically
         // that `N` is non-zero with const generics so panics are possible.
         assert!(N > 0);
+        let () = Self::FITS_INLINE;
 
-        Self(BitVec::repeat(false, N))
+        Self([0u8; CAPACITY])
     }
 }
 
 impl<const N: usize> Bitvector<N> {
+    // Referenced by every constructor, so a `Bitvector` too long for the buffer fails to compile.
+    const FITS_INLINE: () =
+        assert!(N <= CAPACITY * BITS_PER_BYTE, "a `Bitvector` holds at most 1024 bits");
+
     /// Return the bit at `index`. `None` if index is out-of-bounds.
-    pub fn get(&mut self, index: usize) -> Option<bool> {
-        self.0.get(index).map(|value| *value)
+    pub fn get(&self, index: usize) -> Option<bool> {
+        (**self).get(index).map(|value| *value)
     }
 
     /// Set the bit at `index` to `value`. Return the previous value
@@ -76,15 +92,70 @@ impl<const N: usize> Bitvector<N> {
         })
     }
 
-    fn pack_bits(&self) -> Result<Vec<u8>, MerkleizationError> {
-        let mut data = vec![];
-        let _ = self.serialize(&mut data)?;
-        pack_bytes(&mut data);
-        Ok(data)
+    /// Return the number of bits set to `true`.
+    pub fn count_ones(&self) -> usize {
+        self.as_bytes().iter().map(|byte| byte.count_ones() as usize).sum()
+    }
+
+    /// Return `true` if no bit is set.
+    pub fn is_zero(&self) -> bool {
+        self.as_bytes().iter().all(|byte| *byte == 0)
+    }
+
+    /// Return an iterator over the indices of the bits set to `true`, in increasing order.
+    pub fn iter_ones(&self) -> impl Iterator<Item = usize> + '_ {
+        (**self).iter_ones()
+    }
+
+    /// Return the bits set in either `self` or `other`.
+    pub fn union(&self, other: &Self) -> Self {
+        let mut result = *self;
+        for (byte, other) in result.0.iter_mut().zip(other.as_bytes()) {
+            *byte |= other;
+        }
+        result
+    }
+
+    /// Return the bits set in both `self` and `other`.
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut result = *self;
+        for (byte, other) in result.0.iter_mut().zip(other.as_bytes()) {
+            *byte &= other;
+        }
+        result
+    }
+
+    /// Return `true` if no bit is set in both `self` and `other`.
+    pub fn is_disjoint(&self, other: &Self) -> bool {
+        self.as_bytes().iter().zip(other.as_bytes()).all(|(a, b)| a & b == 0)
+    }
+
+    /// Return `true` if every bit set in `self` is also set in `other`.
+    pub fn is_subset(&self, other: &Self) -> bool {
+        self.as_bytes().iter().zip(other.as_bytes()).all(|(a, b)| a & !b == 0)
+    }
+
+    /// Move every bit `n` places towards the end, dropping the last `n` bits and clearing
+    /// the first `n` bits, e.g. to record a new epoch in justification bits.
+    /// Return an error if `n` is larger than `N`.
+    pub fn shift_up(&mut self, n: usize) -> Result<(), Error> {
+        if n > N {
+            return Err(Error::Instance(InstanceError::Bounded { bound: N, provided: n }))
+        }
+        if n < N {
+            self.copy_within(..N - n, n);
+        }
+        self[..n].fill(false);
+        Ok(())
+    }
+
+    /// Return the bytes holding the bits, as they are encoded.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0[..byte_length(N)]
     }
 
     fn chunk_count() -> usize {
-        (N + BITS_PER_CHUNK - 1) / BITS_PER_CHUNK
+        N.div_ceil(BITS_PER_CHUNK)
     }
 }
 
@@ -92,13 +163,13 @@ impl<const N: usize> Deref for Bitvector<N> {
     type Target = BitvectorInner;
 
     fn deref(&self) -> &Self::Target {
-        &self.0
+        &BitSlice::from_slice(self.as_bytes())[..N]
     }
 }
 
 impl<const N: usize> DerefMut for Bitvector<N> {
     fn deref_mut(&mut self) -> &mut Self::Target {
-        &mut self.0
+        &mut BitSlice::from_slice_mut(&mut self.0[..byte_length(N)])[..N]
     }
 }
 
@@ -117,12 +188,8 @@ impl<const N: usize> Serialize for Bitvector<N> {
         if N == 0 {
             return Err(TypeError::InvalidBound(N).into())
         }
-        let bytes_to_write = Self::size_hint();
-        buffer.reserve(bytes_to_write);
-        for byte in self.chunks(BITS_PER_BYTE) {
-            buffer.push(byte.load());
-        }
-        Ok(bytes_to_write)
+        buffer.extend_from_slice(self.as_bytes());
+        Ok(Self::size_hint())
     }
 }
 
@@ -131,6 +198,7 @@ impl<const N: usize> Deserialize for Bitvector<N> {
         if N == 0 {
             return Err(TypeError::InvalidBound(N).into())
         }
+        let () = Self::FITS_INLINE;
 
         let expected_length = byte_length(N);
         if encoding.len() < expected_length {
@@ -146,10 +214,8 @@ impl<const N: usize> Deserialize for Bitvector<N> {
             })
         }
 
-        let mut result = Self::default();
-        for (slot, byte) in result.chunks_mut(BITS_PER_BYTE).zip(encoding.iter().copied()) {
-            slot.store_le(byte);
-        }
+        let mut result = Self([0u8; CAPACITY]);
+        result.0[..expected_length].copy_from_slice(encoding);
         let remainder_count = N % BITS_PER_BYTE;
         if remainder_count != 0 {
             let last_byte = encoding.last().unwrap();
@@ -162,10 +228,25 @@ impl<const N: usize> Deserialize for Bitvector<N> {
     }
 }
 
+impl_context_free!([const N: usize] Bitvector<N>);
+
 impl<const N: usize> Merkleized for Bitvector<N> {
     fn hash_tree_root(&mut self) -> Result<Node, MerkleizationError> {
-        let chunks = self.pack_bits()?;
-        merkleize(&chunks, Some(Self::chunk_count()))
+        let bytes = self.as_bytes();
+        if bytes.len() <= BYTES_PER_CHUNK {
+            let mut root = Node::default();
+            root.as_mut()[..bytes.len()].copy_from_slice(bytes);
+            return Ok(root)
+        }
+        let mut merkleizer = Merkleizer::new(Some(Self::chunk_count()));
+        merkleizer.write_bytes(bytes)?;
+        merkleizer.finish()
+    }
+}
+
+impl<const N: usize> HasSchema for Bitvector<N> {
+    fn schema() -> Schema {
+        Schema::Bitvector(N)
     }
 }
 
@@ -202,9 +283,7 @@ impl<const N: usize> serde::Serialize for Bitvector<N> {
     where
         S: serde::Serializer,
     {
-        let mut buf = Vec::with_capacity(byte_length(N));
-        let _ = crate::Serialize::serialize(self, &mut buf).map_err(serde::ser::Error::custom)?;
-        crate::serde::as_hex::serialize(&buf, serializer)
+        crate::serde::as_hex::serialize(self.as_bytes(), serializer)
     }
 }
 
@@ -222,6 +301,7 @@ impl<'de, const N: usize> serde::Deserialize<'de> for Bitvector<N> {
 mod tests {
     use super::*;
     use crate::serialize;
+    use core::mem;
 
     const COUNT: usize = 12;
 
@@ -279,4 +359,52 @@ mod tests {
         let recovered = Bitvector::<COUNT>::deserialize(&buffer).expect("can decode");
         assert_eq!(input, recovered);
     }
+
+    #[test]
+    fn copy_bitvector() {
+        let mut value = Bitvector::<COUNT>::default();
+        value.set(9, true);
+        let mut copy = value;
+        copy.set(10, true);
+        assert_eq!(value.as_bytes(), [0u8, 0b10]);
+        assert_eq!(copy.as_bytes(), [0u8, 0b110]);
+        assert_eq!(copy.count_ones(), 2);
+        assert_eq!(value.len(), COUNT);
+    }
+
+    #[test]
+    fn bitvector_is_packed_inline() {
+        assert_eq!(Bitvector::<1024>::default().as_bytes().len(), 128);
+        assert_eq!(Bitvector::<9>::default().as_bytes().len(), 2);
+        assert_eq!(mem::size_of::<Bitvector<4>>(), CAPACITY);
+        assert_eq!(mem::size_of::<Bitvector<1024>>(), CAPACITY);
+    }
+
+    #[test]
+    fn bitvector_operations() {
+        let a = Bitvector::<12>::try_from(&[true, false, true].repeat(4)[..]).unwrap();
+        let b = Bitvector::<12>::try_from(&[false, false, true].repeat(4)[..]).unwrap();
+        let c = Bitvector::<12>::try_from(&[false, true, false].repeat(4)[..]).unwrap();
+
+        assert_eq!(a.count_ones(), 8);
+        assert_eq!(a.iter_ones().collect::<Vec<_>>(), [0, 2, 3, 5, 6, 8, 9, 11]);
+        assert_eq!(a.union(&c).count_ones(), 12);
+        assert_eq!(a.intersection(&b), b);
+        assert!(a.intersection(&c).is_zero());
+        assert!(a.is_disjoint(&c));
+        assert!(!a.is_disjoint(&b));
+        assert!(b.is_subset(&a));
+        assert!(!a.is_subset(&b));
+    }
+
+    #[test]
+    fn shift_up_bitvector() {
+        let mut bits = Bitvector::<4>::try_from([true, false, true, true].as_ref()).unwrap();
+        bits.shift_up(1).unwrap();
+        assert_eq!(bits.iter_ones().collect::<Vec<_>>(), [1, 3]);
+        assert_eq!(bits.as_bytes(), [0b1010]);
+        bits.shift_up(4).unwrap();
+        assert!(bits.is_zero());
+        assert!(bits.shift_up(5).is_err());
+    }
 }
@@ -1,7 +1,8 @@
 use crate::{
-    de::{Deserialize, DeserializeError},
+    de::{impl_context_free, Deserialize, DeserializeError},
     lib::*,
     merkleization::{MerkleizationError, Merkleized, Node},
+    schema::{HasSchema, Schema},
     ser::{Serialize, SerializeError},
     Serializable, SimpleSerialize,
 };
@@ -14,6 +15,21 @@ impl Serializable for bool {
     fn size_hint() -> usize {
         1
     }
+
+    fn serialize_fixed_sequence(
+        values: &[Self],
+        buffer: &mut Vec<u8>,
+    ) -> Result<usize, SerializeError> {
+        buffer.extend(values.iter().map(|&value| u8::from(value)));
+        Ok(values.len())
+    }
+
+    fn deserialize_fixed_sequence(encoding: &[u8]) -> Result<Vec<Self>, DeserializeError> {
+        if let Some(b) = encoding.iter().find(|&&b| b > 1) {
+            return Err(DeserializeError::InvalidByte(*b))
+        }
+        Ok(encoding.iter().map(|&b| b == 1).collect())
+    }
 }
 
 impl Serialize for bool {
@@ -39,6 +55,8 @@ impl Deserialize for bool {
     }
 }
 
+impl_context_free!([] bool);
+
 impl Merkleized for bool {
     fn hash_tree_root(&mut self) -> Result<Node, MerkleizationError> {
         let mut node = Node::default();
@@ -53,6 +71,12 @@ impl Merkleized for bool {
     }
 }
 
+impl HasSchema for bool {
+    fn schema() -> Schema {
+        Schema::Boolean
+    }
+}
+
 impl SimpleSerialize for bool {}
 
 #[cfg(test)]
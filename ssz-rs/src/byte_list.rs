@@ -0,0 +1,276 @@
+use crate::{
+    de::{impl_context_free, Deserialize, DeserializeError},
+    error::{Error, HexError, InstanceError},
+    lib::*,
+    merkleization::{
+        merkleize, mix_in_length, MerkleizationError, Merkleized, Merkleizer, Node, BYTES_PER_CHUNK,
+    },
+    schema::{HasSchema, Schema},
+    ser::{Serialize, SerializeError},
+    utils::{try_bytes_from_hex_str, write_bytes_to_lower_hex},
+    Serializable, SimpleSerialize,
+};
+
+/// A variable number of bytes, up to `N`, encoded like a `List<u8, N>`.
+///
+/// The bytes are stored contiguously and formatted as `0x`-prefixed hex, e.g. for transactions
+/// or the `extra_data` of an execution payload.
+#[derive(Default, PartialEq, Eq, Clone, Hash, PartialOrd, Ord)]
+pub struct ByteList<const N: usize>(Vec<u8>);
+
+impl<const N: usize> ByteList<N> {
+    /// Return the bytes, as they are encoded.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+
+    /// Append `byte` to the end of the list, or return an error if the list already holds `N`
+    /// bytes.
+    pub fn try_push(&mut self, byte: u8) -> Result<(), Error> {
+        self.try_extend_from_slice(&[byte])
+    }
+
+    /// Append `bytes` to the end of the list. If this would exceed the bound `N`, the list is
+    /// left unchanged and an error is returned.
+    pub fn try_extend_from_slice(&mut self, bytes: &[u8]) -> Result<(), Error> {
+        let provided = self.len() + bytes.len();
+        if provided > N {
+            return Err(Error::Instance(InstanceError::Bounded { bound: N, provided }))
+        }
+        self.0.extend_from_slice(bytes);
+        Ok(())
+    }
+
+    /// Shorten the list to `len` bytes. Has no effect if the list is already shorter.
+    pub fn truncate(&mut self, len: usize) {
+        self.0.truncate(len);
+    }
+
+    pub fn clear(&mut self) {
+        self.0.clear();
+    }
+
+    fn chunk_count() -> usize {
+        N.div_ceil(BYTES_PER_CHUNK)
+    }
+}
+
+impl<const N: usize> fmt::LowerHex for ByteList<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_bytes_to_lower_hex(f, &self.0)
+    }
+}
+
+impl<const N: usize> fmt::Debug for ByteList<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ByteList<{N}>(len={})({self:#x})", self.len())
+    }
+}
+
+impl<const N: usize> fmt::Display for ByteList<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self:#x}")
+    }
+}
+
+impl<const N: usize> FromStr for ByteList<N> {
+    type Err = HexError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let data = try_bytes_from_hex_str(s)?;
+        if data.len() > N {
+            return Err(InstanceError::Bounded { bound: N, provided: data.len() }.into())
+        }
+        Ok(Self(data))
+    }
+}
+
+impl<const N: usize> TryFrom<Vec<u8>> for ByteList<N> {
+    type Error = (Vec<u8>, Error);
+
+    fn try_from(data: Vec<u8>) -> Result<Self, Self::Error> {
+        if data.len() > N {
+            let len = data.len();
+            Err((data, Error::Instance(InstanceError::Bounded { bound: N, provided: len })))
+        } else {
+            Ok(Self(data))
+        }
+    }
+}
+
+impl<const N: usize> TryFrom<&[u8]> for ByteList<N> {
+    type Error = Error;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        if data.len() > N {
+            return Err(Error::Instance(InstanceError::Bounded { bound: N, provided: data.len() }))
+        }
+        Ok(Self(data.to_vec()))
+    }
+}
+
+impl<const N: usize> From<ByteList<N>> for Vec<u8> {
+    fn from(value: ByteList<N>) -> Self {
+        value.0
+    }
+}
+
+impl<const N: usize> AsRef<[u8]> for ByteList<N> {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl<const N: usize> AsMut<[u8]> for ByteList<N> {
+    fn as_mut(&mut self) -> &mut [u8] {
+        &mut self.0
+    }
+}
+
+impl<const N: usize> Deref for ByteList<N> {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<const N: usize> DerefMut for ByteList<N> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<const N: usize> Serializable for ByteList<N> {
+    fn is_variable_size() -> bool {
+        true
+    }
+
+    fn size_hint() -> usize {
+        0
+    }
+}
+
+impl<const N: usize> Serialize for ByteList<N> {
+    fn serialize(&self, buffer: &mut Vec<u8>) -> Result<usize, SerializeError> {
+        if self.len() > N {
+            return Err(InstanceError::Bounded { bound: N, provided: self.len() }.into())
+        }
+        buffer.extend_from_slice(&self.0);
+        Ok(self.len())
+    }
+}
+
+impl<const N: usize> Deserialize for ByteList<N> {
+    fn deserialize(encoding: &[u8]) -> Result<Self, DeserializeError> {
+        if encoding.len() > N {
+            return Err(InstanceError::Bounded { bound: N, provided: encoding.len() }.into())
+        }
+        Ok(Self(encoding.to_vec()))
+    }
+}
+
+impl_context_free!([const N: usize] ByteList<N>);
+
+impl<const N: usize> Merkleized for ByteList<N> {
+    fn hash_tree_root(&mut self) -> Result<Node, MerkleizationError> {
+        if self.len() > N {
+            return Err(MerkleizationError::InputExceedsLimit(N))
+        }
+        let data_root = if self.len() <= BYTES_PER_CHUNK {
+            let mut chunk = Node::default();
+            chunk.as_mut()[..self.len()].copy_from_slice(&self.0);
+            merkleize(chunk.as_ref(), Some(Self::chunk_count()))?
+        } else {
+            let mut merkleizer = Merkleizer::new(Some(Self::chunk_count()));
+            merkleizer.write_bytes(&self.0)?;
+            merkleizer.finish()?
+        };
+        Ok(mix_in_length(&data_root, self.len()))
+    }
+}
+
+impl<const N: usize> HasSchema for ByteList<N> {
+    fn schema() -> Schema {
+        Schema::List { element: Box::new(Schema::Uint(1)), limit: N }
+    }
+}
+
+impl<const N: usize> SimpleSerialize for ByteList<N> {}
+
+#[cfg(feature = "serde")]
+impl<const N: usize> serde::Serialize for ByteList<N> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        crate::serde::as_hex::serialize(self.as_bytes(), serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, const N: usize> serde::Deserialize<'de> for ByteList<N> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        crate::serde::as_hex::deserialize(deserializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{list::List, serialize};
+
+    #[test]
+    fn roundtrip_byte_list() {
+        for len in [0, 3, 32, 33, 100] {
+            let data = (0..len).map(|i| i as u8).collect::<Vec<_>>();
+            let mut value = ByteList::<100>::try_from(data.clone()).unwrap();
+            let mut expected = List::<u8, 100>::try_from(data).unwrap();
+            let encoding = serialize(&value).unwrap();
+            assert_eq!(encoding, serialize(&expected).unwrap());
+            assert_eq!(ByteList::<100>::deserialize(&encoding).unwrap(), value);
+            assert_eq!(value.hash_tree_root().unwrap(), expected.hash_tree_root().unwrap());
+        }
+        assert!(ByteList::<2>::deserialize(&[1, 2, 3]).is_err());
+    }
+
+    #[test]
+    fn mutate_byte_list() {
+        let mut value = ByteList::<4>::try_from([1u8, 2].as_ref()).unwrap();
+        value.try_push(3).unwrap();
+        assert!(value.try_extend_from_slice(&[4, 5]).is_err());
+        assert_eq!(value.as_bytes(), [1, 2, 3]);
+        value.try_extend_from_slice(&[4]).unwrap();
+        assert!(value.try_push(5).is_err());
+        value[0] = 9;
+        value.truncate(2);
+        assert_eq!(value.as_bytes(), [9, 2]);
+    }
+
+    #[test]
+    fn hex_byte_list() {
+        let value = ByteList::<8>::try_from([0xca, 0xfe].as_ref()).unwrap();
+        assert_eq!(value.to_string(), "0xcafe");
+        assert_eq!(format!("{value:?}"), "ByteList<8>(len=2)(0xcafe)");
+        assert_eq!("0xcafe".parse::<ByteList<8>>().unwrap(), value);
+        assert_eq!("0x".parse::<ByteList<8>>().unwrap(), ByteList::default());
+        let err = "0x00112233445566778899".parse::<ByteList<8>>().unwrap_err();
+        assert!(matches!(
+            err,
+            HexError::Instance(InstanceError::Bounded { bound: 8, provided: 10 })
+        ));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_byte_list() {
+        let value = ByteList::<8>::try_from([0xca, 0xfe].as_ref()).unwrap();
+        let json = serde_json::to_string(&value).unwrap();
+        assert_eq!(json, "\"0xcafe\"");
+        assert_eq!(serde_json::from_str::<ByteList<8>>(&json).unwrap(), value);
+        assert!(serde_json::from_str::<ByteList<1>>(&json).is_err());
+    }
+}
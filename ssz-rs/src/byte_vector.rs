@@ -0,0 +1,236 @@
+use crate::{
+    de::{impl_context_free, Deserialize, DeserializeError},
+    error::{Error, HexError, InstanceError, TypeError},
+    lib::*,
+    merkleization::{MerkleizationError, Merkleized, Merkleizer, Node, BYTES_PER_CHUNK},
+    schema::{HasSchema, Schema},
+    ser::{Serialize, SerializeError},
+    utils::{strip_hex_prefix, write_bytes_to_lower_hex},
+    Serializable, SimpleSerialize,
+};
+
+/// A fixed number of bytes, encoded like a `Vector<u8, N>`.
+///
+/// The bytes are stored inline and formatted as `0x`-prefixed hex, e.g. for hashes, public
+/// keys or signatures.
+///
+/// NOTE: a `ByteVector` of length `0` is illegal.
+#[derive(PartialEq, Eq, Clone, Copy, Hash, PartialOrd, Ord)]
+pub struct ByteVector<const N: usize>([u8; N]);
+
+impl<const N: usize> ByteVector<N> {
+    /// Return the bytes, as they are encoded.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl<const N: usize> Default for ByteVector<N> {
+    fn default() -> Self {
+        // SAFETY: there is currently no way to enforce statically
+        // that `N` is non-zero with const generics so panics are possible.
+        assert!(N > 0);
+
+        Self([0u8; N])
+    }
+}
+
+impl<const N: usize> fmt::LowerHex for ByteVector<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write_bytes_to_lower_hex(f, self.0)
+    }
+}
+
+impl<const N: usize> fmt::Debug for ByteVector<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "ByteVector<{N}>({self:#x})")
+    }
+}
+
+impl<const N: usize> fmt::Display for ByteVector<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{self:#x}")
+    }
+}
+
+impl<const N: usize> FromStr for ByteVector<N> {
+    type Err = HexError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut data = [0u8; N];
+        hex::decode_to_slice(strip_hex_prefix(s)?, &mut data)?;
+        Ok(Self(data))
+    }
+}
+
+impl<const N: usize> From<[u8; N]> for ByteVector<N> {
+    fn from(data: [u8; N]) -> Self {
+        Self(data)
+    }
+}
+
+impl<const N: usize> From<ByteVector<N>> for [u8; N] {
+    fn from(value: ByteVector<N>) -> Self {
+        value.0
+    }
+}
+
+impl<const N: usize> TryFrom<&[u8]> for ByteVector<N> {
+    type Error = Error;
+
+    fn try_from(data: &[u8]) -> Result<Self, Self::Error> {
+        data.try_into().map(Self).map_err(|_| {
+            Error::Instance(InstanceError::Exact { required: N, provided: data.len() })
+        })
+    }
+}
+
+impl<const N: usize> AsRef<[u8]> for ByteVector<N> {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl<const N: usize> AsMut<[u8]> for ByteVector<N> {
+    fn as_mut(&mut self) -> &mut [u8] {
+        &mut self.0
+    }
+}
+
+impl<const N: usize> Deref for ByteVector<N> {
+    type Target = [u8];
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl<const N: usize> DerefMut for ByteVector<N> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl<const N: usize> Serializable for ByteVector<N> {
+    fn is_variable_size() -> bool {
+        false
+    }
+
+    fn size_hint() -> usize {
+        N
+    }
+}
+
+impl<const N: usize> Serialize for ByteVector<N> {
+    fn serialize(&self, buffer: &mut Vec<u8>) -> Result<usize, SerializeError> {
+        if N == 0 {
+            return Err(TypeError::InvalidBound(N).into())
+        }
+        buffer.extend_from_slice(&self.0);
+        Ok(N)
+    }
+}
+
+impl<const N: usize> Deserialize for ByteVector<N> {
+    fn deserialize(encoding: &[u8]) -> Result<Self, DeserializeError> {
+        if N == 0 {
+            return Err(TypeError::InvalidBound(N).into())
+        }
+        if encoding.len() < N {
+            return Err(DeserializeError::ExpectedFurtherInput {
+                provided: encoding.len(),
+                expected: N,
+            })
+        }
+        if encoding.len() > N {
+            return Err(DeserializeError::AdditionalInput { provided: encoding.len(), expected: N })
+        }
+        let mut data = [0u8; N];
+        data.copy_from_slice(encoding);
+        Ok(Self(data))
+    }
+}
+
+impl_context_free!([const N: usize] ByteVector<N>);
+
+impl<const N: usize> Merkleized for ByteVector<N> {
+    fn hash_tree_root(&mut self) -> Result<Node, MerkleizationError> {
+        if N <= BYTES_PER_CHUNK {
+            let mut root = Node::default();
+            root.as_mut()[..N].copy_from_slice(&self.0);
+            return Ok(root)
+        }
+        let mut merkleizer = Merkleizer::new(None);
+        merkleizer.write_bytes(&self.0)?;
+        merkleizer.finish()
+    }
+}
+
+impl<const N: usize> HasSchema for ByteVector<N> {
+    fn schema() -> Schema {
+        Schema::Vector { element: Box::new(Schema::Uint(1)), length: N }
+    }
+}
+
+impl<const N: usize> SimpleSerialize for ByteVector<N> {}
+
+#[cfg(feature = "serde")]
+impl<const N: usize> serde::Serialize for ByteVector<N> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        crate::serde::as_hex::serialize(self.as_bytes(), serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, const N: usize> serde::Deserialize<'de> for ByteVector<N> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        crate::serde::as_hex::deserialize(deserializer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{serialize, vector::Vector};
+
+    #[test]
+    fn roundtrip_byte_vector() {
+        let mut value = ByteVector::<48>::from([0xab; 48]);
+        let mut expected = Vector::<u8, 48>::from([0xab; 48]);
+        let encoding = serialize(&value).unwrap();
+        assert_eq!(encoding, serialize(&expected).unwrap());
+        assert_eq!(ByteVector::<48>::deserialize(&encoding).unwrap(), value);
+        assert_eq!(value.hash_tree_root().unwrap(), expected.hash_tree_root().unwrap());
+        assert!(ByteVector::<48>::deserialize(&encoding[1..]).is_err());
+
+        let mut value = ByteVector::<20>::from([0x11; 20]);
+        let mut expected = Vector::<u8, 20>::from([0x11; 20]);
+        assert_eq!(value.hash_tree_root().unwrap(), expected.hash_tree_root().unwrap());
+    }
+
+    #[test]
+    fn hex_byte_vector() {
+        let value = ByteVector::<4>::from([0xde, 0xad, 0xbe, 0xef]);
+        assert_eq!(value.to_string(), "0xdeadbeef");
+        assert_eq!(format!("{value:?}"), "ByteVector<4>(0xdeadbeef)");
+        assert_eq!("0xdeadbeef".parse::<ByteVector<4>>().unwrap(), value);
+        assert!("deadbeef".parse::<ByteVector<4>>().is_err());
+        assert!("0xdeadbe".parse::<ByteVector<4>>().is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_byte_vector() {
+        let value = ByteVector::<4>::from([0xde, 0xad, 0xbe, 0xef]);
+        let json = serde_json::to_string(&value).unwrap();
+        assert_eq!(json, "\"0xdeadbeef\"");
+        assert_eq!(serde_json::from_str::<ByteVector<4>>(&json).unwrap(), value);
+        assert!(serde_json::from_str::<ByteVector<4>>("\"0xdead\"").is_err());
+    }
+}
@@ -3,16 +3,111 @@ mod tests {
     use crate::prelude::*;
 
     #[derive(Default, Debug, PartialEq, Eq, SimpleSerialize)]
+    #[ssz(field_metadata)]
     struct Foo {
         a: u32,
     }
 
+    /// A block body of each fork.
+    #[versioned(variants(Phase0, Altair, Bellatrix), derive(Debug, Clone, PartialEq, Eq))]
+    #[derive(Default, Debug, Clone, PartialEq, Eq, SimpleSerialize)]
+    #[ssz(field_metadata)]
+    struct Body<const N: usize> {
+        slot: u64,
+        graffiti: Vector<u8, 4>,
+        #[versioned(only(Altair, Bellatrix))]
+        sync_bits: Bitvector<N>,
+        #[versioned(only(Altair), rename = "payload")]
+        payload_altair: u8,
+        #[versioned(only(Bellatrix), rename = "payload")]
+        payload_bellatrix: List<u8, 8>,
+    }
+
+    // bytes which can be limited in length when decoding, by a limit given as context
+    #[derive(Default, Debug, PartialEq, Eq)]
+    struct Limited(Vec<u8>);
+
+    impl Serializable for Limited {
+        fn is_variable_size() -> bool {
+            true
+        }
+
+        fn size_hint() -> usize {
+            0
+        }
+    }
+
+    impl Serialize for Limited {
+        fn serialize(&self, buffer: &mut Vec<u8>) -> Result<usize, SerializeError> {
+            buffer.extend_from_slice(&self.0);
+            Ok(self.0.len())
+        }
+    }
+
+    impl Deserialize for Limited {
+        fn deserialize(encoding: &[u8]) -> Result<Self, DeserializeError> {
+            Ok(Self(encoding.to_vec()))
+        }
+    }
+
+    impl DeserializeWithContext<usize> for Limited {
+        fn deserialize_with_context(
+            encoding: &[u8],
+            limit: &usize,
+        ) -> Result<Self, DeserializeError> {
+            if encoding.len() > *limit {
+                return Err(DeserializeError::AdditionalInput {
+                    provided: encoding.len(),
+                    expected: *limit,
+                })
+            }
+            Self::deserialize(encoding)
+        }
+
+        fn is_variable_size_with_context(_: &usize) -> bool {
+            true
+        }
+
+        fn size_hint_with_context(_: &usize) -> usize {
+            0
+        }
+    }
+
+    #[derive(Debug, PartialEq, Eq, Serializable)]
+    enum Reply {
+        Id(u32),
+        Text(Limited),
+    }
+
+    #[derive(Debug, PartialEq, Eq, Serializable)]
+    struct Message {
+        id: u32,
+        payload: Limited,
+        attachments: List<Limited, 4>,
+        reply: Option<Reply>,
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    enum Fork {
+        Early,
+        Late,
+    }
+
+    #[versioned(variants(Early, Late), version = "Fork", derive(Debug, PartialEq, Eq))]
+    #[derive(Default, Debug, PartialEq, Eq, Serializable)]
+    struct Header {
+        slot: u64,
+        #[versioned(only(Late))]
+        index: u16,
+    }
+
     #[derive(Default, Debug, PartialEq, Eq, SimpleSerialize)]
     struct Bar {
         a: List<u32, 128>,
     }
 
     #[derive(Default, Debug, PartialEq, Eq, SimpleSerialize)]
+    #[ssz(field_metadata)]
     struct BasicContainer {
         a: u32,
         d: bool,
@@ -26,6 +121,7 @@ mod tests {
     }
 
     #[derive(Default, Debug, PartialEq, Eq, SimpleSerialize)]
+    #[ssz(field_metadata)]
     struct AnotherContainer {
         a: u32,
         b: bool,
@@ -76,6 +172,109 @@ mod tests {
         d: BasicContainer,
     }
 
+    #[derive(Default, Debug, PartialEq, Eq, SimpleSerialize)]
+    #[ssz(field_metadata)]
+    struct Wrapper<T> {
+        inner: T,
+        n: u64,
+    }
+
+    #[derive(Debug, PartialEq, Eq, SimpleSerialize)]
+    enum GenericUnion<T, U> {
+        A(T),
+        B(Wrapper<U>),
+    }
+
+    trait Spec {
+        type Payload;
+    }
+
+    struct Mainnet;
+
+    impl Spec for Mainnet {
+        type Payload = List<u8, 8>;
+    }
+
+    // `S` is a marker type, so the inferred bound `S: SimpleSerialize` is replaced
+    #[derive(SimpleSerialize)]
+    #[ssz(bound = "S::Payload: ssz_rs::SimpleSerialize")]
+    struct Block<S: Spec> {
+        slot: u64,
+        payload: S::Payload,
+    }
+
+    // `usize` is not an SSZ type, so encode it as a `u16`
+    mod usize_as_u16 {
+        use crate::prelude::*;
+
+        pub fn is_variable_size() -> bool {
+            false
+        }
+
+        pub fn size_hint() -> usize {
+            2
+        }
+
+        pub fn serialize(value: &usize, buffer: &mut Vec<u8>) -> Result<usize, SerializeError> {
+            let bound = u16::MAX as usize;
+            let value = u16::try_from(*value)
+                .map_err(|_| InstanceError::Bounded { bound, provided: *value })?;
+            value.serialize(buffer)
+        }
+
+        pub fn deserialize(encoding: &[u8]) -> Result<usize, DeserializeError> {
+            u16::deserialize(encoding).map(usize::from)
+        }
+
+        pub fn hash_tree_root(value: &mut usize) -> Result<Node, MerkleizationError> {
+            (*value as u16).hash_tree_root()
+        }
+
+        pub fn schema() -> Schema {
+            u16::schema()
+        }
+    }
+
+    #[derive(Default, Debug, PartialEq, Eq, SimpleSerialize)]
+    #[ssz(field_metadata)]
+    struct WithAttributes {
+        a: u32,
+        #[ssz(skip)]
+        cache: Option<u64>,
+        #[ssz(with = "usize_as_u16")]
+        count: usize,
+        b: List<u8, 4>,
+    }
+
+    #[derive(Default, Debug, PartialEq, Eq, SimpleSerialize)]
+    struct ExpectedAttributes {
+        a: u32,
+        count: u16,
+        b: List<u8, 4>,
+    }
+
+    #[derive(Default, Debug, Clone, Copy, PartialEq, Eq, SimpleSerialize)]
+    #[ssz(transparent)]
+    struct Gwei {
+        amount: u64,
+        #[ssz(skip)]
+        cached: bool,
+    }
+
+    #[derive(Default, Debug, PartialEq, Eq, SimpleSerialize)]
+    #[ssz(crate = "crate")]
+    struct WithCratePath(u8);
+
+    #[derive(Default, Debug, PartialEq, Eq, SimpleSerialize)]
+    #[ssz(field_metadata)]
+    struct Pair(u64, List<u8, 32>);
+
+    #[derive(Default, Debug, PartialEq, Eq, SimpleSerialize)]
+    struct NamedPair {
+        a: u64,
+        b: List<u8, 32>,
+    }
+
     #[test]
     fn encode_container() {
         let value = Foo { a: 5u32 };
@@ -222,4 +421,222 @@ mod tests {
         let recovered = SerializableStruct::deserialize(&buffer).expect("can decode");
         assert_eq!(value, recovered);
     }
+
+    #[test]
+    fn can_derive_generic_types() {
+        let mut value = Wrapper { inner: List::<u8, 4>::try_from(vec![1, 2]).unwrap(), n: 3 };
+        let encoding = serialize(&value).unwrap();
+        assert_eq!(Wrapper::<List<u8, 4>>::deserialize(&encoding).unwrap(), value);
+        let root = value.hash_tree_root().unwrap();
+        let mut expected = ExpectedWrapper { inner: value.inner.clone(), n: 3 };
+        assert_eq!(root, expected.hash_tree_root().unwrap());
+        assert_eq!(Wrapper::<u16>::size_hint(), 10);
+
+        let mut value = GenericUnion::<u8, u16>::B(Wrapper { inner: 7, n: 8 });
+        let encoding = serialize(&value).unwrap();
+        assert_eq!(GenericUnion::<u8, u16>::deserialize(&encoding).unwrap(), value);
+        assert!(value.hash_tree_root().is_ok());
+
+        let mut block = Block::<Mainnet> { slot: 1, payload: List::try_from(vec![1, 2]).unwrap() };
+        let encoding = serialize(&block).unwrap();
+        let recovered = Block::<Mainnet>::deserialize(&encoding).unwrap();
+        assert_eq!(recovered.payload, block.payload);
+        assert!(block.hash_tree_root().is_ok());
+    }
+
+    #[derive(SimpleSerialize)]
+    struct ExpectedWrapper {
+        inner: List<u8, 4>,
+        n: u64,
+    }
+
+    #[test]
+    fn can_derive_with_field_attributes() {
+        let mut value = WithAttributes {
+            a: 1,
+            cache: Some(2),
+            count: 3,
+            b: List::try_from(vec![4, 5]).unwrap(),
+        };
+        let mut expected =
+            ExpectedAttributes { a: 1, count: 3, b: List::try_from(vec![4, 5]).unwrap() };
+        let encoding = serialize(&value).unwrap();
+        assert_eq!(encoding, serialize(&expected).unwrap());
+        assert_eq!(WithAttributes::size_hint(), ExpectedAttributes::size_hint());
+        assert_eq!(value.hash_tree_root().unwrap(), expected.hash_tree_root().unwrap());
+
+        let recovered = WithAttributes::deserialize(&encoding).unwrap();
+        assert_eq!(recovered, WithAttributes { cache: None, b: value.b.clone(), ..value });
+
+        let Schema::Container { fields, .. } = WithAttributes::schema() else { unreachable!() };
+        let Schema::Container { fields: expected_fields, .. } = ExpectedAttributes::schema() else {
+            unreachable!()
+        };
+        assert_eq!(fields, expected_fields);
+
+        value.count = usize::MAX;
+        assert!(serialize(&value).is_err());
+    }
+
+    #[test]
+    fn can_derive_transparent_struct() {
+        let mut value = Gwei { amount: 32, cached: true };
+        assert_eq!(serialize(&value).unwrap(), serialize(&32u64).unwrap());
+        assert_eq!(
+            Gwei::deserialize(&32u64.to_le_bytes()).unwrap(),
+            Gwei { amount: 32, cached: false }
+        );
+        assert_eq!(value.hash_tree_root().unwrap(), 32u64.hash_tree_root().unwrap());
+        assert_eq!(Gwei::schema(), u64::schema());
+
+        // packed like the inner type when in a list
+        let mut balances = List::<Gwei, 8>::try_from(vec![value; 3]).unwrap();
+        let mut expected = List::<u64, 8>::try_from(vec![32; 3]).unwrap();
+        assert_eq!(balances.hash_tree_root().unwrap(), expected.hash_tree_root().unwrap());
+
+        let mut value = WithCratePath(3);
+        assert_eq!(value.hash_tree_root().unwrap(), 3u8.hash_tree_root().unwrap());
+    }
+
+    #[test]
+    fn can_derive_tuple_struct_container() {
+        let mut value = Pair(3, List::try_from(vec![1, 2, 3]).unwrap());
+        let mut expected = NamedPair { a: 3, b: List::try_from(vec![1, 2, 3]).unwrap() };
+        let encoding = serialize(&value).unwrap();
+        assert_eq!(encoding, serialize(&expected).unwrap());
+        assert_eq!(Pair::deserialize(&encoding).unwrap(), value);
+        assert_eq!(value.hash_tree_root().unwrap(), expected.hash_tree_root().unwrap());
+        assert!(Pair::is_variable_size());
+
+        let Schema::Container { fields, .. } = Pair::schema() else { unreachable!() };
+        let names = fields.iter().map(|f| f.name).collect::<Vec<_>>();
+        assert_eq!(names, ["0", "1"]);
+    }
+
+    #[test]
+    fn derive_field_metadata() {
+        assert_eq!(AnotherContainer::FIELD_COUNT, 5);
+        assert_eq!(AnotherContainer::FIELD_NAMES, ["a", "b", "c", "d", "e"]);
+        assert_eq!(AnotherContainer::C_CHUNK_INDEX, 2);
+        assert_eq!(AnotherContainer::C_GENERALIZED_INDEX, 10);
+        assert_eq!(AnotherContainer::E_GENERALIZED_INDEX, 12);
+        let schema = AnotherContainer::schema();
+        for (i, name) in AnotherContainer::FIELD_NAMES.into_iter().enumerate() {
+            let expected = schema.generalized_index(&[name.into()]).unwrap();
+            assert_eq!(8 + i, expected);
+        }
+
+        assert_eq!(AnotherContainer::a_offset(), Some(0));
+        assert_eq!(AnotherContainer::b_offset(), Some(4));
+        assert_eq!(AnotherContainer::c_offset(), None);
+        assert_eq!(AnotherContainer::e_offset(), None);
+        assert_eq!(BasicContainer::d_offset(), Some(4));
+
+        // skipped fields are not part of the container
+        assert_eq!(WithAttributes::FIELD_NAMES, ["a", "count", "b"]);
+        assert_eq!(WithAttributes::B_CHUNK_INDEX, 2);
+        assert_eq!(WithAttributes::count_offset(), Some(4));
+
+        assert_eq!(Pair::FIELD_NAMES, ["0", "1"]);
+        assert_eq!(Pair::FIELD_1_GENERALIZED_INDEX, 3);
+        assert_eq!(Foo::A_GENERALIZED_INDEX, 1);
+
+        // a path through nested containers
+        let index = concat_generalized_indices(&[
+            Wrapper::<Foo>::INNER_GENERALIZED_INDEX,
+            Foo::A_GENERALIZED_INDEX,
+        ]);
+        let expected = Wrapper::<Foo>::schema().generalized_index(&["inner".into(), "a".into()]);
+        assert_eq!(index, expected.unwrap());
+    }
+
+    #[test]
+    fn can_derive_versioned_containers() {
+        assert_eq!(BodyPhase0::<2>::FIELD_NAMES, ["slot", "graffiti"]);
+        assert_eq!(BodyAltair::<2>::FIELD_NAMES, ["slot", "graffiti", "sync_bits", "payload"]);
+        assert_eq!(BodyBellatrix::<2>::FIELD_NAMES, ["slot", "graffiti", "sync_bits", "payload"]);
+
+        let inner = BodyBellatrix::<2> {
+            slot: 32,
+            graffiti: Vector::try_from(vec![1, 2, 3, 4]).unwrap(),
+            sync_bits: Bitvector::try_from([true, false].as_ref()).unwrap(),
+            payload: List::try_from(vec![5, 6]).unwrap(),
+        };
+        let mut value = Body::from(inner.clone());
+        assert_eq!(value.version(), BodyVersion::Bellatrix);
+        assert_eq!(*value.slot(), 32);
+        assert_eq!(value.graffiti().as_ref(), [1, 2, 3, 4]);
+        assert_eq!(value.sync_bits(), Some(&inner.sync_bits));
+        assert_eq!(value.payload_altair(), None);
+        assert_eq!(value.payload_bellatrix(), Some(&inner.payload));
+        *value.slot_mut() += 1;
+        assert_eq!(*value.slot(), 33);
+
+        let mut inner = BodyBellatrix { slot: 33, ..inner };
+        let mut encoding = vec![];
+        value.serialize(&mut encoding).unwrap();
+        assert_eq!(encoding, serialize(&inner).unwrap());
+        let recovered = Body::deserialize_version(BodyVersion::Bellatrix, &encoding).unwrap();
+        assert_eq!(recovered, value);
+        assert!(Body::<2>::deserialize_version(BodyVersion::Phase0, &encoding).is_err());
+        assert_eq!(value.hash_tree_root().unwrap(), inner.hash_tree_root().unwrap());
+
+        let recovered = Body::deserialize_with_context(&encoding, &BodyVersion::Bellatrix).unwrap();
+        assert_eq!(recovered, value);
+        assert!(Body::<2>::is_variable_size_with_context(&BodyVersion::Bellatrix));
+        assert!(!Body::<2>::is_variable_size_with_context(&BodyVersion::Phase0));
+        assert_eq!(Body::<2>::size_hint_with_context(&BodyVersion::Phase0), 12);
+
+        let value = Body::Phase0(BodyPhase0::<2>::default());
+        assert_eq!(value.version(), BodyVersion::Phase0);
+        assert_eq!(value.sync_bits(), None);
+
+        // the versions of a family can be those of a type shared with other families
+        let value = Header::from(HeaderLate { slot: 3, index: 7 });
+        assert_eq!(value.version(), Fork::Late);
+        assert_eq!(value.index(), Some(&7));
+        let mut encoding = vec![];
+        value.serialize(&mut encoding).unwrap();
+        assert_eq!(encoding, [3, 0, 0, 0, 0, 0, 0, 0, 7, 0]);
+        assert_eq!(Header::deserialize_version(Fork::Late, &encoding).unwrap(), value);
+        let recovered = Header::deserialize_version(Fork::Early, &encoding[..8]).unwrap();
+        assert_eq!(recovered.version(), Fork::Early);
+    }
+
+    #[test]
+    fn can_deserialize_with_context() {
+        let value = Message {
+            id: 1,
+            payload: Limited(vec![1, 2]),
+            attachments: List::try_from(vec![Limited(vec![3]), Limited(vec![4, 5, 6])]).unwrap(),
+            reply: Some(Reply::Text(Limited(vec![7]))),
+        };
+        let encoding = serialize(&value).unwrap();
+        assert_eq!(Message::deserialize(&encoding).unwrap(), value);
+        assert_eq!(Message::deserialize_with_context(&encoding, &3usize).unwrap(), value);
+        assert!(Message::is_variable_size_with_context(&3usize));
+
+        // the context is forwarded to the elements of collections
+        let result = Message::deserialize_with_context(&encoding, &2usize);
+        assert!(matches!(
+            result,
+            Err(DeserializeError::AdditionalInput { provided: 3, expected: 2 })
+        ));
+
+        // ... and to the variants of unions
+        let value = Message { reply: Some(Reply::Text(Limited(vec![7, 8, 9, 10]))), ..value };
+        let encoding = serialize(&value).unwrap();
+        let result = Message::deserialize_with_context(&encoding, &3usize);
+        assert!(matches!(
+            result,
+            Err(DeserializeError::AdditionalInput { provided: 4, expected: 3 })
+        ));
+
+        // types which do not depend on any context ignore it
+        assert_eq!(u32::deserialize_with_context(&[1, 0, 0, 0], &()).unwrap(), 1);
+        assert_eq!(<Vector<u16, 3> as DeserializeWithContext<str>>::size_hint_with_context(""), 6);
+        let value = Foo { a: 3 };
+        let recovered = Foo::deserialize_with_context(&serialize(&value).unwrap(), &"any").unwrap();
+        assert_eq!(recovered, value);
+    }
 }
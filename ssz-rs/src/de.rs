@@ -67,6 +67,53 @@ pub trait Deserialize {
         Self: Sized;
 }
 
+/// A data structure that can be deserialized using SSZ given some `context`, such as the active
+/// fork, a preset or limits on decoding, for types whose encoding depends on state only known at
+/// runtime.
+///
+/// `Deserialize` is the special case without any context: the types of this crate implementing it
+/// also implement this trait for every context, which they ignore, while collections like `List`
+/// and `Vector` (and derived containers and unions) forward the context to their elements.
+pub trait DeserializeWithContext<C: ?Sized>: Sized {
+    /// Deserialize this value from the given SSZ-encoded buffer, using `context`.
+    fn deserialize_with_context(encoding: &[u8], context: &C) -> Result<Self, DeserializeError>;
+
+    /// Is the encoding of this type variable size, given `context`?
+    fn is_variable_size_with_context(context: &C) -> bool;
+
+    /// Expected number of bytes for the encoding of this type given `context`, or 0 if unknown
+    /// ahead of time.
+    fn size_hint_with_context(context: &C) -> usize;
+}
+
+// Implements `DeserializeWithContext` for each of the given types by way of its `Deserialize` and
+// `Serializable` impls, ignoring the context. Generic parameters of the type go in brackets, e.g.
+// `impl_context_free!([const N: usize] Bitlist<N>)`.
+macro_rules! impl_context_free {
+    ($([$($generics:tt)*] $type:ty),* $(,)?) => {
+        $(
+            impl<C: ?Sized, $($generics)*> $crate::DeserializeWithContext<C> for $type {
+                fn deserialize_with_context(
+                    encoding: &[u8],
+                    _: &C,
+                ) -> Result<Self, $crate::DeserializeError> {
+                    <Self as $crate::Deserialize>::deserialize(encoding)
+                }
+
+                fn is_variable_size_with_context(_: &C) -> bool {
+                    <Self as $crate::Serializable>::is_variable_size()
+                }
+
+                fn size_hint_with_context(_: &C) -> usize {
+                    <Self as $crate::Serializable>::size_hint()
+                }
+            }
+        )*
+    };
+}
+
+pub(crate) use impl_context_free;
+
 fn deserialize_fixed_homogeneous_composite<T>(encoding: &[u8]) -> Result<Vec<T>, DeserializeError>
 where
     T: Serializable,
@@ -74,20 +121,14 @@ where
     // NOTE: Callers have already validated `encoding` is correctly sized
     debug_assert_eq!(encoding.len() % T::size_hint(), 0);
 
-    let mut elements = vec![];
-    for chunk in encoding.chunks_exact(T::size_hint()) {
-        let element = T::deserialize(chunk)?;
-        elements.push(element);
-    }
-    Ok(elements)
+    T::deserialize_fixed_sequence(encoding)
 }
 
+// `deserialize` decodes an element from the slice holding its encoding.
 fn deserialize_variable_homogeneous_composite<T>(
     encoding: &[u8],
-) -> Result<Vec<T>, DeserializeError>
-where
-    T: Deserialize,
-{
+    deserialize: impl Fn(&[u8]) -> Result<T, DeserializeError>,
+) -> Result<Vec<T>, DeserializeError> {
     if encoding.is_empty() {
         return Ok(vec![])
     }
@@ -106,7 +147,7 @@ where
             expected: offsets_len,
         })
     }
-    if offsets_len % BYTES_PER_LENGTH_OFFSET != 0 {
+    if !offsets_len.is_multiple_of(BYTES_PER_LENGTH_OFFSET) {
         return Err(DeserializeError::InvalidOffsetsLength(offsets_len))
     }
 
@@ -127,7 +168,7 @@ where
         }
 
         // SAFETY: index is safe because start <= end; qed
-        let element = T::deserialize(&encoding[start..end])?;
+        let element = deserialize(&encoding[start..end])?;
         result.push(element);
     }
     Ok(result)
@@ -138,12 +179,33 @@ where
     T: Serializable,
 {
     if T::is_variable_size() {
-        deserialize_variable_homogeneous_composite(encoding)
+        deserialize_variable_homogeneous_composite(encoding, T::deserialize)
     } else {
         deserialize_fixed_homogeneous_composite(encoding)
     }
 }
 
+// Like `deserialize_homogeneous_composite` but decoding each element with `context`.
+pub fn deserialize_homogeneous_composite_with_context<T, C>(
+    encoding: &[u8],
+    context: &C,
+) -> Result<Vec<T>, DeserializeError>
+where
+    T: DeserializeWithContext<C>,
+    C: ?Sized,
+{
+    let deserialize = |encoding: &[u8]| T::deserialize_with_context(encoding, context);
+    if T::is_variable_size_with_context(context) {
+        deserialize_variable_homogeneous_composite(encoding, deserialize)
+    } else {
+        // NOTE: Callers have already validated `encoding` is correctly sized
+        let size = T::size_hint_with_context(context);
+        debug_assert_eq!(encoding.len() % size, 0);
+
+        encoding.chunks_exact(size).map(deserialize).collect()
+    }
+}
+
 #[derive(Debug)]
 enum Segment {
     Fixed(usize, usize),
@@ -167,8 +229,19 @@ pub struct ContainerDeserializer {
 impl ContainerDeserializer {
     // NOTE: segments must be parsed in order following the order of the fields of the container.
     pub fn parse<T: Serializable>(&mut self, encoding: &[u8]) -> Result<(), DeserializeError> {
+        self.parse_segment(encoding, T::is_variable_size(), T::size_hint())
+    }
+
+    // Like `parse` but for a segment whose type is described by `is_variable_size` and
+    // `size_hint`, rather than by a `Serializable` type.
+    pub fn parse_segment(
+        &mut self,
+        encoding: &[u8],
+        is_variable_size: bool,
+        size_hint: usize,
+    ) -> Result<(), DeserializeError> {
         let start = self.total_bytes_read;
-        if T::is_variable_size() {
+        if is_variable_size {
             let end = start + BYTES_PER_LENGTH_OFFSET;
 
             let target =
@@ -205,7 +278,7 @@ impl ContainerDeserializer {
             self.offsets.push(next_offset);
             self.segments.push(Segment::Offset);
         } else {
-            let encoded_length = T::size_hint();
+            let encoded_length = size_hint;
             let end = self.total_bytes_read + encoded_length;
             if encoding.len() < self.total_bytes_read {
                 return Err(DeserializeError::ExpectedFurtherInput {
@@ -0,0 +1,372 @@
+use crate::{
+    error::Error,
+    lib::*,
+    merkleization::{merkleize, mix_in_length, mix_in_selector, Node},
+    schema::{element_spans, field_spans, HasSchema, PathElement, Schema},
+    utils::{serialize, write_bytes_to_lower_hex},
+    SimpleSerialize,
+};
+
+/// One side of a `Difference`: the hash tree root of a value along with its encoding if the
+/// value is a leaf, i.e. a basic value, a bitfield or the length of a list.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub root: Node,
+    pub encoding: Option<Vec<u8>>,
+}
+
+impl Entry {
+    fn new(schema: &Schema, tree: &Tree<'_>) -> Self {
+        let is_leaf =
+            schema.is_basic() || matches!(schema, Schema::Bitvector(..) | Schema::Bitlist(..));
+        Self { root: tree.root, encoding: is_leaf.then(|| tree.encoding.to_vec()) }
+    }
+}
+
+impl Display for Entry {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match &self.encoding {
+            Some(encoding) => {
+                write!(f, "0x")?;
+                write_bytes_to_lower_hex(f, encoding)
+            }
+            None => write!(f, "{}", self.root),
+        }
+    }
+}
+
+/// A value that differs between two SSZ values of the same type.
+///
+/// `old` is `None` if the value only exists in the new value, e.g. an element appended to a
+/// `List`, and `new` is `None` if the value only exists in the old value.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Difference {
+    /// The path from the outermost value to the value that differs.
+    pub path: Vec<PathElement>,
+    /// The type of the value that differs.
+    pub schema: Schema,
+    pub old: Option<Entry>,
+    pub new: Option<Entry>,
+}
+
+impl Display for Difference {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "(root)")?;
+        }
+        for element in &self.path {
+            write!(f, "{element}")?;
+        }
+        write!(f, ": ")?;
+        match &self.old {
+            Some(entry) => write!(f, "{entry}")?,
+            None => write!(f, "(absent)")?,
+        }
+        write!(f, " -> ")?;
+        match &self.new {
+            Some(entry) => write!(f, "{entry}"),
+            None => write!(f, "(absent)"),
+        }
+    }
+}
+
+/// `diff` reports every node of the merkle tree of `old` whose root differs from the node at the
+/// same path in the merkle tree of `new`, walking both trees from the top down.
+///
+/// Subtrees with equal roots are skipped, so the report is the path from the outermost value to
+/// each innermost value that differs, e.g. a basic value, a bitfield, the length of a list or a
+/// whole union if the selector changed, along with every enclosing value on the way there. The
+/// differences are in the order the values appear in the encoding, with an enclosing value
+/// before the values inside of it.
+///
+/// ```
+/// # use ssz_rs::prelude::*;
+/// #[derive(Default, Clone, SimpleSerialize)]
+/// struct Foo {
+///     a: u16,
+///     b: List<u8, 4>,
+/// }
+///
+/// let old = Foo { a: 1, b: List::try_from(vec![1, 2]).unwrap() };
+/// let mut new = old.clone();
+/// new.b[1] = 3;
+/// let differences = diff(&old, &new).unwrap();
+/// assert_eq!(differences.len(), 3);
+/// assert_eq!(differences[0].new.as_ref().unwrap().root, new.clone().hash_tree_root().unwrap());
+/// assert_eq!(differences[1].path, [PathElement::from("b")]);
+/// assert_eq!(differences[2].to_string(), ".b[1]: 0x02 -> 0x03");
+/// ```
+pub fn diff<T: SimpleSerialize + HasSchema>(old: &T, new: &T) -> Result<Vec<Difference>, Error> {
+    let old = serialize(old)?;
+    let new = serialize(new)?;
+    diff_encodings(&T::schema(), &old, &new)
+}
+
+/// `diff_encodings` is like `diff` but compares the encodings of two values of the type
+/// described by `schema`.
+pub fn diff_encodings(schema: &Schema, old: &[u8], new: &[u8]) -> Result<Vec<Difference>, Error> {
+    let old = Tree::new(schema, old)?;
+    let new = Tree::new(schema, new)?;
+    let mut differences = vec![];
+    let mut path = vec![];
+    visit(schema, &old, &new, &mut path, &mut differences)?;
+    Ok(differences)
+}
+
+// The merkle tree of a value, down to the roots of its basic values or bitfields.
+//
+// The roots of all values are computed once, bottom up, so that the walk in `visit` can
+// compare subtrees by their roots. The elements of a sequence of basic values are not kept as
+// they are cheap to recover from the encoding.
+struct Tree<'a> {
+    encoding: &'a [u8],
+    root: Node,
+    // the fields of a container, the elements of a sequence of composite values or the payload
+    // of a union
+    children: Vec<Tree<'a>>,
+}
+
+impl<'a> Tree<'a> {
+    fn new(schema: &Schema, encoding: &'a [u8]) -> Result<Self, Error> {
+        let (root, children) = match schema {
+            Schema::Container { fields, .. } => {
+                let children = field_spans(fields, encoding)?
+                    .into_iter()
+                    .zip(fields)
+                    .map(|(span, field)| Tree::new(&field.schema, &encoding[span]))
+                    .collect::<Result<Vec<_>, _>>()?;
+                (merkleize(&chunks(&children), None)?, children)
+            }
+            Schema::Vector { element, .. } | Schema::List { element, .. }
+                if !element.is_basic() =>
+            {
+                let children = element_spans(element, encoding)?
+                    .into_iter()
+                    .map(|span| Tree::new(element, &encoding[span]))
+                    .collect::<Result<Vec<_>, _>>()?;
+                schema.check_element_count(children.len())?;
+                let root = match schema {
+                    Schema::List { limit, .. } => {
+                        mix_in_length(&merkleize(&chunks(&children), Some(*limit))?, children.len())
+                    }
+                    _ => merkleize(&chunks(&children), None)?,
+                };
+                (root, children)
+            }
+            Schema::Union { variants, .. } => {
+                let payload = encoding.split_first().and_then(|(selector, payload)| {
+                    let variant = variants.iter().find(|v| v.selector == *selector)?;
+                    Some((*selector, variant.schema.as_ref()?, payload))
+                });
+                match payload {
+                    Some((selector, schema, payload)) => {
+                        let child = Tree::new(schema, payload)?;
+                        (mix_in_selector(&child.root, selector as usize), vec![child])
+                    }
+                    None => (schema.hash_tree_root_of(encoding)?, vec![]),
+                }
+            }
+            _ => (schema.hash_tree_root_of(encoding)?, vec![]),
+        };
+        Ok(Self { encoding, root, children })
+    }
+
+    // Returns the trees of the elements of a sequence of basic values.
+    fn leaves(element: &Schema, encoding: &'a [u8]) -> Result<Vec<Self>, Error> {
+        element_spans(element, encoding)?
+            .into_iter()
+            .map(|span| Tree::new(element, &encoding[span]))
+            .collect()
+    }
+}
+
+fn chunks(trees: &[Tree<'_>]) -> Vec<u8> {
+    trees.iter().flat_map(|tree| tree.root.as_ref().iter().copied()).collect()
+}
+
+fn record(
+    schema: &Schema,
+    old: Option<&Tree<'_>>,
+    new: Option<&Tree<'_>>,
+    path: &[PathElement],
+    differences: &mut Vec<Difference>,
+) {
+    differences.push(Difference {
+        path: path.to_vec(),
+        schema: schema.clone(),
+        old: old.map(|tree| Entry::new(schema, tree)),
+        new: new.map(|tree| Entry::new(schema, tree)),
+    });
+}
+
+fn visit(
+    schema: &Schema,
+    old: &Tree<'_>,
+    new: &Tree<'_>,
+    path: &mut Vec<PathElement>,
+    differences: &mut Vec<Difference>,
+) -> Result<(), Error> {
+    if old.root == new.root {
+        return Ok(())
+    }
+    record(schema, Some(old), Some(new), path, differences);
+    match schema {
+        Schema::Container { fields, .. } => {
+            for (field, (old, new)) in fields.iter().zip(old.children.iter().zip(&new.children)) {
+                path.push(PathElement::from(field.name));
+                visit(&field.schema, old, new, path, differences)?;
+                path.pop();
+            }
+        }
+        Schema::Vector { element, .. } | Schema::List { element, .. } => {
+            let old_leaves;
+            let new_leaves;
+            let (old_elements, new_elements) = if element.is_basic() {
+                old_leaves = Tree::leaves(element, old.encoding)?;
+                new_leaves = Tree::leaves(element, new.encoding)?;
+                (&old_leaves, &new_leaves)
+            } else {
+                (&old.children, &new.children)
+            };
+            if old_elements.len() != new_elements.len() {
+                let old_len = (old_elements.len() as u64).to_le_bytes();
+                let new_len = (new_elements.len() as u64).to_le_bytes();
+                let length = Schema::Uint(8);
+                path.push(PathElement::Length);
+                record(
+                    &length,
+                    Some(&Tree::new(&length, &old_len)?),
+                    Some(&Tree::new(&length, &new_len)?),
+                    path,
+                    differences,
+                );
+                path.pop();
+            }
+            for i in 0..old_elements.len().max(new_elements.len()) {
+                path.push(PathElement::Index(i));
+                match (old_elements.get(i), new_elements.get(i)) {
+                    (Some(old), Some(new)) => visit(element, old, new, path, differences)?,
+                    (old, new) => record(element, old, new, path, differences),
+                }
+                path.pop();
+            }
+        }
+        Schema::Union { variants, .. } => {
+            // the payloads are only comparable if both values are of the same variant
+            if let ([old_payload], [new_payload]) = (&old.children[..], &new.children[..]) {
+                let selector = old.encoding[0];
+                if selector == new.encoding[0] {
+                    // SAFETY: the variant exists as `Tree::new` found its payload schema; qed
+                    let variant =
+                        variants.iter().find(|v| v.selector == selector).expect("variant exists");
+                    if let Some(payload) = &variant.schema {
+                        path.push(PathElement::from(variant.name));
+                        visit(payload, old_payload, new_payload, path, differences)?;
+                        path.pop();
+                    }
+                }
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[derive(Debug, Default, Clone, PartialEq, Eq, SimpleSerialize)]
+    struct Inner {
+        a: u8,
+        b: List<u16, 8>,
+    }
+
+    #[derive(Debug, Default, Clone, PartialEq, Eq, SimpleSerialize)]
+    struct Outer {
+        x: u32,
+        inner: Inner,
+        inners: List<Inner, 4>,
+        bits: Bitvector<4>,
+        choice: Option<u8>,
+    }
+
+    fn path(elements: &[PathElement]) -> String {
+        elements.iter().map(|e| e.to_string()).collect()
+    }
+
+    #[test]
+    fn test_diff_equal() {
+        let value = Outer::default();
+        assert!(diff(&value, &value.clone()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_diff() {
+        let mut old = Outer {
+            x: 1,
+            inner: Inner { a: 2, b: List::try_from(vec![1, 2, 3]).unwrap() },
+            inners: List::try_from(vec![Inner::default()]).unwrap(),
+            bits: Bitvector::try_from([true, false, false, true].as_ref()).unwrap(),
+            choice: Some(3),
+        };
+        let mut new = old.clone();
+        new.inner.b[2] = 33;
+        new.inner.b.try_push(4).unwrap();
+        new.inners.try_push(Inner { a: 1, ..Default::default() }).unwrap();
+        new.choice = None;
+
+        let differences = diff(&old, &new).unwrap();
+        let paths = differences.iter().map(|d| path(&d.path)).collect::<Vec<_>>();
+        assert_eq!(
+            paths,
+            [
+                "",
+                ".inner",
+                ".inner.b",
+                ".inner.b.len",
+                ".inner.b[2]",
+                ".inner.b[3]",
+                ".inners",
+                ".inners.len",
+                ".inners[1]",
+                ".choice"
+            ]
+        );
+
+        assert_eq!(differences[0].old.as_ref().unwrap().root, old.hash_tree_root().unwrap());
+        assert_eq!(differences[0].new.as_ref().unwrap().root, new.hash_tree_root().unwrap());
+        let old_root = old.inner.hash_tree_root().unwrap();
+        let new_root = new.inner.hash_tree_root().unwrap();
+        assert_eq!(differences[1].to_string(), format!(".inner: {old_root} -> {new_root}"));
+        assert_eq!(differences[4].to_string(), ".inner.b[2]: 0x0300 -> 0x2100");
+        assert_eq!(differences[5].to_string(), ".inner.b[3]: (absent) -> 0x0400");
+        let appended = differences[8].new.as_ref().unwrap();
+        assert_eq!(appended.root, new.inners[1].hash_tree_root().unwrap());
+        assert_eq!(appended.encoding, None);
+        assert_eq!(differences[9].schema, Option::<u8>::schema());
+        assert_eq!(differences[9].old.as_ref().unwrap().root, old.choice.hash_tree_root().unwrap());
+        assert_eq!(differences[9].new.as_ref().unwrap().root, new.choice.hash_tree_root().unwrap());
+    }
+
+    #[test]
+    fn test_diff_skips_equal_subtrees() {
+        let old = Outer { x: 1, ..Default::default() };
+        let new = Outer { x: 2, ..Default::default() };
+        let differences = diff(&old, &new).unwrap();
+        let paths = differences.iter().map(|d| path(&d.path)).collect::<Vec<_>>();
+        assert_eq!(paths, ["", ".x"]);
+    }
+
+    #[test]
+    fn test_diff_union_payload() {
+        let old = Some(Inner::default());
+        let new = Some(Inner { a: 7, ..Default::default() });
+        let differences = diff(&old, &new).unwrap();
+        let paths = differences.iter().map(|d| path(&d.path)).collect::<Vec<_>>();
+        assert_eq!(paths, ["", ".Some", ".Some.a"]);
+        assert_eq!(differences[2].to_string(), ".Some.a: 0x00 -> 0x07");
+        assert_eq!(differences[2].schema, Schema::Uint(1));
+    }
+}
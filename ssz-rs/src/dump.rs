@@ -0,0 +1,633 @@
+use crate::{
+    lib::*,
+    schema::{HasSchema, Schema},
+    ser::{SerializeError, BYTES_PER_LENGTH_OFFSET},
+    utils::serialize,
+    SimpleSerialize,
+};
+
+const BYTES_PER_LINE: usize = 16;
+// width of a full line of bytes, formatted as `00 11 22 ..`
+const HEX_COLUMN_WIDTH: usize = BYTES_PER_LINE * 3 - 1;
+const BITS_PER_BYTE: usize = crate::BITS_PER_BYTE as usize;
+
+/// `HexDump` formats an SSZ encoding as an annotated hex dump, following the layout given by
+/// a `Schema`.
+///
+/// Each line shows the absolute position in the encoding, the raw bytes and how they are
+/// interpreted: field names, the fixed and variable parts of containers, offsets and the
+/// position they point to, list lengths, `Bitlist` delimiter bits and union selectors.
+///
+/// Malformed encodings are annotated up to the first error, which is reported in place.
+///
+/// ```
+/// # use ssz_rs::prelude::*;
+/// #[derive(Default, SimpleSerialize)]
+/// struct Foo {
+///     a: u16,
+///     b: List<u8, 4>,
+/// }
+///
+/// let dump = HexDump::of::<Foo>(&[1, 0, 6, 0, 0, 0, 42]);
+/// let expected = "\
+/// 00000000                                                  Foo (fixed part: 6 byte(s))
+/// 00000000  01 00                                             a: uint16 = 1
+/// 00000002  06 00 00 00                                       b: offset 6 -> 00000006 (List[uint8, 4])
+/// 00000006                                                    -- variable part: 1 byte(s) --
+/// 00000006  2a                                                b: List[uint8, 4] (len=1)
+/// ";
+/// assert_eq!(dump.to_string(), expected);
+/// ```
+pub struct HexDump<'a> {
+    schema: Schema,
+    encoding: &'a [u8],
+}
+
+impl<'a> HexDump<'a> {
+    pub fn new(schema: Schema, encoding: &'a [u8]) -> Self {
+        Self { schema, encoding }
+    }
+
+    /// Annotate `encoding` as the encoding of some value of type `T`.
+    pub fn of<T: HasSchema>(encoding: &'a [u8]) -> Self {
+        Self::new(T::schema(), encoding)
+    }
+}
+
+impl Display for HexDump<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut annotator = Annotator { f, encoding: self.encoding };
+        match annotator.visit(&self.schema, 0, self.encoding.len(), 0, "") {
+            Ok(()) | Err(Halt::Malformed) => Ok(()),
+            Err(Halt::Fmt(err)) => Err(err),
+        }
+    }
+}
+
+/// `hex_dump` is a convenience function to render the annotated encoding of `value`.
+pub fn hex_dump<T: SimpleSerialize + HasSchema>(value: &T) -> Result<String, SerializeError> {
+    let encoding = serialize(value)?;
+    Ok(format!("{}", HexDump::of::<T>(&encoding)))
+}
+
+enum Halt {
+    Fmt(fmt::Error),
+    // the encoding was found to be invalid and this has already been written out
+    Malformed,
+}
+
+impl From<fmt::Error> for Halt {
+    fn from(err: fmt::Error) -> Self {
+        Self::Fmt(err)
+    }
+}
+
+// Writes `name: ` unless the name is empty, e.g. for the outermost value.
+struct Label<'a>(&'a str);
+
+impl Display for Label<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.0.is_empty() {
+            Ok(())
+        } else {
+            write!(f, "{}: ", self.0)
+        }
+    }
+}
+
+// Writes the bits of bitfield `data` in index order, as `Debug` does for `Bitvector`.
+struct Bits<'a> {
+    data: &'a [u8],
+    len: usize,
+}
+
+impl Display for Bits<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "[")?;
+        for index in 0..self.len {
+            let bit = (self.data[index / BITS_PER_BYTE] >> (index % BITS_PER_BYTE)) & 1;
+            write!(f, "{bit}")?;
+            if (index + 1) % 4 == 0 && index + 1 != self.len {
+                write!(f, "_")?;
+            }
+        }
+        write!(f, "]")
+    }
+}
+
+// Writes little-endian `data` as a hex number, most significant byte first.
+struct BigEndianHex<'a>(&'a [u8]);
+
+impl fmt::LowerHex for BigEndianHex<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if f.alternate() {
+            write!(f, "0x")?;
+        }
+        for byte in self.0.iter().rev() {
+            write!(f, "{byte:02x}")?;
+        }
+        Ok(())
+    }
+}
+
+struct Annotator<'a, 'b, 'c> {
+    f: &'a mut Formatter<'b>,
+    encoding: &'c [u8],
+}
+
+impl<'a, 'b, 'c> Annotator<'a, 'b, 'c> {
+    // Write the bytes in `start..end` followed by the `annotation`.
+    // Spans longer than a line continue on the following lines without annotation.
+    fn line(
+        &mut self,
+        start: usize,
+        end: usize,
+        depth: usize,
+        annotation: fmt::Arguments<'_>,
+    ) -> Result<(), Halt> {
+        let mut position = start;
+        loop {
+            let line_end = end.min(position + BYTES_PER_LINE);
+            write!(self.f, "{position:08x}  ")?;
+            for (i, byte) in self.encoding[position..line_end].iter().enumerate() {
+                if i != 0 {
+                    write!(self.f, " ")?;
+                }
+                write!(self.f, "{byte:02x}")?;
+            }
+            if position == start {
+                let width = ((line_end - position) * 3).saturating_sub(1);
+                let padding = HEX_COLUMN_WIDTH.saturating_sub(width);
+                write!(self.f, "{:padding$} {:indent$}{annotation}", "", "", indent = depth * 2)?;
+            }
+            writeln!(self.f)?;
+
+            position = line_end;
+            if position >= end {
+                return Ok(())
+            }
+        }
+    }
+
+    fn malformed(
+        &mut self,
+        start: usize,
+        end: usize,
+        depth: usize,
+        message: fmt::Arguments<'_>,
+    ) -> Result<(), Halt> {
+        self.line(start, end, depth, format_args!("!! {message}"))?;
+        Err(Halt::Malformed)
+    }
+
+    // NOTE: callers ensure `position + BYTES_PER_LENGTH_OFFSET` is in bounds
+    fn read_offset(&self, position: usize) -> usize {
+        let mut bytes = [0u8; BYTES_PER_LENGTH_OFFSET];
+        bytes.copy_from_slice(&self.encoding[position..position + BYTES_PER_LENGTH_OFFSET]);
+        u32::from_le_bytes(bytes) as usize
+    }
+
+    fn visit(
+        &mut self,
+        schema: &Schema,
+        start: usize,
+        end: usize,
+        depth: usize,
+        label: &str,
+    ) -> Result<(), Halt> {
+        let encoding = self.encoding;
+        let data = &encoding[start..end];
+        let len = data.len();
+        let label = Label(label);
+        match schema {
+            Schema::Uint(size) => {
+                if len != *size {
+                    return self.malformed(
+                        start,
+                        end,
+                        depth,
+                        format_args!("{label}{schema} expects {size} byte(s) but found {len}"),
+                    )
+                }
+                if *size <= 16 {
+                    let mut bytes = [0u8; 16];
+                    bytes[..len].copy_from_slice(data);
+                    let value = u128::from_le_bytes(bytes);
+                    self.line(start, end, depth, format_args!("{label}{schema} = {value}"))
+                } else {
+                    let value = BigEndianHex(data);
+                    self.line(start, end, depth, format_args!("{label}{schema} = {value:#x}"))
+                }
+            }
+            Schema::Boolean => match data {
+                [0] => self.line(start, end, depth, format_args!("{label}{schema} = false")),
+                [1] => self.line(start, end, depth, format_args!("{label}{schema} = true")),
+                [b] => self.malformed(
+                    start,
+                    end,
+                    depth,
+                    format_args!("{label}invalid byte {b:#04x} for {schema}"),
+                ),
+                _ => self.malformed(
+                    start,
+                    end,
+                    depth,
+                    format_args!("{label}{schema} expects 1 byte but found {len}"),
+                ),
+            },
+            Schema::Bitvector(bound) => {
+                let expected = schema.size_hint();
+                if len != expected {
+                    return self.malformed(
+                        start,
+                        end,
+                        depth,
+                        format_args!("{label}{schema} expects {expected} byte(s) but found {len}"),
+                    )
+                }
+                let remainder = bound % BITS_PER_BYTE;
+                if remainder != 0 && data[len - 1] >> remainder != 0 {
+                    return self.malformed(
+                        start,
+                        end,
+                        depth,
+                        format_args!("{label}{schema} has padding bits set in its last byte"),
+                    )
+                }
+                let bits = Bits { data, len: *bound };
+                self.line(start, end, depth, format_args!("{label}{schema} = {bits}"))
+            }
+            Schema::Bitlist(bound) => {
+                let last = match data.last() {
+                    Some(0) | None => {
+                        return self.malformed(
+                            start,
+                            end,
+                            depth,
+                            format_args!("{label}{schema} is missing its delimiter bit"),
+                        )
+                    }
+                    Some(last) => *last,
+                };
+                // SAFETY: checked subtraction is unnecessary, as last != 0; qed
+                let delimiter = BITS_PER_BYTE - 1 - last.leading_zeros() as usize;
+                let bit_len = (len - 1) * BITS_PER_BYTE + delimiter;
+                if bit_len > *bound {
+                    return self.malformed(
+                        start,
+                        end,
+                        depth,
+                        format_args!("{label}{schema} has {bit_len} bits, exceeding its bound"),
+                    )
+                }
+                let bits = Bits { data, len: bit_len };
+                self.line(
+                    start,
+                    end,
+                    depth,
+                    format_args!(
+                        "{label}{schema} (len={bit_len}) = {bits}, delimiter is bit {delimiter} of byte {:08x}",
+                        end - 1
+                    ),
+                )
+            }
+            Schema::Vector { element, length } => self.visit_sequence(
+                schema,
+                element,
+                Some(*length),
+                *length,
+                start,
+                end,
+                depth,
+                label,
+            ),
+            Schema::List { element, limit } => {
+                self.visit_sequence(schema, element, None, *limit, start, end, depth, label)
+            }
+            Schema::Container { name, fields } => {
+                let fixed_len = fields.iter().map(|f| f.schema.fixed_part_length()).sum::<usize>();
+                if len < fixed_len {
+                    return self.malformed(
+                        start,
+                        end,
+                        depth,
+                        format_args!(
+                            "{label}{name} expects a fixed part of {fixed_len} byte(s) but found {len}"
+                        ),
+                    )
+                }
+                self.line(
+                    start,
+                    start,
+                    depth,
+                    format_args!("{label}{name} (fixed part: {fixed_len} byte(s))"),
+                )?;
+
+                let mut cursor = start;
+                let mut variable_fields = vec![];
+                for field in fields {
+                    let schema = &field.schema;
+                    if schema.is_variable_size() {
+                        let offset = self.read_offset(cursor);
+                        self.line(
+                            cursor,
+                            cursor + BYTES_PER_LENGTH_OFFSET,
+                            depth + 1,
+                            format_args!(
+                                "{}: offset {offset} -> {:08x} ({schema})",
+                                field.name,
+                                start + offset
+                            ),
+                        )?;
+                        let previous = variable_fields.last().map(|(_, offset)| *offset);
+                        if previous.is_none() && offset != fixed_len {
+                            return self.malformed(
+                                cursor,
+                                cursor + BYTES_PER_LENGTH_OFFSET,
+                                depth + 1,
+                                format_args!("first offset must point to the end of the fixed part at {fixed_len}"),
+                            )
+                        }
+                        if offset < previous.unwrap_or_default() || offset > len {
+                            return self.malformed(
+                                cursor,
+                                cursor + BYTES_PER_LENGTH_OFFSET,
+                                depth + 1,
+                                format_args!("offset {offset} is out of order or out of bounds"),
+                            )
+                        }
+                        variable_fields.push((field, offset));
+                    } else {
+                        let size = schema.size_hint();
+                        self.visit(schema, cursor, cursor + size, depth + 1, field.name)?;
+                    }
+                    cursor += schema.fixed_part_length();
+                }
+
+                if variable_fields.is_empty() {
+                    if len != fixed_len {
+                        return self.malformed(
+                            cursor,
+                            end,
+                            depth + 1,
+                            format_args!("{} unexpected trailing byte(s)", len - fixed_len),
+                        )
+                    }
+                    return Ok(())
+                }
+
+                self.line(
+                    cursor,
+                    cursor,
+                    depth + 1,
+                    format_args!("-- variable part: {} byte(s) --", len - fixed_len),
+                )?;
+                for (i, (field, offset)) in variable_fields.iter().enumerate() {
+                    let next = variable_fields.get(i + 1).map(|(_, offset)| *offset).unwrap_or(len);
+                    self.visit(&field.schema, start + offset, start + next, depth + 1, field.name)?;
+                }
+                Ok(())
+            }
+            Schema::Union { name, variants } => {
+                let selector = match data.first() {
+                    Some(selector) => *selector,
+                    None => {
+                        return self.malformed(
+                            start,
+                            end,
+                            depth,
+                            format_args!("{label}{name} is missing its selector"),
+                        )
+                    }
+                };
+                let variant = match variants.iter().find(|v| v.selector == selector) {
+                    Some(variant) => variant,
+                    None => {
+                        return self.malformed(
+                            start,
+                            end,
+                            depth,
+                            format_args!("{label}{name} has no variant with selector {selector}"),
+                        )
+                    }
+                };
+                self.line(
+                    start,
+                    start + 1,
+                    depth,
+                    format_args!("{label}{name} selector = {selector} ({})", variant.name),
+                )?;
+                match &variant.schema {
+                    Some(schema) => self.visit(schema, start + 1, end, depth + 1, variant.name),
+                    None if len > 1 => self.malformed(
+                        start + 1,
+                        end,
+                        depth + 1,
+                        format_args!("unexpected payload for variant {}", variant.name),
+                    ),
+                    None => Ok(()),
+                }
+            }
+        }
+    }
+
+    // Annotate the encoding of a `Vector` (with `exact` length) or a `List`.
+    #[allow(clippy::too_many_arguments)]
+    fn visit_sequence(
+        &mut self,
+        schema: &Schema,
+        element: &Schema,
+        exact: Option<usize>,
+        bound: usize,
+        start: usize,
+        end: usize,
+        depth: usize,
+        label: Label<'_>,
+    ) -> Result<(), Halt> {
+        let len = end - start;
+        if !element.is_variable_size() {
+            let size = element.size_hint();
+            if !len.is_multiple_of(size) {
+                return self.malformed(
+                    start,
+                    end,
+                    depth,
+                    format_args!("{label}{schema} has {len} byte(s), not a multiple of {size}"),
+                )
+            }
+            let count = len / size;
+            if exact.map(|exact| count != exact).unwrap_or(count > bound) {
+                return self.malformed(
+                    start,
+                    end,
+                    depth,
+                    format_args!("{label}{schema} has an invalid number of elements: {count}"),
+                )
+            }
+            // bytes are more legible when kept together
+            if *element == Schema::Uint(1) {
+                return self.line(start, end, depth, format_args!("{label}{schema} (len={count})"))
+            }
+            self.line(start, start, depth, format_args!("{label}{schema} (len={count})"))?;
+            for i in 0..count {
+                let position = start + i * size;
+                self.visit(element, position, position + size, depth + 1, &format!("[{i}]"))?;
+            }
+            return Ok(())
+        }
+
+        if len == 0 {
+            if exact.is_some() {
+                return self.malformed(
+                    start,
+                    end,
+                    depth,
+                    format_args!("{label}{schema} cannot be empty"),
+                )
+            }
+            return self.line(start, end, depth, format_args!("{label}{schema} (len=0)"))
+        }
+        if len < BYTES_PER_LENGTH_OFFSET {
+            return self.malformed(
+                start,
+                end,
+                depth,
+                format_args!("{label}{schema} is too short to contain an offset"),
+            )
+        }
+        let first_offset = self.read_offset(start);
+        if first_offset == 0 ||
+            !first_offset.is_multiple_of(BYTES_PER_LENGTH_OFFSET) ||
+            first_offset > len
+        {
+            return self.malformed(
+                start,
+                start + BYTES_PER_LENGTH_OFFSET,
+                depth,
+                format_args!("{label}{schema} has an invalid first offset {first_offset}"),
+            )
+        }
+        let count = first_offset / BYTES_PER_LENGTH_OFFSET;
+        if exact.map(|exact| count != exact).unwrap_or(count > bound) {
+            return self.malformed(
+                start,
+                start + BYTES_PER_LENGTH_OFFSET,
+                depth,
+                format_args!("{label}{schema} has an invalid number of elements: {count}"),
+            )
+        }
+        self.line(start, start, depth, format_args!("{label}{schema} (len={count})"))?;
+
+        let mut offsets = Vec::with_capacity(count + 1);
+        for i in 0..count {
+            let position = start + i * BYTES_PER_LENGTH_OFFSET;
+            let offset = self.read_offset(position);
+            self.line(
+                position,
+                position + BYTES_PER_LENGTH_OFFSET,
+                depth + 1,
+                format_args!("[{i}]: offset {offset} -> {:08x}", start + offset),
+            )?;
+            if offset < offsets.last().copied().unwrap_or_default() || offset > len {
+                return self.malformed(
+                    position,
+                    position + BYTES_PER_LENGTH_OFFSET,
+                    depth + 1,
+                    format_args!("offset {offset} is out of order or out of bounds"),
+                )
+            }
+            offsets.push(offset);
+        }
+        offsets.push(len);
+        for (i, span) in offsets.windows(2).enumerate() {
+            self.visit(element, start + span[0], start + span[1], depth + 1, &format!("[{i}]"))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[derive(Default, Debug, SimpleSerialize)]
+    struct Inner {
+        a: u8,
+        b: Bitvector<4>,
+    }
+
+    #[derive(Default, Debug, SimpleSerialize)]
+    enum Choice {
+        #[default]
+        None,
+        Value(u16),
+    }
+
+    #[derive(Default, Debug, SimpleSerialize)]
+    struct Foo {
+        a: u32,
+        b: List<u16, 4>,
+        c: Inner,
+        d: Bitlist<16>,
+        e: Choice,
+    }
+
+    #[test]
+    fn test_dump_container() {
+        let value = Foo {
+            a: 5,
+            b: List::try_from(vec![1, 2]).unwrap(),
+            c: Inner { a: 3, b: Bitvector::try_from([true, false, false, true].as_ref()).unwrap() },
+            d: Bitlist::try_from([true, true, false].as_ref()).unwrap(),
+            e: Choice::Value(7),
+        };
+        let dump = hex_dump(&value).unwrap();
+        let expected = "\
+00000000                                                  Foo (fixed part: 18 byte(s))
+00000000  05 00 00 00                                       a: uint32 = 5
+00000004  12 00 00 00                                       b: offset 18 -> 00000012 (List[uint16, 4])
+00000008                                                    c: Inner (fixed part: 2 byte(s))
+00000008  03                                                  a: uint8 = 3
+00000009  09                                                  b: Bitvector[4] = [1001]
+0000000a  16 00 00 00                                       d: offset 22 -> 00000016 (Bitlist[16])
+0000000e  17 00 00 00                                       e: offset 23 -> 00000017 (Choice)
+00000012                                                    -- variable part: 8 byte(s) --
+00000012                                                    b: List[uint16, 4] (len=2)
+00000012  01 00                                               [0]: uint16 = 1
+00000014  02 00                                               [1]: uint16 = 2
+00000016  0b                                                d: Bitlist[16] (len=3) = [110], delimiter is bit 3 of byte 00000016
+00000017  01                                                e: Choice selector = 1 (Value)
+00000018  07 00                                               Value: uint16 = 7
+";
+        assert_eq!(dump, expected);
+    }
+
+    #[test]
+    fn test_dump_malformed() {
+        // offset of `b` points past the end of the input
+        let mut encoding = [0u8; 18];
+        encoding[4] = 0xff;
+        let dump = HexDump::of::<Foo>(&encoding).to_string();
+        assert!(dump.contains("b: offset 255 -> 000000ff"));
+        assert!(dump.lines().last().unwrap().contains("!! first offset must point"));
+
+        let encoding = [1u8, 7];
+        let dump = HexDump::of::<Choice>(&encoding).to_string();
+        assert!(dump.lines().last().unwrap().contains("!! Value: uint16 expects 2 byte(s)"));
+    }
+
+    #[test]
+    fn test_dump_bytes_and_large_uints() {
+        let value = List::<u8, 64>::try_from(vec![7u8; 20]).unwrap();
+        let dump = hex_dump(&value).unwrap();
+        assert_eq!(dump.lines().count(), 2);
+        assert!(dump.starts_with("00000000  07 07"));
+        assert!(dump.lines().next().unwrap().ends_with("List[uint8, 64] (len=20)"));
+
+        let value = U256::from(1u64);
+        let dump = hex_dump(&value).unwrap();
+        let first = dump.lines().next().unwrap();
+        assert!(first.ends_with(&format!("uint256 = 0x{}01", "00".repeat(31))));
+    }
+}
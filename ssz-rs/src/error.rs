@@ -1,4 +1,8 @@
-use crate::{de::DeserializeError, lib::*, merkleization::MerkleizationError, ser::SerializeError};
+use crate::{
+    de::DeserializeError, lib::*, merkleization::MerkleizationError, ser::SerializeError,
+    utils::HEX_ENCODING_PREFIX,
+};
+use hex::FromHexError;
 
 /// Top-level error to wrap all other errors in this crate
 #[derive(Debug)]
@@ -94,3 +98,39 @@ impl Display for InstanceError {
 
 #[cfg(feature = "std")]
 impl std::error::Error for InstanceError {}
+
+/// An invalid hex string.
+#[derive(Debug)]
+pub enum HexError {
+    Hex(FromHexError),
+    MissingPrefix,
+    /// The decoded data is invalid for the type.
+    Instance(InstanceError),
+}
+
+impl Display for HexError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Hex(e) => write!(f, "{e}"),
+            Self::MissingPrefix => {
+                write!(f, "missing prefix `{HEX_ENCODING_PREFIX}` when deserializing hex data")
+            }
+            Self::Instance(e) => write!(f, "{e}"),
+        }
+    }
+}
+
+impl From<FromHexError> for HexError {
+    fn from(e: FromHexError) -> Self {
+        Self::Hex(e)
+    }
+}
+
+impl From<InstanceError> for HexError {
+    fn from(e: InstanceError) -> Self {
+        Self::Instance(e)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for HexError {}
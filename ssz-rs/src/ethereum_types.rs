@@ -0,0 +1,179 @@
+//! `SimpleSerialize` for the primitive types of the `ethereum-types` crate.
+use crate::{
+    de::{impl_context_free, Deserialize, DeserializeError},
+    lib::*,
+    merkleization::{MerkleizationError, Merkleized, Node},
+    schema::{HasSchema, Schema},
+    ser::{Serialize, SerializeError},
+    Serializable, SimpleSerialize,
+};
+use ethereum_types::{Bloom, H128, H160, H256, H264, H32, H512, H520, H64, U128, U256, U64};
+
+// Implement `SimpleSerialize` for a fixed-size hash, encoded as a `Vector<u8, N>` by way of the
+// inner array
+macro_rules! define_hash {
+    ($name:ty, $size:expr) => {
+        impl Serializable for $name {
+            fn is_variable_size() -> bool {
+                false
+            }
+
+            fn size_hint() -> usize {
+                $size
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize(&self, buffer: &mut Vec<u8>) -> Result<usize, SerializeError> {
+                self.0.serialize(buffer)
+            }
+        }
+
+        impl Deserialize for $name {
+            fn deserialize(encoding: &[u8]) -> Result<Self, DeserializeError> {
+                <[u8; $size]>::deserialize(encoding).map(<$name>::from)
+            }
+        }
+
+        impl_context_free!([] $name);
+
+        impl Merkleized for $name {
+            fn hash_tree_root(&mut self) -> Result<Node, MerkleizationError> {
+                self.0.hash_tree_root()
+            }
+        }
+
+        impl HasSchema for $name {
+            fn schema() -> Schema {
+                <[u8; $size]>::schema()
+            }
+        }
+
+        impl SimpleSerialize for $name {}
+    };
+}
+
+define_hash!(H32, 4);
+define_hash!(H64, 8);
+define_hash!(H128, 16);
+define_hash!(H160, 20);
+define_hash!(H256, 32);
+define_hash!(H264, 33);
+define_hash!(H512, 64);
+define_hash!(H520, 65);
+define_hash!(Bloom, 256);
+
+// Implement `SimpleSerialize` for an unsigned integer made of `$limbs` little-endian `u64` limbs
+macro_rules! define_uint {
+    ($name:ty, $limbs:expr) => {
+        impl Serializable for $name {
+            fn is_variable_size() -> bool {
+                false
+            }
+
+            fn size_hint() -> usize {
+                $limbs * 8
+            }
+        }
+
+        impl Serialize for $name {
+            fn serialize(&self, buffer: &mut Vec<u8>) -> Result<usize, SerializeError> {
+                for limb in self.0 {
+                    buffer.extend_from_slice(&limb.to_le_bytes());
+                }
+                Ok(Self::size_hint())
+            }
+        }
+
+        impl Deserialize for $name {
+            fn deserialize(encoding: &[u8]) -> Result<Self, DeserializeError> {
+                let byte_size = Self::size_hint();
+                if encoding.len() < byte_size {
+                    return Err(DeserializeError::ExpectedFurtherInput {
+                        provided: encoding.len(),
+                        expected: byte_size,
+                    })
+                }
+                if encoding.len() > byte_size {
+                    return Err(DeserializeError::AdditionalInput {
+                        provided: encoding.len(),
+                        expected: byte_size,
+                    })
+                }
+
+                let mut limbs = [0u64; $limbs];
+                for (limb, bytes) in limbs.iter_mut().zip(encoding.chunks_exact(8)) {
+                    // SAFETY: chunks have exactly 8 bytes; qed
+                    *limb = u64::from_le_bytes(bytes.try_into().expect("is right size"));
+                }
+                Ok(Self(limbs))
+            }
+        }
+
+        impl_context_free!([] $name);
+
+        impl Merkleized for $name {
+            fn hash_tree_root(&mut self) -> Result<Node, MerkleizationError> {
+                let mut root = Node::default();
+                for (limb, bytes) in self.0.iter().zip(root.as_mut().chunks_exact_mut(8)) {
+                    bytes.copy_from_slice(&limb.to_le_bytes());
+                }
+                Ok(root)
+            }
+
+            fn is_composite_type() -> bool {
+                false
+            }
+        }
+
+        impl HasSchema for $name {
+            fn schema() -> Schema {
+                Schema::Uint(Self::size_hint())
+            }
+        }
+
+        impl SimpleSerialize for $name {}
+    };
+}
+
+define_uint!(U64, 1);
+define_uint!(U128, 2);
+define_uint!(U256, 4);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        utils::{deserialize, serialize},
+        vector::Vector,
+    };
+
+    #[test]
+    fn test_hashes() {
+        let mut hash = H256::repeat_byte(0xab);
+        let mut expected = Vector::<u8, 32>::from([0xab; 32]);
+        assert_eq!(serialize(&hash).unwrap(), serialize(&expected).unwrap());
+        assert_eq!(hash.hash_tree_root().unwrap(), expected.hash_tree_root().unwrap());
+        assert_eq!(deserialize::<H256>(&[0xab; 32]).unwrap(), hash);
+        assert!(deserialize::<H256>(&[0xab; 33]).is_err());
+
+        let mut bloom = Bloom::repeat_byte(0x01);
+        let mut expected = Vector::<u8, 256>::from([0x01; 256]);
+        assert_eq!(bloom.hash_tree_root().unwrap(), expected.hash_tree_root().unwrap());
+        assert_eq!(Bloom::schema(), Vector::<u8, 256>::schema());
+    }
+
+    #[test]
+    fn test_uints() {
+        let mut value = U256::from(u128::MAX) + U256::from(7u64);
+        let mut expected = crate::U256::from(u128::MAX) + crate::U256::from(7u64);
+        assert_eq!(serialize(&value).unwrap(), serialize(&expected).unwrap());
+        assert_eq!(value.hash_tree_root().unwrap(), expected.hash_tree_root().unwrap());
+        assert_eq!(deserialize::<U256>(&serialize(&expected).unwrap()).unwrap(), value);
+
+        let mut value = U64::from(1337u64);
+        assert_eq!(serialize(&value).unwrap(), serialize(&1337u64).unwrap());
+        assert_eq!(value.hash_tree_root().unwrap(), 1337u64.hash_tree_root().unwrap());
+        assert!(deserialize::<U128>(&[0u8; 8]).is_err());
+    }
+}
@@ -35,19 +35,32 @@
 #[cfg(not(feature = "std"))]
 extern crate alloc;
 
+#[cfg(feature = "alloy-primitives")]
+mod alloy;
 mod array;
+#[cfg(feature = "num-bigint")]
+mod biguint;
 mod bitlist;
 mod bitvector;
 mod boolean;
+mod byte_list;
+mod byte_vector;
 mod container;
 mod de;
+mod diff;
+mod dump;
 mod error;
+#[cfg(feature = "ethereum-types")]
+mod ethereum_types;
 mod list;
 mod merkleization;
+mod partial;
+mod patch;
 mod ruint;
+mod schema;
 mod ser;
 #[cfg(feature = "serde")]
-mod serde;
+pub mod serde;
 mod uint;
 mod union;
 mod utils;
@@ -61,26 +74,25 @@ mod lib {
         pub use std::*;
     }
 
-    pub use self::core::{any, cmp, fmt, iter, slice};
+    pub use self::core::{any, array, cmp, fmt, mem, ops, ptr, slice};
 
     pub use self::{
         cmp::Ordering,
         core::{
             array::TryFromSliceError,
             fmt::{Debug, Display, Formatter},
-            ops::{Deref, DerefMut, Index, IndexMut},
-            slice::{IterMut, SliceIndex},
+            ops::{Deref, DerefMut, Index, IndexMut, Range},
+            slice::SliceIndex,
+            str::FromStr,
         },
-        iter::Enumerate,
     };
 
     #[cfg(not(feature = "std"))]
-    pub use alloc::{format, string::String, vec, vec::Vec};
+    pub use alloc::{boxed::Box, collections::BTreeMap, format, string::String, vec, vec::Vec};
 
     #[cfg(feature = "std")]
-    pub use std::vec::Vec;
+    pub use std::{boxed::Box, collections::BTreeMap, vec::Vec};
 
-    #[cfg(feature = "serde")]
     pub use self::core::marker::PhantomData;
 }
 
@@ -95,6 +107,36 @@ pub trait Serializable: Serialize + Deserialize {
     // expected number of bytes for the serialization of this type
     // or 0 if unknown ahead of time
     fn size_hint() -> usize;
+
+    // NOTE: the following are only used for types of a fixed size, where a sequence of values
+    // is encoded as the concatenation of the encoding of each value, and so let basic types
+    // provide faster paths than handling one value at a time.
+
+    // append the encoding of each of the `values` to the `buffer`
+    // and return the number of bytes written
+    #[doc(hidden)]
+    fn serialize_fixed_sequence(
+        values: &[Self],
+        buffer: &mut lib::Vec<u8>,
+    ) -> Result<usize, SerializeError>
+    where
+        Self: Sized,
+    {
+        let mut total_size = 0;
+        for value in values {
+            total_size += value.serialize(buffer)?;
+        }
+        Ok(total_size)
+    }
+
+    // decode the values encoded in `encoding`, which has a multiple of `size_hint` bytes
+    #[doc(hidden)]
+    fn deserialize_fixed_sequence(encoding: &[u8]) -> Result<lib::Vec<Self>, DeserializeError>
+    where
+        Self: Sized,
+    {
+        encoding.chunks_exact(Self::size_hint()).map(Self::deserialize).collect()
+    }
 }
 
 /// `SimpleSerialize` is a trait for types conforming to the SSZ spec.
@@ -106,16 +148,31 @@ mod exports {
     pub use crate::{
         bitlist::Bitlist,
         bitvector::Bitvector,
-        de::{Deserialize, DeserializeError},
-        error::{Error as SimpleSerializeError, InstanceError, TypeError},
+        byte_list::ByteList,
+        byte_vector::ByteVector,
+        de::{Deserialize, DeserializeError, DeserializeWithContext},
+        diff::{diff, diff_encodings, Difference, Entry},
+        dump::{hex_dump, HexDump},
+        error::{Error as SimpleSerializeError, HexError, InstanceError, TypeError},
         list::List,
-        merkleization::{is_valid_merkle_branch, MerkleizationError, Merkleized, Node},
+        merkleization::{
+            compute_merkle_root, concat_generalized_indices, is_valid_merkle_branch,
+            update_merkle_root, MerkleizationError, Merkleized, Node,
+        },
+        partial::Partial,
+        patch::{apply_patches, Replacement},
+        ruint::U256,
+        schema::{Field, HasSchema, PathElement, Schema, Variant},
         ser::{Serialize, SerializeError},
-        uint::U256,
-        utils::{deserialize, serialize},
+        utils::{deserialize, hash_tree_root_from_bytes, serialize},
         vector::Vector,
         Serializable, SimpleSerialize,
     };
+
+    #[cfg(feature = "alloy-primitives")]
+    pub use crate::alloy::BoundedBytes;
+    #[cfg(feature = "num-bigint")]
+    pub use crate::biguint::BigU256;
 }
 
 pub use crate::exports::*;
@@ -128,7 +185,7 @@ pub mod prelude {
     // expose this so the derive macro has everything in scope
     // with a simple `prelude` import
     pub use crate as ssz_rs;
-    pub use ssz_rs_derive::{Serializable, SimpleSerialize};
+    pub use ssz_rs_derive::{versioned, Serializable, SimpleSerialize};
 }
 
 #[doc(hidden)]
@@ -140,4 +197,8 @@ pub mod __internal {
         merkleization::{merkleize, mix_in_selector},
         ser::Serializer,
     };
+
+    // for the serde representations of unions
+    #[cfg(feature = "serde")]
+    pub use serde;
 }
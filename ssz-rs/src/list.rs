@@ -1,12 +1,15 @@
 use crate::{
-    de::{deserialize_homogeneous_composite, Deserialize, DeserializeError},
+    de::{
+        deserialize_homogeneous_composite, deserialize_homogeneous_composite_with_context,
+        Deserialize, DeserializeError, DeserializeWithContext,
+    },
     error::{Error, InstanceError},
     lib::*,
     merkleization::{
-        elements_to_chunks, merkleize, mix_in_length, pack, MerkleizationError, Merkleized, Node,
-        BYTES_PER_CHUNK,
+        merkleize_elements, mix_in_length, MerkleizationError, Merkleized, Node, BYTES_PER_CHUNK,
     },
-    ser::{Serialize, SerializeError, Serializer},
+    schema::{HasSchema, Schema},
+    ser::{serialize_homogeneous_composite, Serialize, SerializeError},
     Serializable, SimpleSerialize,
 };
 #[cfg(feature = "serde")]
@@ -132,11 +135,7 @@ where
         if self.len() > N {
             return Err(InstanceError::Bounded { bound: N, provided: self.len() }.into())
         }
-        let mut serializer = Serializer::default();
-        for element in &self.data {
-            serializer.with_element(element)?;
-        }
-        serializer.serialize(buffer)
+        serialize_homogeneous_composite(&self.data, buffer)
     }
 }
 
@@ -145,19 +144,62 @@ where
     T: Serializable,
 {
     fn deserialize(encoding: &[u8]) -> Result<Self, DeserializeError> {
-        if !T::is_variable_size() {
-            let remainder = encoding.len() % T::size_hint();
-            if remainder != 0 {
-                return Err(DeserializeError::AdditionalInput {
-                    provided: encoding.len(),
-                    // SAFETY: checked subtraction is unnecessary, as encoding.len() > remainder;
-                    // qed
-                    expected: encoding.len() - remainder,
-                })
-            }
+        check_element_sizes(encoding, T::is_variable_size(), T::size_hint())?;
+        let result = deserialize_homogeneous_composite(encoding)?;
+        Self::from_elements(result)
+    }
+}
+
+impl<T, C, const N: usize> DeserializeWithContext<C> for List<T, N>
+where
+    T: Serializable + DeserializeWithContext<C>,
+    C: ?Sized,
+{
+    fn deserialize_with_context(encoding: &[u8], context: &C) -> Result<Self, DeserializeError> {
+        check_element_sizes(
+            encoding,
+            T::is_variable_size_with_context(context),
+            T::size_hint_with_context(context),
+        )?;
+        let result = deserialize_homogeneous_composite_with_context(encoding, context)?;
+        Self::from_elements(result)
+    }
+
+    fn is_variable_size_with_context(_: &C) -> bool {
+        true
+    }
+
+    fn size_hint_with_context(_: &C) -> usize {
+        0
+    }
+}
+
+// Checks `encoding` holds a whole number of elements if they are of a fixed size.
+fn check_element_sizes(
+    encoding: &[u8],
+    is_variable_size: bool,
+    size_hint: usize,
+) -> Result<(), DeserializeError> {
+    if !is_variable_size {
+        let remainder = encoding.len() % size_hint;
+        if remainder != 0 {
+            return Err(DeserializeError::AdditionalInput {
+                provided: encoding.len(),
+                // SAFETY: checked subtraction is unnecessary, as encoding.len() > remainder;
+                // qed
+                expected: encoding.len() - remainder,
+            })
         }
+    }
+    Ok(())
+}
 
-        let result = deserialize_homogeneous_composite(encoding)?;
+impl<T, const N: usize> List<T, N>
+where
+    T: Serializable,
+{
+    // Builds the list from its decoded elements, checking they are within the bound.
+    fn from_elements(result: Vec<T>) -> Result<Self, DeserializeError> {
         if result.len() > N {
             return Err(InstanceError::Bounded { bound: N, provided: result.len() }.into())
         }
@@ -167,16 +209,81 @@ where
         })?;
         Ok(result)
     }
-}
 
-impl<T, const N: usize> List<T, N>
-where
-    T: Serializable,
-{
+    /// Append `element` to the end of the list, without checking the bound `N`.
+    ///
+    /// A list pushed past `N` elements fails to serialize.
+    #[deprecated(note = "use `try_push`, which checks the bound `N`")]
     pub fn push(&mut self, element: T) {
         self.data.push(element);
     }
 
+    /// Append `element` to the end of the list, or return an error if the list already holds
+    /// `N` elements.
+    pub fn try_push(&mut self, element: T) -> Result<(), Error> {
+        self.check_bound(1)?;
+        self.data.push(element);
+        Ok(())
+    }
+
+    /// Append every element of `iter` to the end of the list. If this would exceed the bound
+    /// `N`, the list is left unchanged and an error is returned.
+    pub fn try_extend<I: IntoIterator<Item = T>>(&mut self, iter: I) -> Result<(), Error> {
+        let len = self.len();
+        let mut iter = iter.into_iter();
+        while let Some(element) = iter.next() {
+            if self.len() == N {
+                // NOTE: the rest of `iter` may be unbounded, so it is not consumed and only its
+                // lower bound is counted
+                let provided = N.saturating_add(1).saturating_add(iter.size_hint().0);
+                self.data.truncate(len);
+                return Err(Error::Instance(InstanceError::Bounded { bound: N, provided }))
+            }
+            self.data.push(element);
+        }
+        Ok(())
+    }
+
+    /// Insert `element` at `index`, shifting all elements after it to the right, or return an
+    /// error if the list already holds `N` elements.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index > len`.
+    pub fn insert(&mut self, index: usize, element: T) -> Result<(), Error> {
+        self.check_bound(1)?;
+        self.data.insert(index, element);
+        Ok(())
+    }
+
+    /// Remove and return the element at `index`, shifting all elements after it to the left.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn remove(&mut self, index: usize) -> T {
+        self.data.remove(index)
+    }
+
+    /// Remove and return the element at `index`, replacing it with the last element.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn swap_remove(&mut self, index: usize) -> T {
+        self.data.swap_remove(index)
+    }
+
+    /// Retain only the elements for which `f` returns `true`.
+    pub fn retain<F: FnMut(&T) -> bool>(&mut self, f: F) {
+        self.data.retain(f);
+    }
+
+    /// Shorten the list to `len` elements. Has no effect if the list is already shorter.
+    pub fn truncate(&mut self, len: usize) {
+        self.data.truncate(len);
+    }
+
     pub fn pop(&mut self) -> Option<T> {
         self.data.pop()
     }
@@ -185,11 +292,45 @@ where
         self.data.clear();
     }
 
+    /// Construct a list from the elements of `iter`, or return an error if there are more
+    /// than `N` elements.
+    pub fn try_from_iter<I: IntoIterator<Item = T>>(iter: I) -> Result<Self, Error> {
+        let mut list = Self::default();
+        list.try_extend(iter)?;
+        Ok(list)
+    }
+
+    fn check_bound(&self, additional: usize) -> Result<(), Error> {
+        let provided = self.len() + additional;
+        if provided > N {
+            return Err(Error::Instance(InstanceError::Bounded { bound: N, provided }))
+        }
+        Ok(())
+    }
+
     pub fn iter_mut(&mut self) -> IterMut<'_, T> {
         IterMut { inner: self.data.iter_mut() }
     }
 }
 
+/// Collect an iterator into a `List`.
+///
+/// # Panics
+///
+/// Panics if the iterator yields more than `N` elements. See `List::try_from_iter` for a
+/// fallible version.
+impl<T, const N: usize> FromIterator<T> for List<T, N>
+where
+    T: Serializable,
+{
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> Self {
+        match Self::try_from_iter(iter) {
+            Ok(list) => list,
+            Err(err) => panic!("{err}"),
+        }
+    }
+}
+
 pub struct IterMut<'a, T> {
     inner: slice::IterMut<'a, T>,
 }
@@ -208,20 +349,13 @@ where
 {
     // Number of chunks for this type, rounded up to a complete number of chunks
     fn chunk_count() -> usize {
-        (N * T::size_hint() + BYTES_PER_CHUNK - 1) / BYTES_PER_CHUNK
+        (N * T::size_hint()).div_ceil(BYTES_PER_CHUNK)
     }
 
     fn compute_hash_tree_root(&mut self) -> Result<Node, MerkleizationError> {
-        if T::is_composite_type() {
-            let count = self.len();
-            let chunks = elements_to_chunks(self.data.iter_mut().enumerate(), count)?;
-            let data_root = merkleize(&chunks, Some(N))?;
-            Ok(mix_in_length(&data_root, self.len()))
-        } else {
-            let chunks = pack(self)?;
-            let data_root = merkleize(&chunks, Some(Self::chunk_count()))?;
-            Ok(mix_in_length(&data_root, self.len()))
-        }
+        let limit = if T::is_composite_type() { N } else { Self::chunk_count() };
+        let data_root = merkleize_elements(self.data.iter_mut(), Some(limit))?;
+        Ok(mix_in_length(&data_root, self.len()))
     }
 }
 
@@ -234,6 +368,15 @@ where
     }
 }
 
+impl<T, const N: usize> HasSchema for List<T, N>
+where
+    T: Serializable + HasSchema,
+{
+    fn schema() -> Schema {
+        Schema::List { element: Box::new(T::schema()), limit: N }
+    }
+}
+
 impl<T, const N: usize> SimpleSerialize for List<T, N> where T: SimpleSerialize {}
 
 #[cfg(feature = "serde")]
@@ -293,7 +436,7 @@ mod tests {
     fn encode_list() {
         let mut value: List<u16, COUNT> = List::default();
         for _ in 0..COUNT {
-            value.push(33u16);
+            value.try_push(33u16).unwrap();
         }
         let encoding = serialize(&value).expect("can encode");
         let expected = [
@@ -347,7 +490,7 @@ mod tests {
         type Foo = List<List<u8, 16>, 32>;
 
         let mut value = Foo::default();
-        value.push(Default::default());
+        value.try_push(Default::default()).unwrap();
         let encoding = ssz_rs::serialize(&value).unwrap();
 
         let mut recovered: Foo = ssz_rs::deserialize(&encoding).unwrap();
@@ -371,4 +514,77 @@ mod tests {
             assert_eq!(*value, 1);
         }
     }
+
+    #[test]
+    fn roundtrip_basic_lists() {
+        let values = (0..100u64).map(|i| i << 40 | i).collect::<Vec<_>>();
+        let value = List::<u64, 128>::try_from(values.clone()).unwrap();
+        let encoding = serialize(&value).unwrap();
+        assert_eq!(encoding, values.iter().flat_map(|v| v.to_le_bytes()).collect::<Vec<_>>());
+        assert_eq!(List::<u64, 128>::deserialize(&encoding).unwrap(), value);
+
+        let value = List::<bool, 8>::try_from(vec![true, false, true]).unwrap();
+        let encoding = serialize(&value).unwrap();
+        assert_eq!(encoding, [1, 0, 1]);
+        assert_eq!(List::<bool, 8>::deserialize(&encoding).unwrap(), value);
+        assert!(List::<bool, 8>::deserialize(&[1, 2]).is_err());
+    }
+
+    #[test]
+    fn mutate_list_within_bound() {
+        let mut list = List::<u8, 4>::try_from_iter([1, 2]).unwrap();
+        list.try_push(3).unwrap();
+        list.insert(0, 0).unwrap();
+        assert_eq!(list[..], [0, 1, 2, 3]);
+
+        let err = list.try_push(4).unwrap_err();
+        assert!(matches!(err, Error::Instance(InstanceError::Bounded { bound: 4, provided: 5 })));
+        assert!(list.insert(1, 4).is_err());
+
+        assert_eq!(list.swap_remove(0), 0);
+        assert_eq!(list.remove(0), 3);
+        assert_eq!(list[..], [1, 2]);
+        let err = list.try_extend([5, 6, 7]).unwrap_err();
+        assert!(matches!(err, Error::Instance(InstanceError::Bounded { bound: 4, provided: 5 })));
+        assert_eq!(list[..], [1, 2]);
+        list.try_extend([5, 6]).unwrap();
+        list.retain(|x| x % 2 == 0);
+        assert_eq!(list[..], [2, 6]);
+        list.truncate(1);
+        assert_eq!(list[..], [2]);
+
+        let list = (0..4).collect::<List<u8, 4>>();
+        assert_eq!(list.len(), 4);
+        assert!(List::<u8, 4>::try_from_iter(0..5).is_err());
+    }
+
+    #[test]
+    fn extend_list_past_bound() {
+        let mut list = List::<u8, 4>::try_from_iter([1, 2]).unwrap();
+        let err = list.try_extend(std::iter::repeat(3)).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Instance(InstanceError::Bounded { bound: 4, provided: usize::MAX })
+        ));
+        let mut rest = 10..;
+        let err = list.try_extend(&mut rest).unwrap_err();
+        assert!(matches!(
+            err,
+            Error::Instance(InstanceError::Bounded { bound: 4, provided: usize::MAX })
+        ));
+        // only the values up to the first one past the bound are taken
+        assert_eq!(rest.next(), Some(13));
+        let err = list.try_extend(0..10).unwrap_err();
+        assert!(matches!(err, Error::Instance(InstanceError::Bounded { bound: 4, provided: 12 })));
+        assert_eq!(list[..], [1, 2]);
+    }
+
+    #[test]
+    #[allow(deprecated)]
+    fn push_past_bound() {
+        let mut list = (0..4).collect::<List<u8, 4>>();
+        list.push(4);
+        let err = serialize(&list).unwrap_err();
+        assert!(matches!(err, SerializeError::InvalidInstance(InstanceError::Bounded { .. })));
+    }
 }
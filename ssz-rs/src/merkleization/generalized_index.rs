@@ -0,0 +1,96 @@
+// A generalized index, viewed as the path from the root of a merkle tree to the node it
+// addresses: after the leading `1` bit, each bit selects the left (`0`) or right (`1`) child.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct GeneralizedIndexPath {
+    bits: usize,
+    depth: u32,
+}
+
+impl GeneralizedIndexPath {
+    pub(crate) fn new(generalized_index: usize) -> Option<Self> {
+        if generalized_index == 0 {
+            return None
+        }
+        let depth = usize::BITS - 1 - generalized_index.leading_zeros();
+        Some(Self { bits: generalized_index ^ (1 << depth), depth })
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.depth == 0
+    }
+
+    pub(crate) fn depth(&self) -> u32 {
+        self.depth
+    }
+
+    // Consumes the next `depth` steps of the path, returning the index of the node reached
+    // relative to the subtree of that depth.
+    pub(crate) fn take(&mut self, depth: u32) -> Option<usize> {
+        if depth > self.depth {
+            return None
+        }
+        let remaining = self.depth - depth;
+        let index = self.bits.checked_shr(remaining).unwrap_or(0);
+        self.bits &= (1 << remaining) - 1;
+        self.depth = remaining;
+        Some(index)
+    }
+}
+
+// Returns the depth of a merkle tree with `count` leaves, after padding to a power of two.
+pub(crate) fn tree_depth(count: usize) -> u32 {
+    count.next_power_of_two().trailing_zeros()
+}
+
+/// Return the generalized index of the node addressed by following each of the `indices` in
+/// turn, from the root of the tree down through the subtree rooted at each node, as
+/// `concat_generalized_indices` in the SSZ spec.
+///
+/// For example, the index of the `root` of the `finalized_checkpoint` of a `BeaconState`:
+/// `concat_generalized_indices(&[52, 3]) == 105`.
+///
+/// Panics if any index is `0`, which does not address a node.
+pub const fn concat_generalized_indices(indices: &[usize]) -> usize {
+    let mut result = 1;
+    let mut i = 0;
+    while i < indices.len() {
+        let index = indices[i];
+        assert!(index != 0, "generalized index 0 does not address a node");
+        let depth = usize::BITS - 1 - index.leading_zeros();
+        result = (result << depth) | (index ^ (1 << depth));
+        i += 1;
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_generalized_index_path() {
+        assert!(GeneralizedIndexPath::new(0).is_none());
+        assert!(GeneralizedIndexPath::new(1).unwrap().is_empty());
+
+        // 0b1_0_110
+        let mut path = GeneralizedIndexPath::new(22).unwrap();
+        assert_eq!(path.depth(), 4);
+        assert_eq!(path.take(1), Some(0));
+        assert_eq!(path.take(4), None);
+        assert_eq!(path.take(3), Some(6));
+        assert!(path.is_empty());
+        assert_eq!(path.take(0), Some(0));
+
+        assert_eq!(tree_depth(1), 0);
+        assert_eq!(tree_depth(5), 3);
+        assert_eq!(tree_depth(8), 3);
+    }
+
+    #[test]
+    fn test_concat_generalized_indices() {
+        assert_eq!(concat_generalized_indices(&[]), 1);
+        assert_eq!(concat_generalized_indices(&[1, 22]), 22);
+        assert_eq!(concat_generalized_indices(&[52, 3]), 105);
+        assert_eq!(concat_generalized_indices(&[2, 3, 1, 4]), 0b1_0100);
+    }
+}
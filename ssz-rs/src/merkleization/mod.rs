@@ -1,19 +1,24 @@
+mod generalized_index;
 mod node;
 mod proofs;
 
-use crate::{
-    lib::*,
-    ser::{Serialize, SerializeError},
-};
+use crate::{lib::*, schema::PathElement, ser::SerializeError, SimpleSerialize};
 use sha2::{Digest, Sha256};
 
+pub use generalized_index::concat_generalized_indices;
+pub(crate) use generalized_index::{tree_depth, GeneralizedIndexPath};
 pub use node::Node;
-pub use proofs::is_valid_merkle_branch;
+pub use proofs::{compute_merkle_root, is_valid_merkle_branch, update_merkle_root};
 
 pub(crate) const BYTES_PER_CHUNK: usize = 32;
 pub(crate) const BITS_PER_CHUNK: usize = BYTES_PER_CHUNK * (crate::BITS_PER_BYTE as usize);
 
 /// A `Merkleized` type provides a "hash tree root" following the SSZ spec.
+///
+/// Chunks are merkleized on the stack as they are produced, so computing the root of a value
+/// does not allocate, except to encode the elements of a sequence of arrays of basic values
+/// spanning several chunks, e.g. `Vector<[u8; 48], N>`. The crate still needs `alloc` even
+/// without the `std` feature.
 pub trait Merkleized {
     /// Compute the "hash tree root" of `Self`.
     fn hash_tree_root(&mut self) -> Result<Node, MerkleizationError>;
@@ -31,6 +36,16 @@ pub enum MerkleizationError {
     SerializationError(SerializeError),
     /// More data was provided than expected
     InputExceedsLimit(usize),
+    /// The generalized index does not address a node that can be replaced.
+    InvalidGeneralizedIndex(usize),
+    /// The replacement for the node at the generalized index is not valid for that node.
+    InvalidReplacement(usize),
+    /// The node at the generalized index is not known.
+    MissingNode(usize),
+    /// The path does not address a value of the expected type.
+    InvalidPath(Vec<PathElement>),
+    /// A proof does not match the root it is checked against.
+    InvalidProof,
 }
 
 impl From<SerializeError> for MerkleizationError {
@@ -46,6 +61,23 @@ impl Display for MerkleizationError {
                 write!(f, "failed to serialize value: {err}")
             }
             Self::InputExceedsLimit(size) => write!(f, "data exceeds the declared limit {size}"),
+            Self::InvalidGeneralizedIndex(index) => {
+                write!(f, "generalized index {index} does not address a replaceable node")
+            }
+            Self::InvalidReplacement(index) => {
+                write!(f, "invalid replacement for the node at generalized index {index}")
+            }
+            Self::MissingNode(index) => {
+                write!(f, "the node at generalized index {index} is not known")
+            }
+            Self::InvalidPath(path) => {
+                write!(f, "the path `")?;
+                for element in path {
+                    write!(f, "{element}")?;
+                }
+                write!(f, "` does not address a value of the expected type")
+            }
+            Self::InvalidProof => write!(f, "the proof does not match the expected root"),
         }
     }
 }
@@ -53,32 +85,6 @@ impl Display for MerkleizationError {
 #[cfg(feature = "std")]
 impl std::error::Error for MerkleizationError {}
 
-// Ensures `buffer` can be exactly broken up into `BYTES_PER_CHUNK` chunks of bytes
-// via padding any partial chunks at the end of `buffer`
-pub fn pack_bytes(buffer: &mut Vec<u8>) {
-    let incomplete_chunk_len = buffer.len() % BYTES_PER_CHUNK;
-    if incomplete_chunk_len != 0 {
-        // SAFETY: checked subtraction is unnecessary,
-        // as BYTES_PER_CHUNK > incomplete_chunk_len; qed
-        let bytes_to_pad = BYTES_PER_CHUNK - incomplete_chunk_len;
-        buffer.resize(buffer.len() + bytes_to_pad, 0);
-    }
-}
-
-// Packs serializations of `values` into the return buffer with the
-// guarantee that `buffer.len() % BYTES_PER_CHUNK == 0`
-pub fn pack<T>(values: &[T]) -> Result<Vec<u8>, MerkleizationError>
-where
-    T: Serialize,
-{
-    let mut buffer = vec![];
-    for value in values {
-        value.serialize(&mut buffer)?;
-    }
-    pack_bytes(&mut buffer);
-    Ok(buffer)
-}
-
 fn hash_nodes(hasher: &mut Sha256, a: &[u8], b: &[u8], out: &mut [u8]) {
     hasher.update(a);
     hasher.update(b);
@@ -103,34 +109,34 @@ impl Index<usize> for Context {
 // Grab the precomputed context from the build stage
 include!(concat!(env!("OUT_DIR"), "/context.rs"));
 
-/// Return the root of the Merklization of a binary tree formed from `chunks`.
+/// Return the root of the Merklization of a binary tree formed from `layer`.
 ///
-/// `chunks` forms the bottom layer of a binary tree that is Merkleized.
+/// `layer` forms the bottom layer of a binary tree that is Merkleized.
 ///
 /// This implementation is memory efficient by relying on pre-computed subtrees of all
 /// "zero" leaves stored in the `CONTEXT`. SSZ specifies that `chunks` is padded to the next power
 /// of two and this can be quite large for some types. "Zero" subtrees are virtualized to avoid the
 /// memory and computation cost of large trees with partially empty leaves.
 ///
-/// The implementation approach treats `chunks` as the bottom layer of a perfect binary tree
+/// The implementation approach treats `layer` as the bottom layer of a perfect binary tree
 /// and for each height performs the hashing required to compute the parent layer in place.
 /// This process is repated until the root is computed.
 ///
-/// Invariant: `chunks.len() % BYTES_PER_CHUNK == 0`
+/// Invariant: `layer.len() % BYTES_PER_CHUNK == 0`
 /// Invariant: `leaf_count.next_power_of_two() == leaf_count`
 /// Invariant: `leaf_count != 0`
 /// Invariant: `leaf_count.trailing_zeros() < MAX_MERKLE_TREE_DEPTH`
-fn merkleize_chunks_with_virtual_padding(
-    chunks: &[u8],
+fn merkleize_layer_in_place(
+    layer: &mut [u8],
     leaf_count: usize,
 ) -> Result<Node, MerkleizationError> {
-    debug_assert!(chunks.len() % BYTES_PER_CHUNK == 0);
+    debug_assert!(layer.len().is_multiple_of(BYTES_PER_CHUNK));
     // NOTE: This also asserts that leaf_count != 0
     debug_assert!(leaf_count.next_power_of_two() == leaf_count);
     // SAFETY: this holds as long as leaf_count != 0 and usize is no longer than u64
     debug_assert!((leaf_count.trailing_zeros() as usize) < MAX_MERKLE_TREE_DEPTH);
 
-    let chunk_count = chunks.len() / BYTES_PER_CHUNK;
+    let chunk_count = layer.len() / BYTES_PER_CHUNK;
     let height = leaf_count.trailing_zeros() + 1;
 
     if chunk_count == 0 {
@@ -141,7 +147,6 @@ fn merkleize_chunks_with_virtual_padding(
         return Ok(CONTEXT[depth as usize].try_into().expect("can produce a single root chunk"))
     }
 
-    let mut layer = chunks.to_vec();
     // SAFETY: checked subtraction is unnecessary, as we return early when chunk_count == 0; qed
     let mut last_index = chunk_count - 1;
     let mut hasher = Sha256::new();
@@ -214,16 +219,40 @@ fn merkleize_chunks_with_virtual_padding(
     }
 
     // SAFETY: index is safe because layer.len() >= BYTES_PER_CHUNK:
-    // layer.len() == chunks.len()
-    // chunks.len() % BYTES_PER_CHUNK == 0 and chunks.len() != 0 (because chunk_count != 0)
-    // so chunks.len() >= BYTES_PER_CHUNK; qed
+    // layer.len() % BYTES_PER_CHUNK == 0 and layer.len() != 0 (because chunk_count != 0)
+    // so layer.len() >= BYTES_PER_CHUNK; qed
     Ok(layer[..BYTES_PER_CHUNK].try_into().expect("can produce a single root chunk"))
 }
 
+// NOTE: trees with up to this many chunks, e.g. most containers, are merkleized in place in a
+// buffer on the stack and larger trees are streamed through a `Merkleizer`
+const MAX_STACK_CHUNK_COUNT: usize = 32;
+
+// Return the root of the Merklization of a binary tree formed from `chunks`,
+// see `merkleize_layer_in_place` for the invariants.
+fn merkleize_chunks_with_virtual_padding(
+    chunks: &[u8],
+    leaf_count: usize,
+) -> Result<Node, MerkleizationError> {
+    if chunks.len() <= MAX_STACK_CHUNK_COUNT * BYTES_PER_CHUNK {
+        let mut buffer = [0u8; MAX_STACK_CHUNK_COUNT * BYTES_PER_CHUNK];
+        let layer = &mut buffer[..chunks.len()];
+        layer.copy_from_slice(chunks);
+        merkleize_layer_in_place(layer, leaf_count)
+    } else {
+        let mut merkleizer = Merkleizer::new(Some(leaf_count));
+        for chunk in chunks.chunks_exact(BYTES_PER_CHUNK) {
+            // SAFETY: `chunks_exact` yields slices of `BYTES_PER_CHUNK` bytes; qed
+            merkleizer.write_chunk(chunk.try_into().expect("is right size"))?;
+        }
+        merkleizer.finish()
+    }
+}
+
 // Return the root of the Merklization of a binary tree formed from `chunks`.
 // Invariant: `chunks.len() % BYTES_PER_CHUNK == 0`
 pub fn merkleize(chunks: &[u8], limit: Option<usize>) -> Result<Node, MerkleizationError> {
-    debug_assert!(chunks.len() % BYTES_PER_CHUNK == 0);
+    debug_assert!(chunks.len().is_multiple_of(BYTES_PER_CHUNK));
     let chunk_count = chunks.len() / BYTES_PER_CHUNK;
     let mut leaf_count = chunk_count.next_power_of_two();
     if let Some(limit) = limit {
@@ -235,13 +264,146 @@ pub fn merkleize(chunks: &[u8], limit: Option<usize>) -> Result<Node, Merkleizat
     merkleize_chunks_with_virtual_padding(chunks, leaf_count)
 }
 
+/// `Merkleizer` computes the same root as `merkleize` for chunks written one after another,
+/// holding onto at most one node for each level of the tree instead of all of the chunks.
+pub(crate) struct Merkleizer {
+    // the root of a complete subtree of `2^level` chunks still missing its right sibling, for
+    // each `level` where bit `level` of `count` is set
+    nodes: [Node; MAX_MERKLE_TREE_DEPTH],
+    count: usize,
+    limit: Option<usize>,
+    // bytes written that do not fill a chunk yet
+    buffer: Node,
+    buffer_len: usize,
+    hasher: Sha256,
+}
+
+impl Merkleizer {
+    /// Creates a `Merkleizer` for up to `limit` chunks, with the same meaning as in `merkleize`.
+    #[inline]
+    pub(crate) fn new(limit: Option<usize>) -> Self {
+        Self {
+            nodes: [Node::default(); MAX_MERKLE_TREE_DEPTH],
+            count: 0,
+            limit,
+            buffer: Node::default(),
+            buffer_len: 0,
+            hasher: Sha256::new(),
+        }
+    }
+
+    /// Packs `bytes` into chunks, following any bytes written before.
+    #[inline]
+    pub(crate) fn write_bytes(&mut self, mut bytes: &[u8]) -> Result<(), MerkleizationError> {
+        if self.buffer_len != 0 {
+            let len = bytes.len().min(BYTES_PER_CHUNK - self.buffer_len);
+            self.buffer.as_mut()[self.buffer_len..self.buffer_len + len]
+                .copy_from_slice(&bytes[..len]);
+            self.buffer_len += len;
+            bytes = &bytes[len..];
+            if self.buffer_len < BYTES_PER_CHUNK {
+                return Ok(())
+            }
+            self.buffer_len = 0;
+            self.push(self.buffer)?;
+        }
+        let mut chunks = bytes.chunks_exact(BYTES_PER_CHUNK);
+        for chunk in &mut chunks {
+            // SAFETY: `chunks_exact` yields slices of `BYTES_PER_CHUNK` bytes; qed
+            self.push(chunk.try_into().expect("is right size"))?;
+        }
+        let remainder = chunks.remainder();
+        self.buffer.as_mut()[..remainder.len()].copy_from_slice(remainder);
+        self.buffer_len = remainder.len();
+        Ok(())
+    }
+
+    /// Writes the next `chunk`, e.g. the root of an element of a composite type.
+    ///
+    /// Invariant: no partial chunk is pending from `write_bytes`
+    #[inline]
+    pub(crate) fn write_chunk(&mut self, chunk: Node) -> Result<(), MerkleizationError> {
+        debug_assert!(self.buffer_len == 0);
+        self.push(chunk)
+    }
+
+    #[inline]
+    fn push(&mut self, chunk: Node) -> Result<(), MerkleizationError> {
+        if let Some(limit) = self.limit {
+            if self.count >= limit {
+                return Err(MerkleizationError::InputExceedsLimit(limit))
+            }
+        }
+        let mut node = chunk;
+        let mut level = 0;
+        while (self.count >> level) & 1 == 1 {
+            let mut parent = Node::default();
+            hash_nodes(
+                &mut self.hasher,
+                self.nodes[level].as_ref(),
+                node.as_ref(),
+                parent.as_mut(),
+            );
+            node = parent;
+            level += 1;
+        }
+        self.nodes[level] = node;
+        self.count += 1;
+        Ok(())
+    }
+
+    /// Returns the root of the tree of all chunks written, padded with zero chunks up to the
+    /// limit, or the next power of two if there is no limit.
+    #[inline]
+    pub(crate) fn finish(mut self) -> Result<Node, MerkleizationError> {
+        if self.buffer_len != 0 {
+            self.buffer.as_mut()[self.buffer_len..].fill(0);
+            self.buffer_len = 0;
+            self.push(self.buffer)?;
+        }
+        let leaf_count = self.limit.unwrap_or(self.count).next_power_of_two();
+        let depth = leaf_count.trailing_zeros() as usize;
+        if self.count == leaf_count {
+            return Ok(self.nodes[depth])
+        }
+        // fold the pending subtrees from the right, padding with subtrees of zero chunks
+        let mut root: Option<Node> = None;
+        for level in 0..depth {
+            let zero = &CONTEXT[level];
+            let is_pending = (self.count >> level) & 1 == 1;
+            root = match (is_pending, root) {
+                (true, right) => {
+                    let right = right.as_ref().map(|node| node.as_ref()).unwrap_or(zero);
+                    let mut parent = Node::default();
+                    hash_nodes(
+                        &mut self.hasher,
+                        self.nodes[level].as_ref(),
+                        right,
+                        parent.as_mut(),
+                    );
+                    Some(parent)
+                }
+                (false, Some(left)) => {
+                    let mut parent = Node::default();
+                    hash_nodes(&mut self.hasher, left.as_ref(), zero, parent.as_mut());
+                    Some(parent)
+                }
+                (false, None) => None,
+            };
+        }
+        // SAFETY: index is safe because depth < MAX_MERKLE_TREE_DEPTH, see `merkleize`; qed
+        Ok(root
+            .unwrap_or_else(|| CONTEXT[depth].try_into().expect("can produce a single root chunk")))
+    }
+}
+
 fn mix_in_decoration(root: &Node, mut decoration: usize) -> Node {
     let decoration_data = decoration.hash_tree_root().expect("can merkleize usize");
 
     let mut hasher = Sha256::new();
-    let mut output = vec![0u8; BYTES_PER_CHUNK];
-    hash_nodes(&mut hasher, root.as_ref(), decoration_data.as_ref(), &mut output);
-    output.as_slice().try_into().expect("can extract root")
+    let mut output = Node::default();
+    hash_nodes(&mut hasher, root.as_ref(), decoration_data.as_ref(), output.as_mut());
+    output
 }
 
 pub(crate) fn mix_in_length(root: &Node, length: usize) -> Node {
@@ -252,27 +414,68 @@ pub fn mix_in_selector(root: &Node, selector: usize) -> Node {
     mix_in_decoration(root, selector)
 }
 
-pub(crate) fn elements_to_chunks<'a, T: Merkleized + 'a>(
-    elements: impl Iterator<Item = (usize, &'a mut T)>,
-    count: usize,
-) -> Result<Vec<u8>, MerkleizationError> {
-    let mut chunks = vec![0u8; count * BYTES_PER_CHUNK];
-    for (i, elem) in elements {
-        let chunk = elem.hash_tree_root()?;
-        let range = i * BYTES_PER_CHUNK..(i + 1) * BYTES_PER_CHUNK;
-        chunks[range].copy_from_slice(chunk.as_ref());
+// Return the root of the Merklization of `elements` with the same meaning of `limit` as in
+// `merkleize`, streaming the chunks of the elements through a `Merkleizer`.
+pub(crate) fn merkleize_elements<'a, T: SimpleSerialize + 'a>(
+    elements: impl Iterator<Item = &'a mut T>,
+    limit: Option<usize>,
+) -> Result<Node, MerkleizationError> {
+    let mut merkleizer = Merkleizer::new(limit);
+    if T::is_composite_type() {
+        for element in elements {
+            merkleizer.write_chunk(element.hash_tree_root()?)?;
+        }
+    } else if T::size_hint() <= BYTES_PER_CHUNK {
+        let size = T::size_hint();
+        for element in elements {
+            // NOTE: the root of a value of a basic type is its encoding, padded to a whole chunk
+            let root = element.hash_tree_root()?;
+            merkleizer.write_bytes(&root.as_ref()[..size])?;
+        }
+    } else {
+        // NOTE: values of basic types spanning several chunks, e.g. `[u8; 48]`, are encoded
+        let mut buffer = Vec::with_capacity(T::size_hint());
+        for element in elements {
+            buffer.clear();
+            element.serialize(&mut buffer)?;
+            merkleizer.write_bytes(&buffer)?;
+        }
     }
-    Ok(chunks)
+    merkleizer.finish()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate as ssz_rs;
-    use crate::prelude::*;
+    use crate::{prelude::*, ser::serialize_homogeneous_composite};
     use hex_literal::hex;
     use ssz_rs_derive::SimpleSerialize;
 
+    // Ensures `buffer` can be exactly broken up into `BYTES_PER_CHUNK` chunks of bytes
+    // via padding any partial chunks at the end of `buffer`
+    fn pack_bytes(buffer: &mut Vec<u8>) {
+        let incomplete_chunk_len = buffer.len() % BYTES_PER_CHUNK;
+        if incomplete_chunk_len != 0 {
+            // SAFETY: checked subtraction is unnecessary,
+            // as BYTES_PER_CHUNK > incomplete_chunk_len; qed
+            let bytes_to_pad = BYTES_PER_CHUNK - incomplete_chunk_len;
+            buffer.resize(buffer.len() + bytes_to_pad, 0);
+        }
+    }
+
+    // Packs serializations of `values` into the return buffer with the
+    // guarantee that `buffer.len() % BYTES_PER_CHUNK == 0`
+    fn pack<T>(values: &[T]) -> Result<Vec<u8>, MerkleizationError>
+    where
+        T: Serializable,
+    {
+        let mut buffer = vec![];
+        serialize_homogeneous_composite(values, &mut buffer)?;
+        pack_bytes(&mut buffer);
+        Ok(buffer)
+    }
+
     #[test]
     fn test_packing_basic_types_simple() {
         let b = true;
@@ -299,7 +502,7 @@ mod tests {
     #[test]
     fn test_packing_basic_types_multiple() {
         let data = U256::from_bytes_le([1u8; 32]);
-        let input = &[data.clone(), data.clone(), data];
+        let input = &[data, data, data];
         let result = pack(input).expect("can pack values");
 
         let expected = vec![1u8; 3 * 32];
@@ -326,7 +529,7 @@ mod tests {
     // Invariant: `leaf_count.next_power_of_two() == leaf_count`
     // NOTE: naive implementation, can make much more efficient
     fn merkleize_chunks(chunks: &[u8], leaf_count: usize) -> Result<Node, MerkleizationError> {
-        debug_assert!(chunks.len() % BYTES_PER_CHUNK == 0);
+        debug_assert!(chunks.len().is_multiple_of(BYTES_PER_CHUNK));
         debug_assert!(leaf_count.next_power_of_two() == leaf_count);
 
         // SAFETY: checked subtraction is unnecessary,
@@ -415,6 +618,65 @@ mod tests {
         assert_eq!(root, hex!("9317695d95b5a3b46e976b5a9cbfcfccb600accaddeda9ac867cc9669b862979"));
     }
 
+    #[test]
+    fn test_merkleizer() {
+        for chunk_count in [0, 1, 2, 3, 5, 8, 13, 32, 33, 100] {
+            let chunks = (0..chunk_count * BYTES_PER_CHUNK).map(|i| i as u8).collect::<Vec<_>>();
+            for limit in [None, Some(chunk_count), Some(chunk_count + 1), Some(1024)] {
+                let expected = merkleize(&chunks, limit).unwrap();
+
+                let mut merkleizer = Merkleizer::new(limit);
+                for chunk in chunks.chunks(BYTES_PER_CHUNK) {
+                    merkleizer.write_chunk(chunk.try_into().unwrap()).unwrap();
+                }
+                assert_eq!(merkleizer.finish().unwrap(), expected);
+
+                let mut merkleizer = Merkleizer::new(limit);
+                for bytes in chunks.chunks(7) {
+                    merkleizer.write_bytes(bytes).unwrap();
+                }
+                assert_eq!(merkleizer.finish().unwrap(), expected);
+            }
+        }
+
+        let mut merkleizer = Merkleizer::new(Some(1));
+        merkleizer.write_bytes(&[1; BYTES_PER_CHUNK]).unwrap();
+        assert!(merkleizer.write_bytes(&[1]).is_ok());
+        assert!(merkleizer.finish().is_err());
+    }
+
+    #[test]
+    fn test_merkleize_elements() {
+        for count in [0, 1, 2, 3, 5, 8, 13, 32, 33, 100] {
+            let mut values = (0..count as u16).collect::<Vec<_>>();
+            let chunks = pack(&values).unwrap();
+            for limit in [None, Some(chunks.len() / BYTES_PER_CHUNK), Some(1024)] {
+                let expected = merkleize(&chunks, limit).unwrap();
+                assert_eq!(merkleize_elements(values.iter_mut(), limit).unwrap(), expected);
+            }
+
+            let mut values = (0..count as u64).map(|i| [i; 5]).collect::<Vec<_>>();
+            let chunks = pack(&values).unwrap();
+            for limit in [None, Some(chunks.len() / BYTES_PER_CHUNK), Some(1024)] {
+                let expected = merkleize(&chunks, limit).unwrap();
+                assert_eq!(merkleize_elements(values.iter_mut(), limit).unwrap(), expected);
+            }
+
+            let mut values = (0..count as u8)
+                .map(|i| List::<u8, 4>::try_from(vec![i]).unwrap())
+                .collect::<Vec<_>>();
+            let mut chunks = vec![];
+            for value in &mut values {
+                chunks.extend_from_slice(value.hash_tree_root().unwrap().as_ref());
+            }
+            for limit in [None, Some(count), Some(1024)] {
+                let expected = merkleize(&chunks, limit).unwrap();
+                assert_eq!(merkleize_elements(values.iter_mut(), limit).unwrap(), expected);
+            }
+        }
+        assert!(merkleize_elements([1u64; 5].iter_mut(), Some(1)).is_err());
+    }
+
     #[test]
     fn test_hash_tree_root_of_list() {
         let mut a_list = List::<u16, 1024>::try_from(vec![
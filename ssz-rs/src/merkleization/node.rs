@@ -1,7 +1,7 @@
 use crate::{lib::*, merkleization::BYTES_PER_CHUNK, prelude::*, utils::write_bytes_to_lower_hex};
 
 /// A node in a merkle tree.
-#[derive(Default, Clone, Copy, Eq, Hash, SimpleSerialize)]
+#[derive(Default, Clone, Copy, Eq, Hash, Serializable)]
 #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Node(
     #[cfg_attr(feature = "serde", serde(with = "crate::serde::as_hex"))] [u8; BYTES_PER_CHUNK],
@@ -46,6 +46,21 @@ impl TryFrom<&[u8]> for Node {
     }
 }
 
+// NOTE: a node is its own root, so skip packing its bytes
+impl Merkleized for Node {
+    fn hash_tree_root(&mut self) -> Result<Node, MerkleizationError> {
+        Ok(*self)
+    }
+}
+
+impl HasSchema for Node {
+    fn schema() -> Schema {
+        <[u8; BYTES_PER_CHUNK]>::schema()
+    }
+}
+
+impl SimpleSerialize for Node {}
+
 impl<T> PartialEq<T> for Node
 where
     T: AsRef<[u8]>,
@@ -5,20 +5,30 @@ use sha2::{Digest, Sha256};
 /// against the `root` given the other metadata.
 pub fn is_valid_merkle_branch<'a>(
     leaf: &Node,
-    mut branch: impl Iterator<Item = &'a Node>,
+    branch: impl Iterator<Item = &'a Node>,
     depth: usize,
     index: usize,
     root: &Node,
 ) -> bool {
+    compute_merkle_root(leaf, branch, depth, index).as_ref() == Some(root)
+}
+
+/// `compute_merkle_root` computes the root of the Merkle tree with the given `leaf`
+/// at `index` and depth `depth`, given the `branch` of sibling nodes from the leaf upwards.
+///
+/// Returns `None` if the `branch` has fewer than `depth` nodes.
+pub fn compute_merkle_root<'a>(
+    leaf: &Node,
+    mut branch: impl Iterator<Item = &'a Node>,
+    depth: usize,
+    index: usize,
+) -> Option<Node> {
     let mut value = *leaf;
 
     let mut hasher = Sha256::new();
     for i in 0..depth {
-        let next_node = match branch.next() {
-            Some(node) => node,
-            None => return false,
-        };
-        if (index / 2usize.pow(i as u32)) % 2 != 0 {
+        let next_node = branch.next()?;
+        if !(index / 2usize.pow(i as u32)).is_multiple_of(2) {
             hasher.update(next_node.as_ref());
             hasher.update(value.as_ref());
         } else {
@@ -27,7 +37,27 @@ pub fn is_valid_merkle_branch<'a>(
         }
         value.as_mut().copy_from_slice(&hasher.finalize_reset());
     }
-    value == *root
+    Some(value)
+}
+
+/// `update_merkle_root` computes the new root of the Merkle tree with the given `root` after
+/// replacing `leaf` with `new_leaf`, without access to the rest of the tree.
+///
+/// As the branch of a leaf does not depend on the leaf itself, the same `branch` that proves
+/// `leaf` against `root` proves `new_leaf` against the returned root. Returns `None` if the
+/// branch is not a valid proof of `leaf` against `root`.
+pub fn update_merkle_root<'a>(
+    leaf: &Node,
+    new_leaf: &Node,
+    branch: impl Iterator<Item = &'a Node> + Clone,
+    depth: usize,
+    index: usize,
+    root: &Node,
+) -> Option<Node> {
+    if !is_valid_merkle_branch(leaf, branch.clone(), depth, index, root) {
+        return None
+    }
+    compute_merkle_root(new_leaf, branch, depth, index)
 }
 
 #[cfg(test)]
@@ -61,4 +91,36 @@ mod tests {
 
         assert!(is_valid_merkle_branch(&leaf, branch.iter(), depth, index, &root))
     }
+
+    #[test]
+    fn test_update_merkle_root() {
+        use crate::prelude::*;
+
+        #[derive(Default, SimpleSerialize)]
+        struct Foo {
+            a: u64,
+            b: u64,
+            c: u64,
+            d: u64,
+        }
+
+        let mut foo = Foo { a: 1, b: 2, c: 3, d: 4 };
+        let root = foo.hash_tree_root().unwrap();
+
+        let mut hasher = Sha256::new();
+        hasher.update(1u64.hash_tree_root().unwrap().as_ref());
+        hasher.update(2u64.hash_tree_root().unwrap().as_ref());
+        let left = Node::try_from(hasher.finalize().as_ref()).unwrap();
+        let branch = [4u64.hash_tree_root().unwrap(), left];
+        let leaf = 3u64.hash_tree_root().unwrap();
+        let new_leaf = 33u64.hash_tree_root().unwrap();
+
+        let new_root = update_merkle_root(&leaf, &new_leaf, branch.iter(), 2, 2, &root).unwrap();
+        foo.c = 33;
+        assert_eq!(new_root, foo.hash_tree_root().unwrap());
+        assert!(is_valid_merkle_branch(&new_leaf, branch.iter(), 2, 2, &new_root));
+
+        assert!(update_merkle_root(&new_leaf, &leaf, branch.iter(), 2, 2, &root).is_none());
+        assert!(compute_merkle_root(&leaf, branch[..1].iter(), 2, 2).is_none());
+    }
 }
@@ -0,0 +1,463 @@
+use crate::{
+    de::DeserializeError,
+    error::Error,
+    lib::*,
+    merkleization::{
+        compute_merkle_root, tree_depth, MerkleizationError, Node, BITS_PER_CHUNK, BYTES_PER_CHUNK,
+    },
+    schema::{child_index, join_encodings, HasSchema, PathElement, Schema},
+    SimpleSerialize,
+};
+use sha2::{Digest, Sha256};
+
+const BITS_PER_BYTE: usize = crate::BITS_PER_BYTE as usize;
+
+/// `Partial` is a value of type `T` of which only some parts are known, e.g. from proofs
+/// against its hash tree root.
+///
+/// The value is represented by the known nodes of its merkle tree, keyed by their generalized
+/// index. Every other node is computed from its children if they are known and is opaque
+/// otherwise. Values inside of `T` can be read with `get` if every node holding their data is
+/// known.
+///
+/// ```
+/// # use ssz_rs::prelude::*;
+/// #[derive(Default, Debug, SimpleSerialize)]
+/// struct Foo {
+///     a: u64,
+///     b: List<u8, 32>,
+/// }
+///
+/// let mut foo = Foo { a: 23, b: List::try_from(vec![1, 2, 3]).unwrap() };
+/// let root = foo.hash_tree_root().unwrap();
+///
+/// // a proof of `a` against `root`
+/// let leaf = 23u64.hash_tree_root().unwrap();
+/// let branch = [foo.b.hash_tree_root().unwrap()];
+///
+/// let mut partial = Partial::<Foo>::new(root);
+/// partial.add_proof(2, leaf, &branch).unwrap();
+/// assert_eq!(partial.get::<u64>(&["a".into()]).unwrap(), 23);
+/// assert!(partial.get::<List<u8, 32>>(&["b".into()]).is_err());
+/// assert_eq!(partial.hash_tree_root(), Some(root));
+/// ```
+pub struct Partial<T> {
+    nodes: BTreeMap<usize, Node>,
+    _type: PhantomData<T>,
+}
+
+impl<T> Debug for Partial<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Partial").field("nodes", &self.nodes).finish()
+    }
+}
+
+impl<T> Clone for Partial<T> {
+    fn clone(&self) -> Self {
+        Self { nodes: self.nodes.clone(), _type: PhantomData }
+    }
+}
+
+impl<T: SimpleSerialize + HasSchema> Partial<T> {
+    /// Creates a `Partial` where only the hash tree `root` of the value is known.
+    pub fn new(root: Node) -> Self {
+        Self { nodes: BTreeMap::from([(1, root)]), _type: PhantomData }
+    }
+
+    /// Creates a `Partial` from known `nodes`, without any root to check them against.
+    pub fn from_nodes(nodes: impl IntoIterator<Item = (usize, Node)>) -> Self {
+        Self { nodes: nodes.into_iter().collect(), _type: PhantomData }
+    }
+
+    /// Adds the `leaf` at `generalized_index` given its `branch` of sibling nodes,
+    /// from the leaf upwards, after checking them against the hash tree root.
+    pub fn add_proof(
+        &mut self,
+        generalized_index: usize,
+        leaf: Node,
+        branch: &[Node],
+    ) -> Result<(), MerkleizationError> {
+        if generalized_index == 0 {
+            return Err(MerkleizationError::InvalidGeneralizedIndex(generalized_index))
+        }
+        let depth = (usize::BITS - 1 - generalized_index.leading_zeros()) as usize;
+        let index = generalized_index ^ (1 << depth);
+        let root = compute_merkle_root(&leaf, branch.iter(), depth, index)
+            .ok_or(MerkleizationError::MissingNode(generalized_index))?;
+        self.check_root(&root)?;
+
+        self.nodes.insert(generalized_index, leaf);
+        for (i, node) in branch.iter().take(depth).enumerate() {
+            self.nodes.insert((generalized_index >> i) ^ 1, *node);
+        }
+        Ok(())
+    }
+
+    /// Adds the `nodes` of a multiproof, keyed by their generalized index, after checking
+    /// that they determine the known hash tree root.
+    ///
+    /// The root is recomputed from the `nodes` alone, so a multiproof must not contain the root
+    /// itself or any node that can be computed from the other nodes, i.e. an ancestor of
+    /// another node. Such nodes would not be checked against the root.
+    pub fn add_multiproof(
+        &mut self,
+        nodes: impl IntoIterator<Item = (usize, Node)>,
+    ) -> Result<(), MerkleizationError> {
+        let proof = Self::from_nodes(nodes);
+        for generalized_index in proof.nodes.keys() {
+            if *generalized_index <= 1 {
+                return Err(MerkleizationError::InvalidGeneralizedIndex(*generalized_index))
+            }
+            let mut ancestor = generalized_index / 2;
+            while ancestor > 1 {
+                if proof.nodes.contains_key(&ancestor) {
+                    return Err(MerkleizationError::InvalidGeneralizedIndex(ancestor))
+                }
+                ancestor /= 2;
+            }
+        }
+        // NOTE: as no node of `proof` is an ancestor of another, every parent is computed
+        // from the supplied nodes
+        let root = proof.node(1).ok_or(MerkleizationError::MissingNode(1))?;
+        self.check_root(&root)?;
+        self.nodes.extend(proof.nodes);
+        Ok(())
+    }
+
+    fn check_root(&self, root: &Node) -> Result<(), MerkleizationError> {
+        match self.node(1) {
+            Some(expected) if expected != *root => Err(MerkleizationError::InvalidProof),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the hash tree root of the value, if it can be computed from the known nodes.
+    pub fn hash_tree_root(&self) -> Option<Node> {
+        self.node(1)
+    }
+
+    /// Returns the node at `generalized_index`, if it is known or can be computed from known
+    /// nodes.
+    pub fn node(&self, generalized_index: usize) -> Option<Node> {
+        if let Some(node) = self.nodes.get(&generalized_index) {
+            return Some(*node)
+        }
+        if !self.has_descendants(generalized_index) {
+            return None
+        }
+        let left = self.node(generalized_index.checked_mul(2)?)?;
+        let right = self.node(2 * generalized_index + 1)?;
+        let mut hasher = Sha256::new();
+        hasher.update(left.as_ref());
+        hasher.update(right.as_ref());
+        Node::try_from(hasher.finalize().as_ref()).ok()
+    }
+
+    fn has_descendants(&self, generalized_index: usize) -> bool {
+        let last = match self.nodes.keys().next_back() {
+            Some(last) => *last,
+            None => return false,
+        };
+        let mut start = generalized_index;
+        let mut end = generalized_index + 1;
+        while let (Some(next_start), Some(next_end)) = (start.checked_mul(2), end.checked_mul(2)) {
+            if next_start > last {
+                break
+            }
+            if self.nodes.range(next_start..next_end).next().is_some() {
+                return true
+            }
+            start = next_start;
+            end = next_end;
+        }
+        false
+    }
+
+    /// Returns the value at `path` inside of the value, which must be of type `U`, if all the
+    /// nodes holding its data are known.
+    pub fn get<U: SimpleSerialize + HasSchema>(&self, path: &[PathElement]) -> Result<U, Error> {
+        let location = T::schema().locate(path)?;
+        if location.schema != U::schema() {
+            return Err(MerkleizationError::InvalidPath(path.to_vec()).into())
+        }
+        let encoding = if location.schema.is_basic() {
+            let chunk = self.chunk(location.generalized_index)?;
+            chunk.as_ref()[location.offset..location.offset + location.schema.size_hint()].to_vec()
+        } else {
+            self.encoding_of(&location.schema, location.generalized_index)?
+        };
+        Ok(U::deserialize(&encoding)?)
+    }
+
+    /// Returns the whole value, if all the nodes holding its data are known.
+    pub fn value(&self) -> Result<T, Error> {
+        self.get(&[])
+    }
+
+    fn chunk(&self, generalized_index: usize) -> Result<Node, MerkleizationError> {
+        self.node(generalized_index).ok_or(MerkleizationError::MissingNode(generalized_index))
+    }
+
+    // Reads the length mixed into the node at `generalized_index`, checking it is within `bound`.
+    fn length(&self, generalized_index: usize, bound: usize) -> Result<usize, MerkleizationError> {
+        let chunk = self.chunk(2 * generalized_index + 1)?;
+        let mut length = [0u8; 8];
+        length.copy_from_slice(&chunk.as_ref()[..8]);
+        match usize::try_from(u64::from_le_bytes(length)) {
+            Ok(length) if length <= bound => Ok(length),
+            _ => Err(MerkleizationError::InputExceedsLimit(bound)),
+        }
+    }
+
+    // Concatenates the first `count` chunks of the tree of the given `depth` under the node at
+    // `generalized_index`, truncated to `len` bytes.
+    fn packed(
+        &self,
+        generalized_index: usize,
+        depth: u32,
+        count: usize,
+        len: usize,
+    ) -> Result<Vec<u8>, MerkleizationError> {
+        let mut bytes = Vec::with_capacity(count * BYTES_PER_CHUNK);
+        for i in 0..count {
+            let chunk = self.chunk(child_index(generalized_index, depth, i)?)?;
+            bytes.extend_from_slice(chunk.as_ref());
+        }
+        bytes.truncate(len);
+        Ok(bytes)
+    }
+
+    // Reconstructs the encoding of the value of type `schema` rooted at `generalized_index`.
+    fn encoding_of(&self, schema: &Schema, generalized_index: usize) -> Result<Vec<u8>, Error> {
+        let encoding = match schema {
+            Schema::Uint(size) => self.packed(generalized_index, 0, 1, *size)?,
+            Schema::Boolean => self.packed(generalized_index, 0, 1, 1)?,
+            Schema::Bitvector(bound) => {
+                let count = bound.div_ceil(BITS_PER_CHUNK);
+                let len = bound.div_ceil(BITS_PER_BYTE);
+                self.packed(generalized_index, tree_depth(count), count, len)?
+            }
+            Schema::Bitlist(bound) => {
+                let len = self.length(generalized_index, *bound)?;
+                let depth = tree_depth(bound.div_ceil(BITS_PER_CHUNK));
+                let count = len.div_ceil(BITS_PER_CHUNK);
+                let byte_len = len.div_ceil(BITS_PER_BYTE);
+                let mut bytes = self.packed(2 * generalized_index, depth, count, byte_len)?;
+                let delimiter = len % BITS_PER_BYTE;
+                match bytes.last_mut() {
+                    Some(last) if delimiter != 0 => *last |= 1 << delimiter,
+                    _ => bytes.push(1),
+                }
+                bytes
+            }
+            Schema::Vector { element, length } => {
+                self.sequence_encoding(element, *length, *length, generalized_index)?
+            }
+            Schema::List { element, limit } => {
+                let len = self.length(generalized_index, *limit)?;
+                self.sequence_encoding(element, *limit, len, 2 * generalized_index)?
+            }
+            Schema::Container { fields, .. } => {
+                let depth = tree_depth(fields.len());
+                let parts = fields
+                    .iter()
+                    .enumerate()
+                    .map(|(i, field)| {
+                        let generalized_index = child_index(generalized_index, depth, i)?;
+                        self.encoding_of(&field.schema, generalized_index)
+                    })
+                    .collect::<Result<Vec<_>, _>>()?;
+                let parts = parts
+                    .iter()
+                    .zip(fields)
+                    .map(|(part, field)| (part.as_slice(), field.schema.is_variable_size()))
+                    .collect::<Vec<_>>();
+                join_encodings(&parts)
+            }
+            Schema::Union { variants, .. } => {
+                let selector = self.chunk(2 * generalized_index + 1)?.as_ref()[0];
+                let variant = variants
+                    .iter()
+                    .find(|v| v.selector == selector)
+                    .ok_or(DeserializeError::InvalidByte(selector))?;
+                let mut encoding = vec![selector];
+                if let Some(schema) = &variant.schema {
+                    encoding.extend(self.encoding_of(schema, 2 * generalized_index)?);
+                }
+                encoding
+            }
+        };
+        Ok(encoding)
+    }
+
+    // Reconstructs the encoding of `len` elements of a sequence with up to `bound` elements,
+    // whose data is rooted at `generalized_index`.
+    fn sequence_encoding(
+        &self,
+        element: &Schema,
+        bound: usize,
+        len: usize,
+        generalized_index: usize,
+    ) -> Result<Vec<u8>, Error> {
+        if element.is_basic() {
+            let size = element.size_hint();
+            let depth = tree_depth((bound * size).div_ceil(BYTES_PER_CHUNK));
+            let count = (len * size).div_ceil(BYTES_PER_CHUNK);
+            return Ok(self.packed(generalized_index, depth, count, len * size)?)
+        }
+
+        let depth = tree_depth(bound);
+        let parts = (0..len)
+            .map(|i| self.encoding_of(element, child_index(generalized_index, depth, i)?))
+            .collect::<Result<Vec<_>, _>>()?;
+        let is_variable = element.is_variable_size();
+        let parts = parts.iter().map(|part| (part.as_slice(), is_variable)).collect::<Vec<_>>();
+        Ok(join_encodings(&parts))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[derive(Debug, Default, Clone, PartialEq, Eq, SimpleSerialize)]
+    struct Inner {
+        x: u8,
+        y: u8,
+    }
+
+    #[derive(Debug, Default, Clone, PartialEq, Eq, SimpleSerialize)]
+    struct Foo {
+        a: u64,
+        b: List<u16, 32>,
+        c: Inner,
+        d: Option<u32>,
+    }
+
+    fn hash(left: &Node, right: &Node) -> Node {
+        let mut hasher = Sha256::new();
+        hasher.update(left.as_ref());
+        hasher.update(right.as_ref());
+        Node::try_from(hasher.finalize().as_ref()).unwrap()
+    }
+
+    fn chunk(bytes: &[u8]) -> Node {
+        let mut chunk = Node::default();
+        chunk.as_mut()[..bytes.len()].copy_from_slice(bytes);
+        chunk
+    }
+
+    fn foo() -> Foo {
+        Foo {
+            a: 7,
+            b: List::try_from((0..20).collect::<Vec<_>>()).unwrap(),
+            c: Inner { x: 1, y: 2 },
+            d: Some(9),
+        }
+    }
+
+    #[test]
+    fn test_generalized_index() {
+        let schema = Foo::schema();
+        assert_eq!(schema.generalized_index(&[]).unwrap(), 1);
+        assert_eq!(schema.generalized_index(&["b".into(), PathElement::Length]).unwrap(), 11);
+        // `b` has 2 chunks of data under 10, with 16 elements each
+        assert_eq!(schema.generalized_index(&["b".into(), 17.into()]).unwrap(), 21);
+        assert_eq!(schema.generalized_index(&["c".into(), "y".into()]).unwrap(), 13);
+        assert_eq!(schema.generalized_index(&["d".into(), "Some".into()]).unwrap(), 14);
+        assert!(schema.generalized_index(&["b".into(), 32.into()]).is_err());
+        assert!(schema.generalized_index(&["a".into(), 0.into()]).is_err());
+        assert!(schema.generalized_index(&["e".into()]).is_err());
+    }
+
+    #[test]
+    fn test_partial() {
+        let mut value = foo();
+        let root = value.hash_tree_root().unwrap();
+        let a = value.a.hash_tree_root().unwrap();
+        let b = value.b.hash_tree_root().unwrap();
+        let c = value.c.hash_tree_root().unwrap();
+        let d = value.d.hash_tree_root().unwrap();
+
+        let mut partial = Partial::<Foo>::new(root);
+        let data = serialize(&value.b).unwrap();
+        let nodes = [
+            (4, a),
+            (20, chunk(&data[..32])),
+            (21, chunk(&data[32..])),
+            (11, chunk(&20u64.to_le_bytes())),
+            (3, hash(&c, &d)),
+        ];
+        partial.add_multiproof(nodes).unwrap();
+        assert_eq!(partial.hash_tree_root(), Some(root));
+        assert_eq!(partial.node(5), Some(b));
+        assert_eq!(partial.get::<u64>(&["a".into()]).unwrap(), 7);
+        assert_eq!(partial.get::<List<u16, 32>>(&["b".into()]).unwrap(), value.b);
+        assert_eq!(partial.get::<u16>(&["b".into(), 17.into()]).unwrap(), 17);
+        assert!(partial.get::<u8>(&["c".into(), "x".into()]).is_err());
+        assert!(partial.get::<u16>(&["a".into()]).is_err());
+        assert!(partial.value().is_err());
+
+        // a proof of `c.x`
+        let branch = [chunk(&[2]), d, partial.node(2).unwrap()];
+        assert!(partial.add_proof(12, chunk(&[3]), &branch).is_err());
+        partial.add_proof(12, chunk(&[1]), &branch).unwrap();
+        assert_eq!(partial.get::<u8>(&["c".into(), "x".into()]).unwrap(), 1);
+        assert_eq!(partial.get::<Inner>(&["c".into()]).unwrap(), value.c);
+
+        // `d` is a union with the payload at 14 and selector at 15
+        partial
+            .add_multiproof([(4, a), (5, b), (6, c), (14, chunk(&[9])), (15, chunk(&[1]))])
+            .unwrap();
+        assert_eq!(partial.value().unwrap(), value);
+        assert_eq!(partial.hash_tree_root(), Some(root));
+    }
+
+    #[test]
+    fn test_partial_invalid_multiproof() {
+        let mut value = foo();
+        let root = value.hash_tree_root().unwrap();
+        let mut partial = Partial::<Foo>::new(root);
+        assert!(partial.add_multiproof([(2, Node::default()), (3, Node::default())]).is_err());
+        assert!(partial.add_multiproof([(2, Node::default())]).is_err());
+        assert_eq!(partial.hash_tree_root(), Some(root));
+    }
+
+    #[test]
+    fn test_partial_multiproof_with_redundant_nodes() {
+        let mut value = foo();
+        let root = value.hash_tree_root().unwrap();
+        let a = value.a.hash_tree_root().unwrap();
+        let b = value.b.hash_tree_root().unwrap();
+        let c = value.c.hash_tree_root().unwrap();
+        let d = value.d.hash_tree_root().unwrap();
+        let garbage = chunk(&[0xff; 8]);
+
+        let mut partial = Partial::<Foo>::new(root);
+        // the root itself is not a proof of anything
+        assert!(partial.add_multiproof([(1, root), (4, garbage)]).is_err());
+        // `4` would be redundant given `2`
+        let tampered = [(2, hash(&a, &b)), (3, hash(&c, &d)), (4, garbage)];
+        assert!(partial.add_multiproof(tampered).is_err());
+        assert!(partial.get::<u64>(&["a".into()]).is_err());
+
+        partial.add_multiproof([(4, a), (5, b), (3, hash(&c, &d))]).unwrap();
+        assert_eq!(partial.get::<u64>(&["a".into()]).unwrap(), 7);
+    }
+
+    #[test]
+    fn test_partial_length_exceeding_limit() {
+        let value = foo();
+        let data = serialize(&value.b).unwrap();
+        for length in [33, u64::MAX] {
+            let partial = Partial::<Foo>::from_nodes([
+                (20, chunk(&data[..32])),
+                (21, chunk(&data[32..])),
+                (11, chunk(&length.to_le_bytes())),
+            ]);
+            let err = partial.get::<List<u16, 32>>(&["b".into()]).unwrap_err();
+            assert!(matches!(err, Error::Merkleization(MerkleizationError::InputExceedsLimit(32))));
+        }
+    }
+}
@@ -0,0 +1,367 @@
+use crate::{
+    error::Error,
+    lib::*,
+    merkleization::{
+        tree_depth, GeneralizedIndexPath, MerkleizationError, Node, BITS_PER_CHUNK, BYTES_PER_CHUNK,
+    },
+    schema::{element_spans, field_spans, join_encodings, split_bitlist, HasSchema, Schema},
+    utils::serialize,
+    SimpleSerialize,
+};
+
+const BITS_PER_BYTE: usize = crate::BITS_PER_BYTE as usize;
+
+/// The new contents of the node addressed by a generalized index.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Replacement {
+    /// A new leaf chunk, e.g. taken from a proof.
+    ///
+    /// The chunk must address a basic value, a chunk of packed basic values or of bits, or a
+    /// value whose hash tree root is the chunk itself, like a `Vector<u8, 32>`.
+    Leaf(Node),
+    /// The SSZ encoding of a new value for the subtree rooted at the generalized index.
+    Subtree(Vec<u8>),
+}
+
+/// `apply_patches` replaces the nodes of the merkle tree of `value` addressed by the given
+/// generalized indices, in order, and returns the new hash tree root of `value`.
+///
+/// Generalized indices must address either a leaf chunk of the tree or the root of some SSZ
+/// value contained in `value`. Internal nodes that do not correspond to a value, the length
+/// of a `List` or `Bitlist`, the selector of a union and chunks past the end of the data
+/// (i.e. padding) cannot be replaced. If any patch is invalid, `value` is left unchanged.
+///
+/// ```
+/// # use ssz_rs::prelude::*;
+/// #[derive(Default, Debug, SimpleSerialize)]
+/// struct Foo {
+///     a: u16,
+///     b: List<u8, 4>,
+/// }
+///
+/// let mut foo = Foo::default();
+/// // `a` has the generalized index 2 and `b` the generalized index 3
+/// let root = apply_patches(
+///     &mut foo,
+///     [(2, Replacement::Leaf(Node::try_from([7u8; 32].as_ref()).unwrap()))],
+/// );
+/// assert!(root.is_err());
+/// apply_patches(&mut foo, [(3, Replacement::Subtree(vec![1, 2, 3]))]).unwrap();
+/// assert_eq!(foo.b[..], [1, 2, 3]);
+/// ```
+pub fn apply_patches<T: SimpleSerialize + HasSchema>(
+    value: &mut T,
+    patches: impl IntoIterator<Item = (usize, Replacement)>,
+) -> Result<Node, Error> {
+    let schema = T::schema();
+    let mut encoding = serialize(value)?;
+    for (generalized_index, replacement) in patches {
+        encoding = patch_encoding(&schema, &encoding, generalized_index, &replacement)?;
+    }
+    let mut patched = T::deserialize(&encoding)?;
+    let root = patched.hash_tree_root()?;
+    *value = patched;
+    Ok(root)
+}
+
+// Returns the encoding of the value of type `schema` given by `encoding` after replacing the
+// node at `generalized_index`.
+pub(crate) fn patch_encoding(
+    schema: &Schema,
+    encoding: &[u8],
+    generalized_index: usize,
+    replacement: &Replacement,
+) -> Result<Vec<u8>, Error> {
+    let path = GeneralizedIndexPath::new(generalized_index)
+        .ok_or(MerkleizationError::InvalidGeneralizedIndex(generalized_index))?;
+    let patcher = Patcher { generalized_index, replacement };
+    patcher.visit(schema, encoding, path)
+}
+
+struct Patcher<'a> {
+    generalized_index: usize,
+    replacement: &'a Replacement,
+}
+
+impl Patcher<'_> {
+    fn invalid_index(&self) -> Error {
+        MerkleizationError::InvalidGeneralizedIndex(self.generalized_index).into()
+    }
+
+    fn invalid_replacement(&self) -> Error {
+        MerkleizationError::InvalidReplacement(self.generalized_index).into()
+    }
+
+    fn visit(
+        &self,
+        schema: &Schema,
+        encoding: &[u8],
+        mut path: GeneralizedIndexPath,
+    ) -> Result<Vec<u8>, Error> {
+        if path.is_empty() {
+            if let Replacement::Subtree(value) = self.replacement {
+                if !schema.is_variable_size() && value.len() != schema.size_hint() {
+                    return Err(self.invalid_replacement())
+                }
+                return Ok(value.clone())
+            }
+        }
+
+        match schema {
+            Schema::Uint(..) | Schema::Boolean => {
+                if !path.is_empty() {
+                    return Err(self.invalid_index())
+                }
+                self.replace_chunk(encoding, 0)
+            }
+            Schema::Bitvector(bound) => {
+                let index = self.take_leaf(&mut path, chunk_count(*bound))?;
+                self.replace_chunk(encoding, index)
+            }
+            Schema::Bitlist(bound) => {
+                self.take_data(&mut path)?;
+                let index = self.take_leaf(&mut path, chunk_count(*bound))?;
+                let (bytes, len) = split_bitlist(encoding)?;
+                let mut bytes = self.replace_chunk(&bytes, index)?;
+                let delimiter = len % BITS_PER_BYTE;
+                if delimiter == 0 {
+                    bytes.push(1);
+                } else {
+                    // SAFETY: index is safe because `len` is not a multiple of 8, so there is
+                    // at least one byte; qed
+                    let last = bytes.last_mut().expect("not empty");
+                    if *last >> delimiter != 0 {
+                        return Err(self.invalid_replacement())
+                    }
+                    *last |= 1 << delimiter;
+                }
+                Ok(bytes)
+            }
+            Schema::Container { fields, .. } => {
+                let depth = tree_depth(fields.len());
+                let index = path.take(depth).ok_or_else(|| self.invalid_index())?;
+                let field = fields.get(index).ok_or_else(|| self.invalid_index())?;
+                let spans = field_spans(fields, encoding)?;
+                let patched = self.visit(&field.schema, &encoding[spans[index].clone()], path)?;
+                let parts = fields
+                    .iter()
+                    .zip(spans)
+                    .enumerate()
+                    .map(|(i, (field, span))| {
+                        let part = if i == index { patched.as_slice() } else { &encoding[span] };
+                        (part, field.schema.is_variable_size())
+                    })
+                    .collect::<Vec<_>>();
+                Ok(join_encodings(&parts))
+            }
+            Schema::Vector { element, length } => {
+                self.visit_sequence(element, *length, encoding, path)
+            }
+            Schema::List { element, limit } => {
+                self.take_data(&mut path)?;
+                self.visit_sequence(element, *limit, encoding, path)
+            }
+            Schema::Union { variants, .. } => {
+                self.take_data(&mut path)?;
+                let (selector, payload) =
+                    encoding.split_first().ok_or_else(|| self.invalid_index())?;
+                let schema = variants
+                    .iter()
+                    .find(|v| v.selector == *selector)
+                    .and_then(|v| v.schema.as_ref())
+                    .ok_or_else(|| self.invalid_index())?;
+                let mut patched = vec![*selector];
+                patched.extend(self.visit(schema, payload, path)?);
+                Ok(patched)
+            }
+        }
+    }
+
+    // Visits the elements of a `Vector` or the data of a `List`, with up to `bound` elements.
+    fn visit_sequence(
+        &self,
+        element: &Schema,
+        bound: usize,
+        encoding: &[u8],
+        mut path: GeneralizedIndexPath,
+    ) -> Result<Vec<u8>, Error> {
+        if element.is_basic() {
+            let count = (bound * element.size_hint()).div_ceil(BYTES_PER_CHUNK);
+            let index = self.take_leaf(&mut path, count)?;
+            return self.replace_chunk(encoding, index)
+        }
+
+        let index = path.take(tree_depth(bound)).ok_or_else(|| self.invalid_index())?;
+        let spans = element_spans(element, encoding)?;
+        let span = spans.get(index).ok_or_else(|| self.invalid_index())?;
+        let patched = self.visit(element, &encoding[span.clone()], path)?;
+        let is_variable = element.is_variable_size();
+        let parts = spans
+            .iter()
+            .enumerate()
+            .map(|(i, span)| {
+                let part = if i == index { patched.as_slice() } else { &encoding[span.clone()] };
+                (part, is_variable)
+            })
+            .collect::<Vec<_>>();
+        Ok(join_encodings(&parts))
+    }
+
+    // Consumes the step from a node with a mixed in length or selector to its data.
+    fn take_data(&self, path: &mut GeneralizedIndexPath) -> Result<(), Error> {
+        match path.take(1) {
+            Some(0) => Ok(()),
+            _ => Err(self.invalid_index()),
+        }
+    }
+
+    // Consumes the rest of the path, which must reach a leaf of a tree with `count` chunks.
+    fn take_leaf(&self, path: &mut GeneralizedIndexPath, count: usize) -> Result<usize, Error> {
+        let depth = tree_depth(count);
+        if path.depth() != depth {
+            return Err(self.invalid_index())
+        }
+        path.take(depth).ok_or_else(|| self.invalid_index())
+    }
+
+    // Replaces the chunk at `index` of the packed `data`, which may only set bytes of `data`.
+    fn replace_chunk(&self, data: &[u8], index: usize) -> Result<Vec<u8>, Error> {
+        let chunk = match self.replacement {
+            Replacement::Leaf(chunk) => chunk,
+            Replacement::Subtree(..) => return Err(self.invalid_replacement()),
+        };
+        let start = index * BYTES_PER_CHUNK;
+        if start >= data.len() {
+            return Err(self.invalid_index())
+        }
+        let end = data.len().min(start + BYTES_PER_CHUNK);
+        let (bytes, padding) = chunk.as_ref().split_at(end - start);
+        if padding.iter().any(|&b| b != 0) {
+            return Err(self.invalid_replacement())
+        }
+        let mut data = data.to_vec();
+        data[start..end].copy_from_slice(bytes);
+        Ok(data)
+    }
+}
+
+fn chunk_count(bits: usize) -> usize {
+    bits.div_ceil(BITS_PER_CHUNK)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::prelude::*;
+
+    #[derive(Debug, Default, Clone, PartialEq, Eq, SimpleSerialize)]
+    struct Inner {
+        a: u8,
+        b: List<u16, 40>,
+    }
+
+    #[derive(Debug, Default, Clone, PartialEq, Eq, SimpleSerialize)]
+    struct Outer {
+        x: u32,
+        inner: Inner,
+        inners: List<Inner, 4>,
+        bits: Bitlist<300>,
+        choice: Option<u8>,
+    }
+
+    fn leaf(bytes: &[u8]) -> Replacement {
+        let mut chunk = Node::default();
+        chunk.as_mut()[..bytes.len()].copy_from_slice(bytes);
+        Replacement::Leaf(chunk)
+    }
+
+    fn outer() -> Outer {
+        Outer {
+            x: 1,
+            inner: Inner { a: 2, b: List::try_from((0..20).collect::<Vec<_>>()).unwrap() },
+            inners: List::try_from(vec![Inner::default(), Inner::default()]).unwrap(),
+            bits: Bitlist::try_from([true; 260].as_ref()).unwrap(),
+            choice: Some(3),
+        }
+    }
+
+    #[test]
+    fn test_apply_patches() {
+        let mut value = outer();
+        let mut expected = value.clone();
+
+        // `Outer` has 5 fields, so a depth of 3; `inner` is at 8 + 1 and its `b` at 2 * 9 + 1
+        // where the second chunk of its data is at ((19 * 2) * 4) + 1
+        let mut chunk = vec![0u8; 8];
+        chunk[..2].copy_from_slice(&33u16.to_le_bytes());
+        let patches = [
+            (8, leaf(&5u32.to_le_bytes())),
+            (153, leaf(&chunk)),
+            // `inners[1]` is at (8 + 2) * 2 * 4 + 1 and its `a` at 2 * 81
+            (162, leaf(&[9])),
+            // the second chunk of `bits` is at (8 + 3) * 2 * 2 + 1
+            (45, leaf(&[0b1010])),
+            // the payload of `choice` is at (8 + 4) * 2
+            (24, Replacement::Subtree(vec![4])),
+        ];
+        let root = apply_patches(&mut value, patches).unwrap();
+
+        expected.x = 5;
+        expected.inner.b[16] = 33;
+        for i in 17..20 {
+            expected.inner.b[i] = 0;
+        }
+        expected.inners[1].a = 9;
+        for i in 256..260 {
+            expected.bits.set(i, i == 257 || i == 259);
+        }
+        expected.choice = Some(4);
+        assert_eq!(value, expected);
+        assert_eq!(root, expected.hash_tree_root().unwrap());
+    }
+
+    #[test]
+    fn test_apply_subtree_patch() {
+        let mut value = outer();
+        let mut expected = value.clone();
+        let inner = Inner { a: 1, b: List::try_from(vec![1, 2, 3]).unwrap() };
+        let patches = [(9, Replacement::Subtree(serialize(&inner).unwrap()))];
+        let root = apply_patches(&mut value, patches).unwrap();
+        expected.inner = inner;
+        assert_eq!(value, expected);
+        assert_eq!(root, expected.hash_tree_root().unwrap());
+    }
+
+    #[test]
+    fn test_apply_invalid_patches() {
+        let value = outer();
+        let invalid = [
+            // gindex 0 is not a node
+            (0, leaf(&[1])),
+            // internal node of the container
+            (2, leaf(&[1])),
+            // padding field of the container
+            (15, leaf(&[1])),
+            // length of `inner.b`
+            (39, leaf(&[1])),
+            // chunk past the data of `inner.b`
+            (154, leaf(&[1])),
+            // composite values cannot be replaced with a leaf
+            (9, leaf(&[1])),
+            // only basic values fit in a leaf
+            (8, leaf(&[1; 5])),
+            // selector of `choice`
+            (25, leaf(&[1])),
+            // bits past the length of `bits`
+            (45, leaf(&[0b1_0000])),
+            // fixed size values must have the right size
+            (8, Replacement::Subtree(vec![1, 2])),
+        ];
+        for (generalized_index, replacement) in invalid {
+            let mut patched = value.clone();
+            let result = apply_patches(&mut patched, [(generalized_index, replacement)]);
+            assert!(result.is_err(), "{generalized_index}");
+            assert_eq!(patched, value);
+        }
+    }
+}
@@ -1,40 +1,224 @@
 use crate::{
-    de::{Deserialize, DeserializeError},
+    de::{impl_context_free, Deserialize, DeserializeError},
     lib::*,
     merkleization::{MerkleizationError, Merkleized, Node},
+    schema::{HasSchema, Schema},
     ser::{Serialize, SerializeError},
     Serializable, SimpleSerialize,
 };
 use ruint::{Bits, Uint};
 
-// impl U256 {
-//     pub fn new() -> Self {
-//         Self(BigUint::default())
-//     }
+/// An unsigned integer represented by 256 bits, backed by a `ruint::Uint<256, 4>`.
+///
+/// `U256` supports the usual arithmetic operators and dereferences to the underlying `Uint` for
+/// everything else. With the `serde` feature, values are (de)serialized as decimal strings; see
+/// `ssz_rs::serde::as_hex_uint` to use `0x`-prefixed hex strings instead.
+#[derive(Default, Debug, PartialEq, Eq, Clone, Copy, PartialOrd, Ord, Hash)]
+pub struct U256(Uint<256, 4>);
 
-//     pub fn zero() -> Self {
-//         Self::default()
-//     }
+impl U256 {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn zero() -> Self {
+        Self::default()
+    }
+
+    pub fn try_from_bytes_le(bytes: &[u8]) -> Result<Self, DeserializeError> {
+        Self::deserialize(bytes)
+    }
+
+    pub fn from_bytes_le(bytes: [u8; 32]) -> Self {
+        Self(Uint::from_le_bytes(bytes))
+    }
+
+    pub fn to_bytes_le(&self) -> Vec<u8> {
+        self.0.to_le_bytes_vec()
+    }
+
+    pub fn from_hex(data: &str) -> Option<Self> {
+        let data = data.strip_prefix("0x").unwrap_or(data);
+        Uint::from_str_radix(data, 16).ok().map(Self)
+    }
+}
+
+impl Deref for U256 {
+    type Target = Uint<256, 4>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl DerefMut for U256 {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl From<Uint<256, 4>> for U256 {
+    fn from(value: Uint<256, 4>) -> Self {
+        Self(value)
+    }
+}
+
+impl From<U256> for Uint<256, 4> {
+    fn from(value: U256) -> Self {
+        value.0
+    }
+}
+
+macro_rules! impl_from_primitive {
+    ($($uint:ty),*) => {
+        $(
+            impl From<$uint> for U256 {
+                fn from(x: $uint) -> Self {
+                    Self(Uint::from(x))
+                }
+            }
+        )*
+    };
+}
+
+impl_from_primitive!(u8, u16, u32, u64, u128, usize);
+
+macro_rules! impl_op {
+    ($op:ident, $method:ident, $op_assign:ident, $method_assign:ident) => {
+        impl ops::$op for U256 {
+            type Output = Self;
+
+            fn $method(self, rhs: Self) -> Self {
+                Self(ops::$op::$method(self.0, rhs.0))
+            }
+        }
+
+        impl ops::$op_assign for U256 {
+            fn $method_assign(&mut self, rhs: Self) {
+                ops::$op_assign::$method_assign(&mut self.0, rhs.0)
+            }
+        }
+    };
+}
+
+impl_op!(Add, add, AddAssign, add_assign);
+impl_op!(Sub, sub, SubAssign, sub_assign);
+impl_op!(Mul, mul, MulAssign, mul_assign);
+impl_op!(Div, div, DivAssign, div_assign);
+impl_op!(Rem, rem, RemAssign, rem_assign);
+impl_op!(BitAnd, bitand, BitAndAssign, bitand_assign);
+impl_op!(BitOr, bitor, BitOrAssign, bitor_assign);
+impl_op!(BitXor, bitxor, BitXorAssign, bitxor_assign);
+
+impl ops::Shl<usize> for U256 {
+    type Output = Self;
+
+    fn shl(self, rhs: usize) -> Self {
+        Self(self.0 << rhs)
+    }
+}
+
+impl ops::Shr<usize> for U256 {
+    type Output = Self;
+
+    fn shr(self, rhs: usize) -> Self {
+        Self(self.0 >> rhs)
+    }
+}
+
+impl ops::Not for U256 {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        Self(!self.0)
+    }
+}
+
+impl Display for U256 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+impl fmt::LowerHex for U256 {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        fmt::LowerHex::fmt(&self.0, f)
+    }
+}
+
+impl FromStr for U256 {
+    type Err = ruint::ParseError;
+
+    // NOTE: `0x`-prefixed strings are parsed as hex, otherwise as decimal
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse().map(Self)
+    }
+}
+
+impl Serializable for U256 {
+    fn is_variable_size() -> bool {
+        false
+    }
+
+    fn size_hint() -> usize {
+        32
+    }
+}
 
-//     pub fn try_from_bytes_le(bytes: &[u8]) -> Result<Self, DeserializeError> {
-//         Self::deserialize(bytes)
-//     }
+impl Serialize for U256 {
+    fn serialize(&self, buffer: &mut Vec<u8>) -> Result<usize, SerializeError> {
+        self.0.serialize(buffer)
+    }
+}
 
-//     pub fn from_bytes_le(bytes: [u8; 32]) -> Self {
-//         Self::deserialize(&bytes).unwrap()
-//     }
+impl Deserialize for U256 {
+    fn deserialize(encoding: &[u8]) -> Result<Self, DeserializeError> {
+        Uint::deserialize(encoding).map(Self)
+    }
+}
 
-//     pub fn to_bytes_le(&self) -> Vec<u8> {
-//         let mut bytes = self.0.to_bytes_le();
-//         bytes.resize(Self::size_hint(), 0u8);
-//         bytes
-//     }
+impl_context_free!([] U256);
 
-//     pub fn from_hex(data: &str) -> Option<Self> {
-//         let data = data.strip_prefix("0x").unwrap_or(data);
-//         BigUint::parse_bytes(data.as_bytes(), 16).map(Self)
-//     }
-// }
+impl Merkleized for U256 {
+    fn hash_tree_root(&mut self) -> Result<Node, MerkleizationError> {
+        self.0.hash_tree_root()
+    }
+
+    fn is_composite_type() -> bool {
+        false
+    }
+}
+
+impl HasSchema for U256 {
+    fn schema() -> Schema {
+        Schema::Uint(Self::size_hint())
+    }
+}
+
+impl SimpleSerialize for U256 {}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for U256 {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(&self.0)
+    }
+}
+
+// NOTE: accepts `0x`-prefixed hex strings as well, like those written with
+// `ssz_rs::serde::as_hex_uint`
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for U256 {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let s = <String>::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
 
 impl<const BITS: usize, const LIMBS: usize> Serializable for Uint<BITS, LIMBS> {
     fn is_variable_size() -> bool {
@@ -74,10 +258,11 @@ impl<const BITS: usize, const LIMBS: usize> Deserialize for Uint<BITS, LIMBS> {
     }
 }
 
+impl_context_free!([const BITS: usize, const LIMBS: usize] Uint<BITS, LIMBS>);
+
 impl<const BITS: usize, const LIMBS: usize> Merkleized for Uint<BITS, LIMBS> {
     fn hash_tree_root(&mut self) -> Result<Node, MerkleizationError> {
-        let data: Vec<u8> = self.to_le_bytes_vec();
-        let node = Node::try_from(data.as_ref()).expect("is right size");
+        let node = Node::try_from(self.as_le_bytes().as_ref()).expect("is right size");
         Ok(node)
     }
 
@@ -86,6 +271,12 @@ impl<const BITS: usize, const LIMBS: usize> Merkleized for Uint<BITS, LIMBS> {
     }
 }
 
+impl<const BITS: usize, const LIMBS: usize> HasSchema for Uint<BITS, LIMBS> {
+    fn schema() -> Schema {
+        Schema::Uint(Self::size_hint())
+    }
+}
+
 impl<const BITS: usize, const LIMBS: usize> SimpleSerialize for Uint<BITS, LIMBS> {}
 
 impl<const BITS: usize, const LIMBS: usize> Serializable for Bits<BITS, LIMBS> {
@@ -126,6 +317,8 @@ impl<const BITS: usize, const LIMBS: usize> Deserialize for Bits<BITS, LIMBS> {
     }
 }
 
+impl_context_free!([const BITS: usize, const LIMBS: usize] Bits<BITS, LIMBS>);
+
 impl<const BITS: usize, const LIMBS: usize> Merkleized for Bits<BITS, LIMBS> {
     fn hash_tree_root(&mut self) -> Result<Node, MerkleizationError> {
         let data: Vec<u8> = self.to_be_bytes_vec(); // TODO: shall it be le?
@@ -138,30 +331,14 @@ impl<const BITS: usize, const LIMBS: usize> Merkleized for Bits<BITS, LIMBS> {
     }
 }
 
-impl<const BITS: usize, const LIMBS: usize> SimpleSerialize for Bits<BITS, LIMBS> {}
+// NOTE: `Bits` are encoded as opaque bytes
+impl<const BITS: usize, const LIMBS: usize> HasSchema for Bits<BITS, LIMBS> {
+    fn schema() -> Schema {
+        Schema::Vector { element: Box::new(Schema::Uint(1)), length: Self::size_hint() }
+    }
+}
 
-// #[cfg(feature = "serde")]
-// impl serde::Serialize for U256 {
-//     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-//     where
-//         S: serde::Serializer,
-//     {
-//         let output = format!("{}", self.0);
-//         serializer.collect_str(&output)
-//     }
-// }
-
-// #[cfg(feature = "serde")]
-// impl<'de> serde::Deserialize<'de> for U256 {
-//     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-//     where
-//         D: serde::Deserializer<'de>,
-//     {
-//         let s = <String>::deserialize(deserializer)?;
-//         let value = s.parse::<BigUint>().map_err(serde::de::Error::custom)?;
-//         Ok(Self(value))
-//     }
-// }
+impl<const BITS: usize, const LIMBS: usize> SimpleSerialize for Bits<BITS, LIMBS> {}
 
 #[cfg(test)]
 mod tests {
@@ -281,4 +458,20 @@ mod tests {
             assert_eq!(result, expected);
         }
     }
+
+    #[test]
+    fn u256_wraps_ruint() {
+        let value = crate::U256::from(u128::MAX) + crate::U256::from(1u64);
+        assert_eq!(*value, U256::from(u128::MAX) + U256::from(1u64));
+        assert_eq!(value.to_string(), "340282366920938463463374607431768211456");
+        assert_eq!(format!("{value:#x}"), format!("0x1{}", "0".repeat(32)));
+        assert_eq!(crate::U256::from_hex(&format!("0x1{}", "0".repeat(32))), Some(value));
+        assert_eq!("0xff".parse::<crate::U256>().unwrap(), crate::U256::from(255u8));
+
+        let bytes = serialize(&value).unwrap();
+        assert_eq!(bytes, serialize(&*value).unwrap());
+        assert_eq!(crate::U256::try_from_bytes_le(&bytes).unwrap(), value);
+        assert_eq!(value.to_bytes_le(), bytes);
+        assert!(crate::U256::try_from_bytes_le(&bytes[1..]).is_err());
+    }
 }
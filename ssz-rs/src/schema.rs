@@ -0,0 +1,821 @@
+use crate::{
+    de::DeserializeError,
+    error::{Error, InstanceError, TypeError},
+    lib::*,
+    merkleization::{
+        mix_in_length, mix_in_selector, tree_depth, MerkleizationError, Merkleizer, Node,
+        BITS_PER_CHUNK, BYTES_PER_CHUNK,
+    },
+    ser::BYTES_PER_LENGTH_OFFSET,
+};
+
+const BITS_PER_BYTE: usize = crate::BITS_PER_BYTE as usize;
+
+/// A named field of an SSZ container.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Field {
+    pub name: &'static str,
+    pub schema: Schema,
+}
+
+/// A variant of an SSZ union.
+/// The `schema` is `None` for the unit variant with an empty payload.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Variant {
+    pub name: &'static str,
+    pub selector: u8,
+    pub schema: Option<Schema>,
+}
+
+/// `Schema` describes the SSZ type of a value at runtime.
+///
+/// It is the dynamic counterpart to the static information provided by `Serializable`
+/// and is useful when inspecting encodings without decoding them into the typed value first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Schema {
+    /// An unsigned integer with the given width in bytes.
+    Uint(usize),
+    Boolean,
+    Vector {
+        element: Box<Schema>,
+        length: usize,
+    },
+    List {
+        element: Box<Schema>,
+        limit: usize,
+    },
+    Bitvector(usize),
+    Bitlist(usize),
+    Container {
+        name: &'static str,
+        fields: Vec<Field>,
+    },
+    Union {
+        name: &'static str,
+        variants: Vec<Variant>,
+    },
+}
+
+impl Schema {
+    /// Is the encoding of this type variable or fixed size?
+    pub fn is_variable_size(&self) -> bool {
+        match self {
+            Self::Uint(..) | Self::Boolean | Self::Bitvector(..) => false,
+            Self::List { .. } | Self::Bitlist(..) | Self::Union { .. } => true,
+            Self::Vector { element, .. } => element.is_variable_size(),
+            Self::Container { fields, .. } => fields.iter().any(|f| f.schema.is_variable_size()),
+        }
+    }
+
+    /// Number of bytes in the encoding of this type or 0 if the type is variable size.
+    pub fn size_hint(&self) -> usize {
+        if self.is_variable_size() {
+            return 0
+        }
+        match self {
+            Self::Uint(size) => *size,
+            Self::Boolean => 1,
+            Self::Bitvector(bound) => bound.div_ceil(8),
+            Self::Vector { element, length } => element.size_hint() * length,
+            Self::Container { fields, .. } => fields.iter().map(|f| f.schema.size_hint()).sum(),
+            _ => unreachable!("variable size types returned early"),
+        }
+    }
+
+    /// Is this type a "basic" SSZ type, rather than a composite one?
+    pub fn is_basic(&self) -> bool {
+        matches!(self, Self::Uint(..) | Self::Boolean)
+    }
+
+    // Number of bytes used in the fixed part of a container for this type.
+    pub(crate) fn fixed_part_length(&self) -> usize {
+        if self.is_variable_size() {
+            BYTES_PER_LENGTH_OFFSET
+        } else {
+            self.size_hint()
+        }
+    }
+}
+
+/// An element of a path from the root of an SSZ value to some value inside of it.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PathElement {
+    /// The field of a container with the given name, or the variant of a union.
+    Field(String),
+    /// The element of a sequence type at the given index.
+    Index(usize),
+    /// The length of a `List` or `Bitlist`.
+    Length,
+}
+
+impl From<&str> for PathElement {
+    fn from(name: &str) -> Self {
+        Self::Field(name.into())
+    }
+}
+
+impl From<usize> for PathElement {
+    fn from(index: usize) -> Self {
+        Self::Index(index)
+    }
+}
+
+impl Display for PathElement {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Field(name) => write!(f, ".{name}"),
+            Self::Index(index) => write!(f, "[{index}]"),
+            Self::Length => write!(f, ".len"),
+        }
+    }
+}
+
+fn read_offset(encoding: &[u8], position: usize) -> Result<usize, DeserializeError> {
+    let bytes = encoding.get(position..position + BYTES_PER_LENGTH_OFFSET).ok_or(
+        DeserializeError::ExpectedFurtherInput {
+            provided: encoding.len().saturating_sub(position),
+            expected: BYTES_PER_LENGTH_OFFSET,
+        },
+    )?;
+    // SAFETY: `bytes` has exactly `BYTES_PER_LENGTH_OFFSET` bytes; qed
+    Ok(u32::from_le_bytes(bytes.try_into().expect("is right size")) as usize)
+}
+
+fn expect_length(encoding: &[u8], expected: usize) -> Result<(), DeserializeError> {
+    match encoding.len().cmp(&expected) {
+        Ordering::Less => {
+            Err(DeserializeError::ExpectedFurtherInput { provided: encoding.len(), expected })
+        }
+        Ordering::Greater => {
+            Err(DeserializeError::AdditionalInput { provided: encoding.len(), expected })
+        }
+        Ordering::Equal => Ok(()),
+    }
+}
+
+// Splits the `encoding` of a container with `fields` into the span of each field's encoding.
+pub(crate) fn field_spans(
+    fields: &[Field],
+    encoding: &[u8],
+) -> Result<Vec<Range<usize>>, DeserializeError> {
+    let mut spans = Vec::with_capacity(fields.len());
+    for_each_field_span(fields, encoding, |_, span| {
+        spans.push(span);
+        Ok::<_, DeserializeError>(())
+    })?;
+    Ok(spans)
+}
+
+// Calls `f` with each of the `fields` of a container and the span of its encoding, in order,
+// validating the offsets in the `encoding` along the way.
+pub(crate) fn for_each_field_span<E: From<DeserializeError>>(
+    fields: &[Field],
+    encoding: &[u8],
+    mut f: impl FnMut(&Field, Range<usize>) -> Result<(), E>,
+) -> Result<(), E> {
+    let fixed_len = fields.iter().map(|f| f.schema.fixed_part_length()).sum::<usize>();
+    if encoding.len() < fixed_len {
+        return Err(DeserializeError::ExpectedFurtherInput {
+            provided: encoding.len(),
+            expected: fixed_len,
+        }
+        .into())
+    }
+
+    // the offset of the last variable size field
+    let mut previous = None;
+    let mut cursor = 0;
+    for (i, field) in fields.iter().enumerate() {
+        let size = field.schema.fixed_part_length();
+        let span = if field.schema.is_variable_size() {
+            let offset = read_offset(encoding, cursor)?;
+            let start = previous.unwrap_or(fixed_len);
+            if offset < start {
+                return Err(DeserializeError::OffsetNotIncreasing { start, end: offset }.into())
+            }
+            if previous.is_none() && offset > fixed_len {
+                return Err(DeserializeError::AdditionalInput {
+                    provided: offset,
+                    expected: fixed_len,
+                }
+                .into())
+            }
+            // the span ends where the next variable size field starts
+            let mut next = None;
+            let mut next_cursor = cursor + size;
+            for field in &fields[i + 1..] {
+                if field.schema.is_variable_size() {
+                    next = Some(read_offset(encoding, next_cursor)?);
+                    break
+                }
+                next_cursor += field.schema.fixed_part_length();
+            }
+            let end = next.unwrap_or(encoding.len());
+            if end < offset {
+                return Err(DeserializeError::OffsetNotIncreasing { start: offset, end }.into())
+            }
+            if end > encoding.len() {
+                return Err(DeserializeError::ExpectedFurtherInput {
+                    provided: encoding.len(),
+                    expected: end,
+                }
+                .into())
+            }
+            previous = Some(offset);
+            offset..end
+        } else {
+            cursor..cursor + size
+        };
+        f(field, span)?;
+        cursor += size;
+    }
+
+    if previous.is_none() && encoding.len() > fixed_len {
+        return Err(DeserializeError::AdditionalInput {
+            provided: encoding.len(),
+            expected: fixed_len,
+        }
+        .into())
+    }
+    Ok(())
+}
+
+// Splits the `encoding` of a homogeneous sequence of `element`s into the span of each element's
+// encoding.
+pub(crate) fn element_spans(
+    element: &Schema,
+    encoding: &[u8],
+) -> Result<Vec<Range<usize>>, DeserializeError> {
+    let mut spans = Vec::with_capacity(element_count(element, encoding)?);
+    for_each_element_span(element, encoding, |span| {
+        spans.push(span);
+        Ok::<_, DeserializeError>(())
+    })?;
+    Ok(spans)
+}
+
+// Returns the number of elements in the `encoding` of a homogeneous sequence of `element`s.
+fn element_count(element: &Schema, encoding: &[u8]) -> Result<usize, DeserializeError> {
+    if !element.is_variable_size() {
+        let size = element.size_hint();
+        let remainder = encoding.len() % size;
+        if remainder != 0 {
+            return Err(DeserializeError::AdditionalInput {
+                provided: encoding.len(),
+                // SAFETY: checked subtraction is unnecessary, as encoding.len() > remainder; qed
+                expected: encoding.len() - remainder,
+            })
+        }
+        return Ok(encoding.len() / size)
+    }
+
+    if encoding.is_empty() {
+        return Ok(0)
+    }
+    let first_offset = read_offset(encoding, 0)?;
+    if first_offset == 0 || !first_offset.is_multiple_of(BYTES_PER_LENGTH_OFFSET) {
+        return Err(DeserializeError::InvalidOffsetsLength(first_offset))
+    }
+    if first_offset > encoding.len() {
+        return Err(DeserializeError::ExpectedFurtherInput {
+            provided: encoding.len(),
+            expected: first_offset,
+        })
+    }
+    Ok(first_offset / BYTES_PER_LENGTH_OFFSET)
+}
+
+// Calls `f` with the span of each element's encoding in the `encoding` of a homogeneous
+// sequence of `element`s, in order, validating the offsets in the `encoding` along the way.
+fn for_each_element_span<E: From<DeserializeError>>(
+    element: &Schema,
+    encoding: &[u8],
+    mut f: impl FnMut(Range<usize>) -> Result<(), E>,
+) -> Result<(), E> {
+    let count = element_count(element, encoding)?;
+    if !element.is_variable_size() {
+        let size = element.size_hint();
+        return (0..count).try_for_each(|i| f(i * size..(i + 1) * size))
+    }
+
+    for i in 0..count {
+        let start = read_offset(encoding, i * BYTES_PER_LENGTH_OFFSET)?;
+        let end = if i + 1 < count {
+            read_offset(encoding, (i + 1) * BYTES_PER_LENGTH_OFFSET)?
+        } else {
+            encoding.len()
+        };
+        if end < start {
+            return Err(DeserializeError::OffsetNotIncreasing { start, end }.into())
+        }
+        if end > encoding.len() {
+            return Err(DeserializeError::ExpectedFurtherInput {
+                provided: encoding.len(),
+                expected: end,
+            }
+            .into())
+        }
+        f(start..end)?;
+    }
+    Ok(())
+}
+
+// Splits the `encoding` of a `Bitlist` into the bytes holding its bits, without the delimiting
+// bit, and its length in bits.
+pub(crate) fn split_bitlist(encoding: &[u8]) -> Result<(Vec<u8>, usize), DeserializeError> {
+    let (last, prefix) = match encoding.split_last() {
+        Some((last, prefix)) if *last != 0 => (*last, prefix),
+        Some((last, _)) => return Err(DeserializeError::InvalidByte(*last)),
+        None => return Err(DeserializeError::ExpectedFurtherInput { provided: 0, expected: 1 }),
+    };
+    // SAFETY: checked subtraction is unnecessary, as last != 0; qed
+    let delimiter = BITS_PER_BYTE - 1 - last.leading_zeros() as usize;
+    let mut bytes = prefix.to_vec();
+    if delimiter != 0 {
+        bytes.push(last ^ (1 << delimiter));
+    }
+    Ok((bytes, prefix.len() * BITS_PER_BYTE + delimiter))
+}
+
+// Joins the encodings of the parts of a container or a sequence, given with whether each part
+// is of variable size, adding offsets as needed.
+pub(crate) fn join_encodings(parts: &[(&[u8], bool)]) -> Vec<u8> {
+    let fixed_len = parts
+        .iter()
+        .map(|(part, is_variable)| if *is_variable { BYTES_PER_LENGTH_OFFSET } else { part.len() })
+        .sum::<usize>();
+    let variable_len = parts
+        .iter()
+        .filter(|(_, is_variable)| *is_variable)
+        .map(|(part, _)| part.len())
+        .sum::<usize>();
+    let mut encoding = Vec::with_capacity(fixed_len + variable_len);
+    let mut offset = fixed_len;
+    for (part, is_variable) in parts {
+        if *is_variable {
+            encoding.extend_from_slice(&(offset as u32).to_le_bytes());
+            offset += part.len();
+        } else {
+            encoding.extend_from_slice(part);
+        }
+    }
+    for (part, is_variable) in parts {
+        if *is_variable {
+            encoding.extend_from_slice(part);
+        }
+    }
+    encoding
+}
+
+impl Schema {
+    // Computes the hash tree root of a value of this type directly from its `encoding`,
+    // validating it as `Deserialize` would.
+    //
+    // The encoding is streamed into a `Merkleizer` in a single pass: basic values are packed
+    // straight from the `encoding` and the roots of composite values are written as they are
+    // computed.
+    pub(crate) fn hash_tree_root_of(&self, encoding: &[u8]) -> Result<Node, Error> {
+        let root = match self {
+            Self::Uint(..) | Self::Boolean => {
+                expect_length(encoding, self.size_hint())?;
+                // SAFETY: index is safe because `expect_length` ensures a single byte; qed
+                if matches!(self, Self::Boolean) && encoding[0] > 1 {
+                    return Err(DeserializeError::InvalidByte(encoding[0]).into())
+                }
+                merkleize_packed(&[encoding], Some(1))?
+            }
+            Self::Bitvector(bound) => {
+                expect_length(encoding, self.size_hint())?;
+                let remainder = bound % BITS_PER_BYTE;
+                match encoding.last() {
+                    Some(last) if remainder != 0 && last >> remainder != 0 => {
+                        return Err(DeserializeError::InvalidByte(*last).into())
+                    }
+                    None => return Err(Error::Type(TypeError::InvalidBound(*bound))),
+                    _ => {}
+                }
+                merkleize_packed(&[encoding], Some(bound.div_ceil(BITS_PER_CHUNK)))?
+            }
+            Self::Bitlist(bound) => {
+                let (last, prefix) = match encoding.split_last() {
+                    Some((last, prefix)) if *last != 0 => (*last, prefix),
+                    Some((last, _)) => return Err(DeserializeError::InvalidByte(*last).into()),
+                    None => {
+                        return Err(DeserializeError::ExpectedFurtherInput {
+                            provided: 0,
+                            expected: 1,
+                        }
+                        .into())
+                    }
+                };
+                // SAFETY: checked subtraction is unnecessary, as last != 0; qed
+                let delimiter = BITS_PER_BYTE - 1 - last.leading_zeros() as usize;
+                let len = prefix.len() * BITS_PER_BYTE + delimiter;
+                if len > *bound {
+                    return Err(DeserializeError::InvalidInstance(InstanceError::Bounded {
+                        bound: *bound,
+                        provided: len,
+                    })
+                    .into())
+                }
+                let last = [last ^ (1 << delimiter)];
+                let parts: &[&[u8]] = if delimiter != 0 { &[prefix, &last] } else { &[prefix] };
+                let limit = bound.div_ceil(BITS_PER_CHUNK);
+                mix_in_length(&merkleize_packed(parts, Some(limit))?, len)
+            }
+            Self::Vector { element, .. } | Self::List { element, .. } => {
+                let len = element_count(element, encoding)?;
+                self.check_element_count(len)?;
+                let limit = match self {
+                    Self::List { limit, .. } if element.is_basic() => {
+                        Some((limit * element.size_hint()).div_ceil(BYTES_PER_CHUNK))
+                    }
+                    Self::List { limit, .. } => Some(*limit),
+                    _ => None,
+                };
+                let root = if element.is_basic() {
+                    if matches!(**element, Self::Boolean) {
+                        if let Some(byte) = encoding.iter().find(|&&b| b > 1) {
+                            return Err(DeserializeError::InvalidByte(*byte).into())
+                        }
+                    }
+                    merkleize_packed(&[encoding], limit)?
+                } else {
+                    let mut merkleizer = Merkleizer::new(limit);
+                    for_each_element_span(element, encoding, |span| {
+                        merkleizer.write_chunk(element.hash_tree_root_of(&encoding[span])?)?;
+                        Ok::<_, Error>(())
+                    })?;
+                    merkleizer.finish()?
+                };
+                match self {
+                    Self::List { .. } => mix_in_length(&root, len),
+                    _ => root,
+                }
+            }
+            Self::Container { fields, .. } => {
+                let mut merkleizer = Merkleizer::new(None);
+                for_each_field_span(fields, encoding, |field, span| {
+                    merkleizer.write_chunk(field.schema.hash_tree_root_of(&encoding[span])?)?;
+                    Ok::<_, Error>(())
+                })?;
+                merkleizer.finish()?
+            }
+            Self::Union { variants, .. } => {
+                let (selector, payload) = encoding
+                    .split_first()
+                    .ok_or(DeserializeError::ExpectedFurtherInput { provided: 0, expected: 1 })?;
+                let variant = variants
+                    .iter()
+                    .find(|v| v.selector == *selector)
+                    .ok_or(DeserializeError::InvalidByte(*selector))?;
+                let root = match &variant.schema {
+                    Some(schema) => schema.hash_tree_root_of(payload)?,
+                    None => {
+                        expect_length(payload, 0)?;
+                        Node::default()
+                    }
+                };
+                mix_in_selector(&root, *selector as usize)
+            }
+        };
+        Ok(root)
+    }
+}
+
+// Merkleizes the concatenation of `parts`, packed into chunks, with the given chunk `limit`.
+fn merkleize_packed(parts: &[&[u8]], limit: Option<usize>) -> Result<Node, MerkleizationError> {
+    let len = parts.iter().map(|part| part.len()).sum::<usize>();
+    // NOTE: a tree of a single chunk is its own root
+    if len <= BYTES_PER_CHUNK && limit.unwrap_or(1) == 1 {
+        let mut root = Node::default();
+        let mut cursor = 0;
+        for part in parts {
+            root.as_mut()[cursor..cursor + part.len()].copy_from_slice(part);
+            cursor += part.len();
+        }
+        return Ok(root)
+    }
+    let mut merkleizer = Merkleizer::new(limit);
+    for part in parts {
+        merkleizer.write_bytes(part)?;
+    }
+    merkleizer.finish()
+}
+
+impl Schema {
+    // Checks the number of elements in an encoding of a `Vector` or `List`.
+    pub(crate) fn check_element_count(&self, count: usize) -> Result<(), DeserializeError> {
+        match self {
+            Self::Vector { length, .. } if count != *length => {
+                Err(DeserializeError::InvalidInstance(InstanceError::Exact {
+                    required: *length,
+                    provided: count,
+                }))
+            }
+            Self::List { limit, .. } if count > *limit => {
+                Err(DeserializeError::InvalidInstance(InstanceError::Bounded {
+                    bound: *limit,
+                    provided: count,
+                }))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+// The position of a value in the merkle tree of some enclosing value.
+pub(crate) struct Location {
+    // generalized index of the node holding the value
+    pub(crate) generalized_index: usize,
+    pub(crate) schema: Schema,
+    // offset of a basic value packed into the chunk at `generalized_index`
+    pub(crate) offset: usize,
+}
+
+// Returns the generalized index of the child at `index` in the subtree of the given `depth`
+// under the node at `generalized_index`.
+pub(crate) fn child_index(
+    generalized_index: usize,
+    depth: u32,
+    index: usize,
+) -> Result<usize, MerkleizationError> {
+    generalized_index
+        .checked_mul(1 << depth)
+        .and_then(|base| base.checked_add(index))
+        .ok_or(MerkleizationError::InvalidGeneralizedIndex(generalized_index))
+}
+
+impl Schema {
+    /// Returns the generalized index of the node holding the value at `path` inside of a value
+    /// of this type.
+    ///
+    /// As in the SSZ spec, the node of a basic value inside of a `List` or `Vector` is the
+    /// chunk it is packed into and the node of the `PathElement::Length` of a `List` or
+    /// `Bitlist` is where its length is mixed in. The payload of a union is addressed by the
+    /// name of its variant.
+    pub fn generalized_index(&self, path: &[PathElement]) -> Result<usize, MerkleizationError> {
+        Ok(self.locate(path)?.generalized_index)
+    }
+
+    pub(crate) fn locate(&self, path: &[PathElement]) -> Result<Location, MerkleizationError> {
+        let invalid = || MerkleizationError::InvalidPath(path.to_vec());
+        let mut location = Location { generalized_index: 1, schema: self.clone(), offset: 0 };
+        for (i, element) in path.iter().enumerate() {
+            let Location { generalized_index, schema, .. } = location;
+            // only the last element of a path may address a basic value
+            if schema.is_basic() {
+                return Err(invalid())
+            }
+            location = match (schema, element) {
+                (Self::Container { mut fields, .. }, PathElement::Field(name)) => {
+                    let index =
+                        fields.iter().position(|f| f.name == name.as_str()).ok_or_else(invalid)?;
+                    Location {
+                        generalized_index: child_index(
+                            generalized_index,
+                            tree_depth(fields.len()),
+                            index,
+                        )?,
+                        schema: fields.swap_remove(index).schema,
+                        offset: 0,
+                    }
+                }
+                (Self::Vector { element, length }, PathElement::Index(index))
+                    if *index < length =>
+                {
+                    locate_element(*element, length, generalized_index, *index)?
+                }
+                (Self::List { element, limit }, PathElement::Index(index)) if *index < limit => {
+                    locate_element(*element, limit, 2 * generalized_index, *index)?
+                }
+                (Self::List { .. } | Self::Bitlist(..), PathElement::Length)
+                    if i == path.len() - 1 =>
+                {
+                    Location {
+                        generalized_index: 2 * generalized_index + 1,
+                        schema: Self::Uint(8),
+                        offset: 0,
+                    }
+                }
+                (Self::Union { variants, .. }, PathElement::Field(name)) => {
+                    let schema = variants
+                        .into_iter()
+                        .find(|v| v.name == name.as_str())
+                        .and_then(|v| v.schema)
+                        .ok_or_else(invalid)?;
+                    Location { generalized_index: 2 * generalized_index, schema, offset: 0 }
+                }
+                _ => return Err(invalid()),
+            };
+        }
+        Ok(location)
+    }
+}
+
+fn locate_element(
+    element: Schema,
+    bound: usize,
+    generalized_index: usize,
+    index: usize,
+) -> Result<Location, MerkleizationError> {
+    if element.is_basic() {
+        let size = element.size_hint();
+        let per_chunk = BYTES_PER_CHUNK / size;
+        let chunks = (bound * size).div_ceil(BYTES_PER_CHUNK);
+        Ok(Location {
+            generalized_index: child_index(
+                generalized_index,
+                tree_depth(chunks),
+                index / per_chunk,
+            )?,
+            schema: element,
+            offset: (index % per_chunk) * size,
+        })
+    } else {
+        Ok(Location {
+            generalized_index: child_index(generalized_index, tree_depth(bound), index)?,
+            schema: element,
+            offset: 0,
+        })
+    }
+}
+
+impl Display for Schema {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Uint(size) => write!(f, "uint{}", size * 8),
+            Self::Boolean => write!(f, "boolean"),
+            Self::Vector { element, length } => write!(f, "Vector[{element}, {length}]"),
+            Self::List { element, limit } => write!(f, "List[{element}, {limit}]"),
+            Self::Bitvector(bound) => write!(f, "Bitvector[{bound}]"),
+            Self::Bitlist(bound) => write!(f, "Bitlist[{bound}]"),
+            Self::Container { name, .. } | Self::Union { name, .. } => write!(f, "{name}"),
+        }
+    }
+}
+
+/// A type implementing `HasSchema` can describe its SSZ type with a `Schema`.
+///
+/// `HasSchema` is not required by `SimpleSerialize`, so types with a manual `SimpleSerialize`
+/// impl keep compiling; implement it by hand to use them with the schema-driven utilities like
+/// `diff`, `hex_dump`, `Partial` and `apply_patches`. The derive implements it whenever the
+/// types of all fields have a schema.
+pub trait HasSchema {
+    fn schema() -> Schema;
+}
+
+// Calls `f` with the schema of `T`, which is built once for each type if `std` is available.
+pub(crate) fn with_schema_of<T: HasSchema + 'static, R>(f: impl FnOnce(&Schema) -> R) -> R {
+    #[cfg(feature = "std")]
+    {
+        use std::{
+            any::TypeId,
+            collections::HashMap,
+            sync::{OnceLock, PoisonError, RwLock},
+        };
+
+        // NOTE: each schema is leaked, as there is only one for each type used
+        static SCHEMAS: OnceLock<RwLock<HashMap<TypeId, &'static Schema>>> = OnceLock::new();
+
+        let schemas = SCHEMAS.get_or_init(Default::default);
+        let id = TypeId::of::<T>();
+        let cached = schemas.read().unwrap_or_else(PoisonError::into_inner).get(&id).copied();
+        let schema = match cached {
+            Some(schema) => schema,
+            None => *schemas
+                .write()
+                .unwrap_or_else(PoisonError::into_inner)
+                .entry(id)
+                .or_insert_with(|| Box::leak(Box::new(T::schema()))),
+        };
+        f(schema)
+    }
+    #[cfg(not(feature = "std"))]
+    f(&T::schema())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+
+    #[derive(Default, Debug, SimpleSerialize)]
+    struct Foo {
+        a: u32,
+        b: List<u16, 8>,
+        c: Vector<bool, 3>,
+    }
+
+    #[derive(Debug, SimpleSerialize)]
+    enum Bar {
+        None,
+        A(Foo),
+        B(Bitvector<4>),
+    }
+
+    #[test]
+    fn test_derived_schema() {
+        let schema = Foo::schema();
+        assert!(schema.is_variable_size());
+        assert_eq!(
+            schema,
+            Schema::Container {
+                name: "Foo",
+                fields: vec![
+                    Field { name: "a", schema: Schema::Uint(4) },
+                    Field {
+                        name: "b",
+                        schema: Schema::List { element: Box::new(Schema::Uint(2)), limit: 8 },
+                    },
+                    Field {
+                        name: "c",
+                        schema: Schema::Vector { element: Box::new(Schema::Boolean), length: 3 },
+                    },
+                ],
+            }
+        );
+
+        let schema = Bar::schema();
+        match schema {
+            Schema::Union { name, variants } => {
+                assert_eq!(name, "Bar");
+                assert_eq!(variants.len(), 3);
+                assert_eq!(variants[0].schema, None);
+                assert_eq!(variants[2].selector, 2);
+                assert_eq!(variants[2].schema, Some(Schema::Bitvector(4)));
+            }
+            _ => panic!("enum should be a union"),
+        }
+    }
+
+    #[test]
+    fn test_schema_sizes() {
+        assert_eq!(<Vector<u64, 4>>::schema().size_hint(), 32);
+        assert_eq!(<Bitvector<9>>::schema().size_hint(), 2);
+        assert_eq!(<Option<u8>>::schema().size_hint(), 0);
+        assert_eq!(<[u16; 3]>::schema().to_string(), "Vector[uint16, 3]");
+        assert_eq!(<List<Bitlist<4>, 2>>::schema().to_string(), "List[Bitlist[4], 2]");
+    }
+
+    fn root_of<T: SimpleSerialize + HasSchema>(mut value: T) -> (Node, Node) {
+        let encoding = serialize(&value).unwrap();
+        (T::schema().hash_tree_root_of(&encoding).unwrap(), value.hash_tree_root().unwrap())
+    }
+
+    #[test]
+    fn test_hash_tree_root_of_encoding() {
+        let foo = Foo {
+            a: 12,
+            b: List::try_from(vec![1, 2, 3]).unwrap(),
+            c: Vector::try_from(vec![true, false, true]).unwrap(),
+        };
+        let (expected, root) = root_of(foo);
+        assert_eq!(expected, root);
+        let (expected, root) =
+            root_of(Bar::B(Bitvector::try_from([true, true, false, true].as_ref()).unwrap()));
+        assert_eq!(expected, root);
+        let (expected, root) = root_of(Bar::None);
+        assert_eq!(expected, root);
+        let (expected, root) = root_of(Bitlist::<300>::try_from([true; 257].as_ref()).unwrap());
+        assert_eq!(expected, root);
+        let (expected, root) = root_of(Bitlist::<3>::default());
+        assert_eq!(expected, root);
+        let (expected, root) = root_of(List::<Foo, 3>::try_from(vec![Foo::default()]).unwrap());
+        assert_eq!(expected, root);
+        let (expected, root) = root_of([U256::from(3u64), U256::from(4u64)]);
+        assert_eq!(expected, root);
+    }
+
+    #[test]
+    fn test_hash_tree_root_of_invalid_encoding() {
+        // invalid boolean
+        assert!(hash_tree_root_from_bytes::<bool>(&[2]).is_err());
+        assert!(hash_tree_root_from_bytes::<List<bool, 4>>(&[1, 0, 3]).is_err());
+        // excess bits
+        assert!(hash_tree_root_from_bytes::<Bitvector<4>>(&[0b1_0000]).is_err());
+        // over the bound
+        assert!(hash_tree_root_from_bytes::<Bitlist<3>>(&[0b1_0000]).is_err());
+        assert!(hash_tree_root_from_bytes::<List<u16, 2>>(&[0; 6]).is_err());
+        // wrong number of elements
+        assert!(hash_tree_root_from_bytes::<Vector<u16, 2>>(&[0; 6]).is_err());
+        assert!(hash_tree_root_from_bytes::<Vector<Bitlist<3>, 2>>(&[4, 0, 0, 0, 1]).is_err());
+        // bad offsets
+        assert!(hash_tree_root_from_bytes::<Foo>(&[0, 0, 0, 0, 9, 0, 0, 0, 1, 0, 1]).is_err());
+        // unknown selector
+        assert!(hash_tree_root_from_bytes::<Bar>(&[3]).is_err());
+        assert!(hash_tree_root_from_bytes::<Option<u8>>(&[0, 1]).is_err());
+
+        for encoding in [
+            vec![2],
+            vec![0, 1],
+            vec![1, 2, 3],
+            vec![0, 0, 0, 0, 9, 0, 0, 0, 1, 0, 1],
+            vec![0, 0, 0, 0, 11, 0, 0, 0, 1, 0, 1, 7, 0],
+        ] {
+            assert_eq!(
+                hash_tree_root_from_bytes::<Foo>(&encoding).is_ok(),
+                Foo::deserialize(&encoding).is_ok()
+            );
+        }
+    }
+}
@@ -102,9 +102,14 @@ impl Serializer {
     pub fn with_element<T: Serializable>(&mut self, element: &T) -> Result<(), SerializeError> {
         let mut element_buffer = Vec::with_capacity(T::size_hint());
         element.serialize(&mut element_buffer)?;
+        self.with_encoding(element_buffer, T::is_variable_size());
+        Ok(())
+    }
 
+    // Like `with_element` but for an element which has already been encoded.
+    pub fn with_encoding(&mut self, mut element_buffer: Vec<u8>, is_variable_size: bool) {
         let element_buffer_len = element_buffer.len();
-        if T::is_variable_size() {
+        if is_variable_size {
             self.parts.push(Part::Offset(element_buffer_len));
             self.variable.append(&mut element_buffer);
             self.fixed_lengths_sum += BYTES_PER_LENGTH_OFFSET;
@@ -113,6 +118,38 @@ impl Serializer {
             self.parts.push(Part::Fixed(element_buffer));
             self.fixed_lengths_sum += element_buffer_len;
         }
-        Ok(())
+    }
+}
+
+fn serialize_fixed_homogeneous_composite<T>(
+    values: &[T],
+    buffer: &mut Vec<u8>,
+) -> Result<usize, SerializeError>
+where
+    T: Serializable,
+{
+    let total_size = values.len() * T::size_hint();
+    if total_size as u64 >= MAXIMUM_LENGTH {
+        return Err(SerializeError::MaximumEncodedLengthReached(total_size))
+    }
+    buffer.reserve(total_size);
+    T::serialize_fixed_sequence(values, buffer)
+}
+
+pub fn serialize_homogeneous_composite<T>(
+    values: &[T],
+    buffer: &mut Vec<u8>,
+) -> Result<usize, SerializeError>
+where
+    T: Serializable,
+{
+    if T::is_variable_size() {
+        let mut serializer = Serializer::default();
+        for value in values {
+            serializer.with_element(value)?;
+        }
+        serializer.serialize(buffer)
+    } else {
+        serialize_fixed_homogeneous_composite(values, buffer)
     }
 }
@@ -1,42 +1,8 @@
-use crate::lib::*;
-use hex::FromHexError;
-
-const HEX_ENCODING_PREFIX: &str = "0x";
-
-#[derive(Debug)]
-pub enum HexError {
-    Hex(FromHexError),
-    MissingPrefix,
-}
-
-impl fmt::Display for HexError {
-    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        match self {
-            Self::Hex(e) => write!(f, "{e}"),
-            Self::MissingPrefix => {
-                write!(f, "missing prefix `{HEX_ENCODING_PREFIX}` when deserializing hex data")
-            }
-        }
-    }
-}
-
-impl From<FromHexError> for HexError {
-    fn from(e: FromHexError) -> Self {
-        Self::Hex(e)
-    }
-}
-
-#[cfg(feature = "std")]
-impl std::error::Error for HexError {}
-
-fn try_bytes_from_hex_str(s: &str) -> Result<Vec<u8>, HexError> {
-    let target = s.strip_prefix(HEX_ENCODING_PREFIX).ok_or(HexError::MissingPrefix)?;
-    let data = hex::decode(target)?;
-    Ok(data)
-}
-
+//! Helpers to use with `#[serde(with = "...")]` on fields of SSZ types.
 pub mod as_hex {
-    use super::*;
+    #[cfg(not(feature = "std"))]
+    use crate::lib::{format, String};
+    use crate::utils::{try_bytes_from_hex_str, HEX_ENCODING_PREFIX};
     use serde::Deserialize;
 
     pub fn serialize<S, T: AsRef<[u8]>>(data: T, serializer: S) -> Result<S::Ok, S::Error>
@@ -63,6 +29,123 @@ pub mod as_hex {
     }
 }
 
+/// (De)serialize unsigned integers like `ruint::Uint` as decimal strings, e.g. `"1000000000"`.
+/// Deserialization also accepts `0x`-prefixed hex strings.
+///
+/// This is the default representation of `U256`.
+pub mod as_decimal {
+    #[cfg(not(feature = "std"))]
+    use crate::lib::String;
+    use crate::lib::{Display, FromStr};
+    use serde::Deserialize;
+
+    pub fn serialize<S, T: Display>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(value)
+    }
+
+    pub fn deserialize<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+        T: FromStr,
+        T::Err: Display,
+    {
+        let s = <String>::deserialize(deserializer)?;
+        s.parse().map_err(serde::de::Error::custom)
+    }
+}
+
+/// (De)serialize unsigned integers like `U256` as `0x`-prefixed hex strings, e.g. `"0x3e8"`,
+/// rather than as decimal strings. Deserialization accepts both.
+///
+/// ```
+/// # use ssz_rs::prelude::*;
+/// #[derive(serde::Serialize, serde::Deserialize)]
+/// struct Fee {
+///     #[serde(with = "ssz_rs::serde::as_hex_uint")]
+///     amount: U256,
+/// }
+///
+/// let fee = Fee { amount: U256::from(1000u64) };
+/// assert_eq!(serde_json::to_string(&fee).unwrap(), r#"{"amount":"0x3e8"}"#);
+/// ```
+pub mod as_hex_uint {
+    use crate::lib::{fmt::LowerHex, Display, FromStr};
+
+    pub fn serialize<S, T: LowerHex>(value: &T, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.collect_str(&format_args!("{value:#x}"))
+    }
+
+    pub fn deserialize<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+        T: FromStr,
+        T::Err: Display,
+    {
+        super::as_decimal::deserialize(deserializer)
+    }
+}
+
+/// (De)serialize an `Option<T>` like the SSZ union `Union[None, T]`, with the selector of the
+/// variant and its value, e.g. `{"selector":"1","data":12}` or `{"selector":"0","data":null}`.
+///
+/// Derived unions get the same representation with `#[ssz(serde = "tagged")]`.
+///
+/// ```
+/// # use ssz_rs::prelude::*;
+/// #[derive(serde::Serialize, serde::Deserialize)]
+/// struct Request {
+///     #[serde(with = "ssz_rs::serde::as_union")]
+///     limit: Option<u64>,
+/// }
+///
+/// let request = Request { limit: Some(12) };
+/// assert_eq!(
+///     serde_json::to_string(&request).unwrap(),
+///     r#"{"limit":{"selector":"1","data":12}}"#,
+/// );
+/// ```
+pub mod as_union {
+    use serde::{Deserialize, Serialize};
+
+    #[derive(Serialize, Deserialize)]
+    #[serde(tag = "selector", content = "data")]
+    enum Union<T> {
+        #[serde(rename = "0")]
+        None(()),
+        #[serde(rename = "1")]
+        Some(T),
+    }
+
+    pub fn serialize<S, T>(value: &Option<T>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+        T: Serialize,
+    {
+        match value {
+            Some(value) => Union::Some(value),
+            None => Union::None(()),
+        }
+        .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D, T>(deserializer: D) -> Result<Option<T>, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+        T: Deserialize<'de>,
+    {
+        match Union::deserialize(deserializer)? {
+            Union::Some(value) => Ok(Some(value)),
+            Union::None(()) => Ok(None),
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use crate::prelude::*;
@@ -144,4 +227,48 @@ mod test {
         let roundtrip_value: ComplexTestStruct = serde_json::from_value(json_repr).unwrap();
         assert_eq!(value, roundtrip_value);
     }
+
+    #[derive(PartialEq, Eq, Debug, serde::Serialize, serde::Deserialize)]
+    struct Balances {
+        decimal: U256,
+        #[serde(with = "crate::serde::as_hex_uint")]
+        hex: U256,
+    }
+
+    #[test]
+    fn test_uint_modes() {
+        let value = Balances { decimal: U256::from(255u64), hex: U256::from(255u64) };
+        let json = serde_json::to_string(&value).unwrap();
+        assert_eq!(json, r#"{"decimal":"255","hex":"0xff"}"#);
+        assert_eq!(serde_json::from_str::<Balances>(&json).unwrap(), value);
+
+        let json = r#"{"decimal":"0xff","hex":"255"}"#;
+        assert_eq!(serde_json::from_str::<Balances>(json).unwrap(), value);
+    }
+
+    #[derive(PartialEq, Eq, Debug, serde::Serialize, serde::Deserialize)]
+    struct Request {
+        #[serde(with = "crate::serde::as_union")]
+        limit: Option<List<u8, 4>>,
+    }
+
+    #[test]
+    fn test_option_as_union() {
+        let value = Request { limit: Some(List::try_from(vec![1, 2]).unwrap()) };
+        let json = serde_json::to_string(&value).unwrap();
+        assert_eq!(json, r#"{"limit":{"selector":"1","data":[1,2]}}"#);
+        assert_eq!(serde_json::from_str::<Request>(&json).unwrap(), value);
+
+        let value = Request { limit: None };
+        let json = serde_json::to_string(&value).unwrap();
+        assert_eq!(json, r#"{"limit":{"selector":"0","data":null}}"#);
+        assert_eq!(serde_json::from_str::<Request>(&json).unwrap(), value);
+
+        let json = r#"{"limit":{"data":[3],"selector":"1"}}"#;
+        let expected = Request { limit: Some(List::try_from(vec![3]).unwrap()) };
+        assert_eq!(serde_json::from_str::<Request>(json).unwrap(), expected);
+        assert!(
+            serde_json::from_str::<Request>(r#"{"limit":{"selector":"2","data":null}}"#).is_err()
+        );
+    }
 }
@@ -1,11 +1,11 @@
 use crate::{
-    de::{Deserialize, DeserializeError},
+    de::{impl_context_free, Deserialize, DeserializeError},
     lib::*,
-    merkleization::{pack_bytes, MerkleizationError, Merkleized, Node},
+    merkleization::{MerkleizationError, Merkleized, Node},
+    schema::{HasSchema, Schema},
     ser::{Serialize, SerializeError},
     Serializable, SimpleSerialize, BITS_PER_BYTE,
 };
-use num_bigint::BigUint;
 
 #[inline]
 fn bits_to_bytes(count: u32) -> usize {
@@ -22,6 +22,32 @@ macro_rules! define_uint {
             fn size_hint() -> usize {
                 bits_to_bytes(<$uint>::BITS)
             }
+
+            // NOTE: copying each value into place in a pre-sized buffer, rather than extending
+            // the buffer value by value, lets this compile down to a `memcpy` on little-endian
+            // targets
+            fn serialize_fixed_sequence(
+                values: &[Self],
+                buffer: &mut Vec<u8>,
+            ) -> Result<usize, SerializeError> {
+                let byte_size = bits_to_bytes(<$uint>::BITS);
+                let start = buffer.len();
+                buffer.resize(start + values.len() * byte_size, 0);
+                for (chunk, value) in buffer[start..].chunks_exact_mut(byte_size).zip(values) {
+                    chunk.copy_from_slice(&value.to_le_bytes());
+                }
+                Ok(values.len() * byte_size)
+            }
+
+            fn deserialize_fixed_sequence(encoding: &[u8]) -> Result<Vec<Self>, DeserializeError> {
+                let byte_size = bits_to_bytes(<$uint>::BITS);
+                let values = encoding
+                    .chunks_exact(byte_size)
+                    // SAFETY: chunks have exactly `byte_size` bytes; qed
+                    .map(|chunk| <$uint>::from_le_bytes(chunk.try_into().expect("is right size")))
+                    .collect();
+                Ok(values)
+            }
         }
 
         impl Serialize for $uint {
@@ -53,12 +79,14 @@ macro_rules! define_uint {
             }
         }
 
+        impl_context_free!([] $uint);
+
         impl Merkleized for $uint {
             fn hash_tree_root(&mut self) -> Result<Node, MerkleizationError> {
-                let mut root = vec![];
-                let _ = self.serialize(&mut root)?;
-                pack_bytes(&mut root);
-                Ok(root.as_slice().try_into().expect("is valid root"))
+                let mut root = Node::default();
+                let bytes = self.to_le_bytes();
+                root.as_mut()[..bytes.len()].copy_from_slice(&bytes);
+                Ok(root)
             }
 
             fn is_composite_type() -> bool {
@@ -66,6 +94,12 @@ macro_rules! define_uint {
             }
         }
 
+        impl HasSchema for $uint {
+            fn schema() -> Schema {
+                Schema::Uint(bits_to_bytes(<$uint>::BITS))
+            }
+        }
+
         impl SimpleSerialize for $uint {}
     };
 }
@@ -77,125 +111,10 @@ define_uint!(u64);
 define_uint!(u128);
 define_uint!(usize);
 
-/// An unsigned integer represented by 256 bits
-#[derive(Default, Debug, PartialEq, Eq, Clone, PartialOrd, Ord, Hash)]
-pub struct U256(BigUint);
-
-impl U256 {
-    pub fn new() -> Self {
-        Self(BigUint::default())
-    }
-
-    pub fn zero() -> Self {
-        Self::default()
-    }
-
-    pub fn try_from_bytes_le(bytes: &[u8]) -> Result<Self, DeserializeError> {
-        Self::deserialize(bytes)
-    }
-
-    pub fn from_bytes_le(bytes: [u8; 32]) -> Self {
-        Self::deserialize(&bytes).unwrap()
-    }
-
-    pub fn to_bytes_le(&self) -> Vec<u8> {
-        let mut bytes = self.0.to_bytes_le();
-        bytes.resize(Self::size_hint(), 0u8);
-        bytes
-    }
-
-    pub fn from_hex(data: &str) -> Option<Self> {
-        let data = data.strip_prefix("0x").unwrap_or(data);
-        BigUint::parse_bytes(data.as_bytes(), 16).map(Self)
-    }
-}
-
-impl From<u64> for U256 {
-    fn from(x: u64) -> Self {
-        Self(x.into())
-    }
-}
-
-impl Serializable for U256 {
-    fn is_variable_size() -> bool {
-        false
-    }
-
-    fn size_hint() -> usize {
-        32
-    }
-}
-
-impl Serialize for U256 {
-    fn serialize(&self, buffer: &mut Vec<u8>) -> Result<usize, SerializeError> {
-        buffer.extend_from_slice(&self.to_bytes_le());
-        Ok(Self::size_hint())
-    }
-}
-
-impl Deserialize for U256 {
-    fn deserialize(encoding: &[u8]) -> Result<Self, DeserializeError> {
-        let byte_size = Self::size_hint();
-        if encoding.len() < byte_size {
-            return Err(DeserializeError::ExpectedFurtherInput {
-                provided: encoding.len(),
-                expected: byte_size,
-            })
-        }
-        if encoding.len() > byte_size {
-            return Err(DeserializeError::AdditionalInput {
-                provided: encoding.len(),
-                expected: byte_size,
-            })
-        }
-
-        // SAFETY: index is safe because encoding.len() == byte_size; qed
-        let value = BigUint::from_bytes_le(&encoding[..byte_size]);
-        Ok(Self(value))
-    }
-}
-
-impl Merkleized for U256 {
-    fn hash_tree_root(&mut self) -> Result<Node, MerkleizationError> {
-        let data = self.to_bytes_le();
-        let node = Node::try_from(data.as_ref()).expect("is right size");
-        Ok(node)
-    }
-
-    fn is_composite_type() -> bool {
-        false
-    }
-}
-
-impl SimpleSerialize for U256 {}
-
-#[cfg(feature = "serde")]
-impl serde::Serialize for U256 {
-    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
-    where
-        S: serde::Serializer,
-    {
-        let output = format!("{}", self.0);
-        serializer.collect_str(&output)
-    }
-}
-
-#[cfg(feature = "serde")]
-impl<'de> serde::Deserialize<'de> for U256 {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: serde::Deserializer<'de>,
-    {
-        let s = <String>::deserialize(deserializer)?;
-        let value = s.parse::<BigUint>().map_err(serde::de::Error::custom)?;
-        Ok(Self(value))
-    }
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::serialize;
+    use crate::{serialize, U256};
 
     #[test]
     fn encode_uints() {
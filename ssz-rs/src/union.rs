@@ -1,7 +1,8 @@
 use crate::{
-    de::{Deserialize, DeserializeError},
+    de::{Deserialize, DeserializeError, DeserializeWithContext},
     lib::*,
     merkleization::{mix_in_selector, MerkleizationError, Merkleized, Node},
+    schema::{HasSchema, Schema, Variant},
     ser::{Serialize, SerializeError},
     Serializable, SimpleSerialize,
 };
@@ -44,28 +45,54 @@ where
     T: Serializable,
 {
     fn deserialize(encoding: &[u8]) -> Result<Self, DeserializeError> {
-        if encoding.is_empty() {
-            return Err(DeserializeError::ExpectedFurtherInput { provided: 0, expected: 1 })
-        }
+        deserialize_option(encoding, T::deserialize)
+    }
+}
 
-        // SAFETY: index is safe because encoding is not empty; qed
-        match encoding[0] {
-            0 => {
-                if encoding.len() != 1 {
-                    return Err(DeserializeError::AdditionalInput {
-                        provided: encoding.len(),
-                        expected: 1,
-                    })
-                }
-                Ok(None)
-            }
-            1 => {
-                // SAFETY: index is safe because encoding is not empty; qed
-                let inner = T::deserialize(&encoding[1..])?;
-                Ok(Some(inner))
+impl<T, C> DeserializeWithContext<C> for Option<T>
+where
+    T: Serializable + DeserializeWithContext<C>,
+    C: ?Sized,
+{
+    fn deserialize_with_context(encoding: &[u8], context: &C) -> Result<Self, DeserializeError> {
+        deserialize_option(encoding, |encoding| T::deserialize_with_context(encoding, context))
+    }
+
+    fn is_variable_size_with_context(_: &C) -> bool {
+        true
+    }
+
+    fn size_hint_with_context(_: &C) -> usize {
+        0
+    }
+}
+
+// `deserialize` decodes the value of `Some` from the slice holding its encoding.
+fn deserialize_option<T>(
+    encoding: &[u8],
+    deserialize: impl FnOnce(&[u8]) -> Result<T, DeserializeError>,
+) -> Result<Option<T>, DeserializeError> {
+    if encoding.is_empty() {
+        return Err(DeserializeError::ExpectedFurtherInput { provided: 0, expected: 1 })
+    }
+
+    // SAFETY: index is safe because encoding is not empty; qed
+    match encoding[0] {
+        0 => {
+            if encoding.len() != 1 {
+                return Err(DeserializeError::AdditionalInput {
+                    provided: encoding.len(),
+                    expected: 1,
+                })
             }
-            b => Err(DeserializeError::InvalidByte(b)),
+            Ok(None)
+        }
+        1 => {
+            // SAFETY: index is safe because encoding is not empty; qed
+            let inner = deserialize(&encoding[1..])?;
+            Ok(Some(inner))
         }
+        b => Err(DeserializeError::InvalidByte(b)),
     }
 }
 
@@ -81,26 +108,37 @@ where
     }
 }
 
+impl<T> HasSchema for Option<T>
+where
+    T: HasSchema,
+{
+    fn schema() -> Schema {
+        Schema::Union {
+            name: "Option",
+            variants: vec![
+                Variant { name: "None", selector: 0, schema: None },
+                Variant { name: "Some", selector: 1, schema: Some(T::schema()) },
+            ],
+        }
+    }
+}
+
 impl<T> SimpleSerialize for Option<T> where T: SimpleSerialize {}
 
 #[cfg(test)]
 mod tests {
-    use crate::prelude::*;
+    use crate::{merkleization::mix_in_selector, prelude::*};
 
-    #[derive(Debug, PartialEq, Eq, SimpleSerialize)]
+    #[derive(Debug, Default, PartialEq, Eq, SimpleSerialize)]
     enum AnotherOption {
+        #[default]
         None,
         A(u8),
         B(u8),
     }
 
-    impl Default for AnotherOption {
-        fn default() -> Self {
-            Self::None
-        }
-    }
-
     #[derive(Debug, Default, PartialEq, Eq, SimpleSerialize)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     struct Inner {
         data: List<u8, 8>,
     }
@@ -156,6 +194,44 @@ mod tests {
         }
     }
 
+    // selectors are pinned so the variants can be in any order
+    #[derive(Debug, PartialEq, Eq, SimpleSerialize)]
+    enum Pinned {
+        #[ssz(selector = 2)]
+        B(u16),
+        C(List<u8, 4>),
+        #[ssz(selector = 1)]
+        A(u8),
+        #[ssz(selector = 0)]
+        Empty,
+    }
+
+    #[cfg(feature = "serde")]
+    #[derive(Debug, PartialEq, Eq, SimpleSerialize)]
+    #[ssz(serde = "tagged")]
+    enum Tagged<T: SimpleSerialize> {
+        Empty,
+        #[ssz(selector = 2)]
+        Value(T),
+        Bytes(List<u8, 4>),
+    }
+
+    #[cfg(feature = "serde")]
+    #[derive(Debug, PartialEq, Eq, SimpleSerialize)]
+    #[ssz(serde = "untagged")]
+    enum Untagged {
+        A(u8),
+        B(Inner),
+    }
+
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, SimpleSerialize)]
+    #[ssz(enum_behaviour = "tag")]
+    enum Status {
+        Ok,
+        Err = 4,
+        Unknown,
+    }
+
     #[test]
     fn test_option() {
         let mut x = Some(12u8);
@@ -354,4 +430,95 @@ mod tests {
         let recovered = Boo::deserialize(&buffer).expect("can decode");
         assert_eq!(value, recovered);
     }
+
+    #[test]
+    fn test_explicit_selectors() {
+        let mut value = Pinned::B(7);
+        let encoding = serialize(&value).unwrap();
+        assert_eq!(encoding, [2, 7, 0]);
+        assert_eq!(Pinned::deserialize(&encoding).unwrap(), value);
+        let expected = mix_in_selector(&7u16.hash_tree_root().unwrap(), 2);
+        assert_eq!(value.hash_tree_root().unwrap(), expected);
+
+        let mut value = Pinned::C(List::try_from(vec![1, 2]).unwrap());
+        let encoding = serialize(&value).unwrap();
+        assert_eq!(encoding, [3, 1, 2]);
+        assert_eq!(Pinned::deserialize(&encoding).unwrap(), value);
+        assert_eq!(
+            Pinned::schema().hash_tree_root_of(&encoding).unwrap(),
+            value.hash_tree_root().unwrap()
+        );
+
+        let mut value = Pinned::Empty;
+        assert_eq!(serialize(&value).unwrap(), [0]);
+        assert_eq!(Pinned::deserialize(&[0]).unwrap(), value);
+        assert_eq!(value.hash_tree_root().unwrap(), mix_in_selector(&Node::default(), 0));
+        assert!(Pinned::deserialize(&[0, 1]).is_err());
+        assert!(Pinned::deserialize(&[4, 1]).is_err());
+
+        let Schema::Union { variants, .. } = Pinned::schema() else { unreachable!() };
+        let selectors = variants.iter().map(|v| (v.name, v.selector)).collect::<Vec<_>>();
+        assert_eq!(selectors, [("B", 2), ("C", 3), ("A", 1), ("Empty", 0)]);
+    }
+
+    #[test]
+    fn test_tag_enum() {
+        let mut value = Status::Err;
+        assert_eq!(serialize(&value).unwrap(), [4]);
+        assert_eq!(serialize(&Status::Unknown).unwrap(), [5]);
+        assert_eq!(Status::deserialize(&[0]).unwrap(), Status::Ok);
+        assert_eq!(Status::deserialize(&[5]).unwrap(), Status::Unknown);
+        assert!(matches!(Status::deserialize(&[1]), Err(DeserializeError::InvalidByte(1))));
+        assert!(matches!(
+            Status::deserialize(&[0, 0]),
+            Err(DeserializeError::AdditionalInput { .. })
+        ));
+        assert_eq!(value.hash_tree_root().unwrap(), 4u8.hash_tree_root().unwrap());
+        assert_eq!(Status::size_hint(), 1);
+        assert_eq!(Status::schema(), Schema::Uint(1));
+
+        // packed like a `uint8` in a list
+        let mut values =
+            List::<Status, 8>::try_from(vec![Status::Ok, Status::Err, Status::Unknown]).unwrap();
+        let mut expected = List::<u8, 8>::try_from(vec![0, 4, 5]).unwrap();
+        assert_eq!(serialize(&values).unwrap(), serialize(&expected).unwrap());
+        assert_eq!(values.hash_tree_root().unwrap(), expected.hash_tree_root().unwrap());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_tagged() {
+        let value = Tagged::Value(12u64);
+        let json = serde_json::to_string(&value).unwrap();
+        assert_eq!(json, r#"{"selector":"2","data":12}"#);
+        assert_eq!(serde_json::from_str::<Tagged<u64>>(&json).unwrap(), value);
+
+        let value = Tagged::<u64>::Bytes(List::try_from(vec![1, 2]).unwrap());
+        let json = serde_json::to_string(&value).unwrap();
+        assert_eq!(json, r#"{"selector":"3","data":[1,2]}"#);
+        assert_eq!(serde_json::from_str::<Tagged<u64>>(&json).unwrap(), value);
+
+        let value = Tagged::<u64>::Empty;
+        let json = serde_json::to_string(&value).unwrap();
+        assert_eq!(json, r#"{"selector":"0","data":null}"#);
+        assert_eq!(serde_json::from_str::<Tagged<u64>>(&json).unwrap(), value);
+
+        let json = r#"{"data":7,"selector":"2"}"#;
+        assert_eq!(serde_json::from_str::<Tagged<u64>>(json).unwrap(), Tagged::Value(7));
+        assert!(serde_json::from_str::<Tagged<u64>>(r#"{"selector":"1","data":7}"#).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_untagged() {
+        let value = Untagged::A(12);
+        let json = serde_json::to_string(&value).unwrap();
+        assert_eq!(json, "12");
+        assert_eq!(serde_json::from_str::<Untagged>(&json).unwrap(), value);
+
+        let value = Untagged::B(Inner { data: List::try_from(vec![3]).unwrap() });
+        let json = serde_json::to_string(&value).unwrap();
+        assert_eq!(json, r#"{"data":[3]}"#);
+        assert_eq!(serde_json::from_str::<Untagged>(&json).unwrap(), value);
+    }
 }
@@ -1,4 +1,12 @@
-use crate::{de::DeserializeError, lib::*, ser::SerializeError, Serializable};
+use crate::{
+    de::DeserializeError,
+    error::{Error, HexError},
+    lib::*,
+    merkleization::Node,
+    schema::{with_schema_of, HasSchema},
+    ser::SerializeError,
+    Serializable,
+};
 
 /// `serialize` is a convenience function for taking a value that
 /// implements `SimpleSerialize` and attempting to encode it to
@@ -22,6 +30,18 @@ where
     T::deserialize(encoding)
 }
 
+/// `hash_tree_root_from_bytes` is a convenience function for computing the hash tree root of
+/// some value of type `T` given its SSZ `encoding`, without deserializing the value.
+///
+/// The encoding is merkleized in a single pass without copying it and is validated along the
+/// way, so this fails on any encoding that `T::deserialize` would reject.
+pub fn hash_tree_root_from_bytes<T>(encoding: &[u8]) -> Result<Node, Error>
+where
+    T: HasSchema + 'static,
+{
+    with_schema_of::<T, _>(|schema| schema.hash_tree_root_of(encoding))
+}
+
 pub(crate) fn write_bytes_to_lower_hex<T: AsRef<[u8]>>(
     f: &mut fmt::Formatter<'_>,
     data: T,
@@ -34,3 +54,14 @@ pub(crate) fn write_bytes_to_lower_hex<T: AsRef<[u8]>>(
     }
     Ok(())
 }
+
+pub(crate) const HEX_ENCODING_PREFIX: &str = "0x";
+
+pub(crate) fn strip_hex_prefix(s: &str) -> Result<&str, HexError> {
+    s.strip_prefix(HEX_ENCODING_PREFIX).ok_or(HexError::MissingPrefix)
+}
+
+pub(crate) fn try_bytes_from_hex_str(s: &str) -> Result<Vec<u8>, HexError> {
+    let data = hex::decode(strip_hex_prefix(s)?)?;
+    Ok(data)
+}
@@ -1,19 +1,31 @@
 use crate::{
-    de::{deserialize_homogeneous_composite, Deserialize, DeserializeError},
+    de::{
+        deserialize_homogeneous_composite, deserialize_homogeneous_composite_with_context,
+        Deserialize, DeserializeError, DeserializeWithContext,
+    },
     error::{Error, InstanceError, TypeError},
     lib::*,
-    merkleization::{elements_to_chunks, merkleize, pack, MerkleizationError, Merkleized, Node},
-    ser::{Serialize, SerializeError, Serializer},
+    merkleization::{merkleize_elements, MerkleizationError, Merkleized, Node},
+    schema::{HasSchema, Schema},
+    ser::{serialize_homogeneous_composite, Serialize, SerializeError},
     Serializable, SimpleSerialize,
 };
 #[cfg(feature = "serde")]
 use serde::ser::SerializeSeq;
 
 /// A homogenous collection of a fixed number of values.
+///
+/// The values are stored inline in an array, so a `Vector` of `Copy` values is `Copy` and
+/// decoding one of values of a fixed size never allocates. A `Vector` can be built from an
+/// array of exactly `N` values without checks.
+///
+/// NOTE: a `Vector` is as large as its `N` values, so moving a large vector like
+/// `Vector<Node, 65536>` copies all of them and may need a larger stack.
+///
 /// NOTE: a `Vector` of length `0` is illegal.
-#[derive(Clone)]
+#[derive(Clone, Copy)]
 pub struct Vector<T: Serializable, const N: usize> {
-    data: Vec<T>,
+    data: [T; N],
 }
 
 impl<T: Serializable, const N: usize> AsRef<[T]> for Vector<T, N> {
@@ -37,12 +49,24 @@ impl<T: Serializable, const N: usize> TryFrom<Vec<T>> for Vector<T, N> {
         if N == 0 {
             return Err((data, Error::Type(TypeError::InvalidBound(N))))
         }
-        if data.len() != N {
+        data.try_into().map(|data| Self { data }).map_err(|data: Vec<T>| {
             let len = data.len();
-            Err((data, Error::Instance(InstanceError::Exact { required: N, provided: len })))
-        } else {
-            Ok(Self { data })
-        }
+            (data, Error::Instance(InstanceError::Exact { required: N, provided: len }))
+        })
+    }
+}
+
+// NOTE: a `Vector` of length `0` can be constructed from an empty array, but is rejected
+// when serializing, as it is everywhere else
+impl<T: Serializable, const N: usize> From<[T; N]> for Vector<T, N> {
+    fn from(data: [T; N]) -> Self {
+        Self { data }
+    }
+}
+
+impl<T: Serializable, const N: usize> From<Vector<T, N>> for [T; N] {
+    fn from(vector: Vector<T, N>) -> Self {
+        vector.data
     }
 }
 
@@ -68,16 +92,7 @@ where
         // that `N` is non-zero with const generics so panics are possible.
         assert!(N > 0);
 
-        let mut data = Vec::with_capacity(N);
-        for _ in 0..N {
-            data.push(T::default());
-        }
-
-        // SAFETY: panic can't happen because data.len() == N != 0; qed
-        data.try_into()
-            // need to drop data so we do not require it as Debug as required by `expect`
-            .map_err(|(_, err)| err)
-            .expect("any Vector can be constructed with nonzero default data")
+        Self { data: array::from_fn(|_| T::default()) }
     }
 }
 
@@ -85,7 +100,7 @@ impl<T, const N: usize> Deref for Vector<T, N>
 where
     T: Serializable,
 {
-    type Target = Vec<T>;
+    type Target = [T];
 
     fn deref(&self) -> &Self::Target {
         &self.data
@@ -136,11 +151,7 @@ where
         if N == 0 {
             return Err(TypeError::InvalidBound(N).into())
         }
-        let mut serializer = Serializer::default();
-        for element in &self.data {
-            serializer.with_element(element)?;
-        }
-        serializer.serialize(buffer)
+        serialize_homogeneous_composite(&self.data, buffer)
     }
 }
 
@@ -149,31 +160,126 @@ where
     T: Serializable,
 {
     fn deserialize(encoding: &[u8]) -> Result<Self, DeserializeError> {
-        if N == 0 {
-            return Err(TypeError::InvalidBound(N).into())
-        }
+        check_length::<N>(encoding, T::is_variable_size(), T::size_hint())?;
         if !T::is_variable_size() {
-            let expected_length = N * T::size_hint();
-            if encoding.len() < expected_length {
-                return Err(DeserializeError::ExpectedFurtherInput {
-                    provided: encoding.len(),
-                    expected: expected_length,
-                })
-            }
-            if encoding.len() > expected_length {
-                return Err(DeserializeError::AdditionalInput {
-                    provided: encoding.len(),
-                    expected: expected_length,
-                })
-            }
+            let data = deserialize_fixed_elements(encoding, T::size_hint(), T::deserialize)?;
+            return Ok(Self { data })
         }
         let inner = deserialize_homogeneous_composite(encoding)?;
-        inner.try_into().map_err(|(_, err)| match err {
-            Error::Deserialize(err) => err,
-            Error::Instance(err) => DeserializeError::InvalidInstance(err),
-            Error::Type(err) => DeserializeError::InvalidType(err),
-            _ => unreachable!("no other error variant can be returned at this point"),
-        })
+        Self::from_elements(inner)
+    }
+}
+
+impl<T, C, const N: usize> DeserializeWithContext<C> for Vector<T, N>
+where
+    T: Serializable + DeserializeWithContext<C>,
+    C: ?Sized,
+{
+    fn deserialize_with_context(encoding: &[u8], context: &C) -> Result<Self, DeserializeError> {
+        check_length::<N>(
+            encoding,
+            T::is_variable_size_with_context(context),
+            T::size_hint_with_context(context),
+        )?;
+        if !T::is_variable_size_with_context(context) {
+            let data = deserialize_fixed_elements(
+                encoding,
+                T::size_hint_with_context(context),
+                |encoding| T::deserialize_with_context(encoding, context),
+            )?;
+            return Ok(Self { data })
+        }
+        let inner = deserialize_homogeneous_composite_with_context(encoding, context)?;
+        Self::from_elements(inner)
+    }
+
+    fn is_variable_size_with_context(context: &C) -> bool {
+        T::is_variable_size_with_context(context)
+    }
+
+    fn size_hint_with_context(context: &C) -> usize {
+        T::size_hint_with_context(context) * N
+    }
+}
+
+// Checks the bound `N` is valid and that `encoding` holds exactly `N` elements if they are of a
+// fixed size.
+fn check_length<const N: usize>(
+    encoding: &[u8],
+    is_variable_size: bool,
+    size_hint: usize,
+) -> Result<(), DeserializeError> {
+    if N == 0 {
+        return Err(TypeError::InvalidBound(N).into())
+    }
+    if !is_variable_size {
+        let expected_length = N * size_hint;
+        if encoding.len() < expected_length {
+            return Err(DeserializeError::ExpectedFurtherInput {
+                provided: encoding.len(),
+                expected: expected_length,
+            })
+        }
+        if encoding.len() > expected_length {
+            return Err(DeserializeError::AdditionalInput {
+                provided: encoding.len(),
+                expected: expected_length,
+            })
+        }
+    }
+    Ok(())
+}
+
+// Decodes `N` values of `size` bytes each straight into an array.
+// NOTE: callers have already validated `encoding` holds exactly `N` values.
+fn deserialize_fixed_elements<T, const N: usize>(
+    encoding: &[u8],
+    size: usize,
+    deserialize: impl Fn(&[u8]) -> Result<T, DeserializeError>,
+) -> Result<[T; N], DeserializeError> {
+    let mut elements = PartialArray::<T, N>::new();
+    for i in 0..N {
+        // SAFETY: index is safe because encoding.len() == N * size; qed
+        elements.push(deserialize(&encoding[i * size..(i + 1) * size])?);
+    }
+    Ok(elements.into_array())
+}
+
+// An array written from the front, which drops the values written so far if it is dropped
+// before it is full, e.g. when decoding a later value fails.
+struct PartialArray<T, const N: usize> {
+    data: [mem::MaybeUninit<T>; N],
+    // invariant: the first `len` values of `data` are initialized
+    len: usize,
+}
+
+impl<T, const N: usize> PartialArray<T, N> {
+    fn new() -> Self {
+        Self { data: [const { mem::MaybeUninit::uninit() }; N], len: 0 }
+    }
+
+    // Panics if the array is already full.
+    fn push(&mut self, value: T) {
+        self.data[self.len].write(value);
+        self.len += 1;
+    }
+
+    // Panics if the array is not full.
+    fn into_array(self) -> [T; N] {
+        assert_eq!(self.len, N, "every value of the array is written");
+        let this = mem::ManuallyDrop::new(self);
+        // SAFETY: all `N` values are initialized, `MaybeUninit<T>` has the same layout as `T`
+        // and `this` is never dropped, so the values are moved out exactly once; qed
+        unsafe { ptr::read((&this.data as *const [mem::MaybeUninit<T>; N]).cast::<[T; N]>()) }
+    }
+}
+
+impl<T, const N: usize> Drop for PartialArray<T, N> {
+    fn drop(&mut self) {
+        for value in &mut self.data[..self.len] {
+            // SAFETY: the first `len` values are initialized and dropped only here; qed
+            unsafe { value.assume_init_drop() }
+        }
     }
 }
 
@@ -181,6 +287,16 @@ impl<T, const N: usize> Vector<T, N>
 where
     T: Serializable,
 {
+    // Builds the vector from its decoded elements, checking there are exactly `N` of them.
+    fn from_elements(elements: Vec<T>) -> Result<Self, DeserializeError> {
+        elements.try_into().map_err(|(_, err)| match err {
+            Error::Deserialize(err) => err,
+            Error::Instance(err) => DeserializeError::InvalidInstance(err),
+            Error::Type(err) => DeserializeError::InvalidType(err),
+            _ => unreachable!("no other error variant can be returned at this point"),
+        })
+    }
+
     pub fn iter_mut(&mut self) -> IterMut<'_, T> {
         let inner = self.data.iter_mut();
         IterMut { inner }
@@ -204,14 +320,7 @@ where
     T: SimpleSerialize,
 {
     fn compute_hash_tree_root(&mut self) -> Result<Node, MerkleizationError> {
-        if T::is_composite_type() {
-            let count = self.len();
-            let chunks = elements_to_chunks(self.data.iter_mut().enumerate(), count)?;
-            merkleize(&chunks, None)
-        } else {
-            let chunks = pack(&self.data)?;
-            merkleize(&chunks, None)
-        }
+        merkleize_elements(self.data.iter_mut(), None)
     }
 }
 
@@ -224,6 +333,15 @@ where
     }
 }
 
+impl<T, const N: usize> HasSchema for Vector<T, N>
+where
+    T: Serializable + HasSchema,
+{
+    fn schema() -> Schema {
+        Schema::Vector { element: Box::new(T::schema()), length: N }
+    }
+}
+
 impl<T, const N: usize> SimpleSerialize for Vector<T, N> where T: SimpleSerialize {}
 
 #[cfg(feature = "serde")]
@@ -289,6 +407,42 @@ mod tests {
         assert_eq!(vector[10..], [0u8; 10]);
     }
 
+    #[test]
+    fn test_from_array() {
+        let vector = Vector::<u16, 3>::from([1, 2, 3]);
+        let copy = vector;
+        assert_eq!(vector, copy);
+        assert_eq!(<[u16; 3]>::from(vector), [1, 2, 3]);
+
+        let vector = Vector::<u16, 3>::try_from(vec![1, 2, 3]).unwrap();
+        assert_eq!(vector, copy);
+        let (data, err) = Vector::<u16, 3>::try_from(vec![1, 2]).unwrap_err();
+        assert_eq!(data, [1, 2]);
+        assert!(matches!(err, Error::Instance(InstanceError::Exact { required: 3, provided: 2 })));
+    }
+
+    #[test]
+    fn test_large_vector() {
+        // the values of a `Vector` of 2 MiB are held on the stack
+        let handle = std::thread::Builder::new().stack_size(64 << 20).spawn(|| {
+            let mut vector = Box::new(Vector::<[u8; 32], 65536>::default());
+            vector[65535] = [1; 32];
+            let encoding = serialize(&*vector).unwrap();
+            let recovered = Vector::<[u8; 32], 65536>::deserialize(&encoding).unwrap();
+            assert_eq!(recovered[65535], [1; 32]);
+            assert_eq!(recovered.len(), 65536);
+        });
+        handle.unwrap().join().unwrap();
+    }
+
+    #[test]
+    fn decode_vector_with_invalid_element() {
+        let result = Vector::<bool, 4>::deserialize(&[1, 0, 2, 1]);
+        assert!(matches!(result, Err(DeserializeError::InvalidByte(2))));
+        let result = Vector::<List<u8, 1>, 2>::deserialize(&[8, 0, 0, 0, 8, 0, 0, 0, 1, 2]);
+        assert!(result.is_err());
+    }
+
     #[test]
     #[should_panic]
     fn test_try_from_invalid() {
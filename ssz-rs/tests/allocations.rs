@@ -0,0 +1,159 @@
+use ssz_rs::prelude::*;
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+};
+
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn allocations_during(f: impl FnOnce()) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    f();
+    ALLOCATIONS.with(Cell::get) - before
+}
+
+#[derive(Debug, Default, SimpleSerialize)]
+struct Checkpoint {
+    epoch: u64,
+    root: Node,
+}
+
+#[derive(Debug, Default, SimpleSerialize)]
+struct AttestationData {
+    slot: u64,
+    index: u64,
+    beacon_block_root: Node,
+    source: Checkpoint,
+    target: Checkpoint,
+}
+
+#[derive(Debug, Default, SimpleSerialize)]
+enum Choice {
+    #[default]
+    None,
+    Some(u32),
+}
+
+#[test]
+fn test_merkleization_does_not_allocate() {
+    let mut value = 23u64;
+    let mut flag = true;
+    let mut data = AttestationData { slot: 12, index: 3, ..Default::default() };
+    let mut choice = Choice::Some(5);
+    let mut flags = Bitvector::<64>::default();
+    let mut more_flags = Bitvector::<1024>::default();
+
+    let count = allocations_during(|| {
+        value.hash_tree_root().unwrap();
+        flag.hash_tree_root().unwrap();
+        data.hash_tree_root().unwrap();
+        choice.hash_tree_root().unwrap();
+        flags.set(3, true);
+        flags.hash_tree_root().unwrap();
+        more_flags.set(700, true);
+        more_flags.hash_tree_root().unwrap();
+    });
+    assert_eq!(count, 0);
+}
+
+#[derive(Debug, Default, SimpleSerialize)]
+struct Attestations {
+    data: List<AttestationData, 16>,
+    bits: Bitlist<64>,
+    choice: Choice,
+}
+
+#[test]
+fn test_hash_tree_root_from_bytes_does_not_allocate() {
+    let mut value = Attestations {
+        data: List::try_from(
+            (0..5).map(|slot| AttestationData { slot, ..Default::default() }).collect::<Vec<_>>(),
+        )
+        .unwrap(),
+        bits: Bitlist::try_from([true, false, true].as_ref()).unwrap(),
+        choice: Choice::Some(5),
+    };
+    let encoding = serialize(&value).unwrap();
+    let root = value.hash_tree_root().unwrap();
+    // the schema is built on first use
+    hash_tree_root_from_bytes::<Attestations>(&encoding).unwrap();
+
+    let count = allocations_during(|| {
+        assert_eq!(hash_tree_root_from_bytes::<Attestations>(&encoding).unwrap(), root);
+    });
+    assert_eq!(count, 0);
+}
+
+#[test]
+fn test_bitvector_does_not_allocate() {
+    let encoding = [0b1010_0101u8; 64];
+    let count = allocations_during(|| {
+        let mut bits = Bitvector::<512>::deserialize(&encoding).unwrap();
+        let mut copy = bits;
+        copy.set(1, true);
+        assert_eq!(bits.union(&copy).count_ones(), 257);
+        assert_eq!(Bitvector::<4>::default(), Bitvector::<4>::deserialize(&[0]).unwrap());
+        bits.hash_tree_root().unwrap();
+    });
+    assert_eq!(count, 0);
+}
+
+#[test]
+fn test_vector_decoding_does_not_allocate() {
+    let encoding = [7u8; 32];
+    let count = allocations_during(|| {
+        let root = Vector::<u8, 32>::deserialize(&encoding).unwrap();
+        let copy = root;
+        assert_eq!(<[u8; 32]>::from(copy), encoding);
+        let values = Vector::<u64, 4>::deserialize(&encoding).unwrap();
+        assert_eq!(values[3], u64::from_le_bytes([7u8; 8]));
+        let flags = Vector::<bool, 32>::deserialize(&[1u8; 32]).unwrap();
+        assert!(flags.iter().all(|flag| *flag));
+    });
+    assert_eq!(count, 0);
+}
+
+#[test]
+fn test_sequence_merkleization_does_not_allocate() {
+    let mut list = List::<u64, 1024>::try_from((0..100).collect::<Vec<_>>()).unwrap();
+    let mut nested = List::<List<u16, 8>, 16>::try_from(vec![
+        List::try_from(vec![1, 2, 3]).unwrap(),
+        List::default(),
+    ])
+    .unwrap();
+    let mut data = List::<AttestationData, 16>::try_from_iter(
+        (0..3).map(|slot| AttestationData { slot, ..Default::default() }),
+    )
+    .unwrap();
+    let mut vector = Vector::<u16, 40>::default();
+    let mut bits = Bitlist::<2048>::try_from([true; 700].as_ref()).unwrap();
+    let mut bytes = ByteList::<1024>::try_from([7u8; 100].as_ref()).unwrap();
+
+    let count = allocations_during(|| {
+        list.hash_tree_root().unwrap();
+        nested.hash_tree_root().unwrap();
+        data.hash_tree_root().unwrap();
+        vector.hash_tree_root().unwrap();
+        bits.hash_tree_root().unwrap();
+        bytes.hash_tree_root().unwrap();
+    });
+    assert_eq!(count, 0);
+}
@@ -0,0 +1,6 @@
+// Checks the errors reported by the derive macros for input which cannot be an SSZ type.
+#[test]
+fn derive_errors() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
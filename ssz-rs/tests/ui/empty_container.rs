@@ -0,0 +1,15 @@
+use ssz_rs::prelude::*;
+
+#[derive(SimpleSerialize)]
+struct Empty {}
+
+#[derive(SimpleSerialize)]
+struct OnlySkipped {
+    #[ssz(skip)]
+    a: u8,
+}
+
+#[derive(SimpleSerialize)]
+struct SkippedPair(#[ssz(skip)] u8, #[ssz(skip)] u16);
+
+fn main() {}
@@ -0,0 +1,36 @@
+use ssz_rs::prelude::*;
+
+#[derive(SimpleSerialize)]
+#[ssz(enum_behaviour = "tag")]
+enum WithPayload {
+    A,
+    B(u8),
+}
+
+#[derive(SimpleSerialize)]
+#[ssz(enum_behaviour = "tag")]
+enum TooLarge {
+    A = 255,
+    B,
+}
+
+#[derive(SimpleSerialize)]
+#[ssz(enum_behaviour = "tag")]
+enum WithSelector {
+    #[ssz(selector = 1)]
+    A,
+}
+
+#[derive(SimpleSerialize)]
+#[ssz(enum_behaviour = "bits")]
+enum Unknown {
+    A,
+}
+
+#[derive(SimpleSerialize)]
+#[ssz(enum_behaviour = "tag")]
+struct NotAnEnum {
+    a: u8,
+}
+
+fn main() {}
@@ -0,0 +1,42 @@
+use ssz_rs::prelude::*;
+
+#[derive(SimpleSerialize)]
+#[ssz(unknown)]
+struct UnknownAttribute {
+    a: u8,
+}
+
+#[derive(SimpleSerialize)]
+struct SkipWith {
+    #[ssz(skip, with = "module")]
+    a: u8,
+}
+
+#[derive(SimpleSerialize)]
+#[ssz(transparent)]
+struct Transparent {
+    a: u8,
+    b: u8,
+}
+
+#[derive(SimpleSerialize)]
+#[ssz(bound = 1)]
+struct Bound<T> {
+    a: T,
+}
+
+#[derive(SimpleSerialize)]
+#[ssz(field_metadata)]
+enum FieldMetadata {
+    A(u8),
+    B(u16),
+}
+
+#[derive(SimpleSerialize)]
+struct NoFieldMetadata {
+    a: u8,
+}
+
+fn main() {
+    let _ = NoFieldMetadata::FIELD_COUNT;
+}
@@ -0,0 +1,9 @@
+use ssz_rs::prelude::*;
+
+#[derive(SimpleSerialize)]
+union Bits {
+    a: u8,
+    b: u16,
+}
+
+fn main() {}
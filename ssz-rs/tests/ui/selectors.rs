@@ -0,0 +1,34 @@
+use ssz_rs::prelude::*;
+
+#[derive(SimpleSerialize)]
+enum Duplicate {
+    #[ssz(selector = 1)]
+    A(u8),
+    #[ssz(selector = 1)]
+    B(u16),
+}
+
+#[derive(SimpleSerialize)]
+enum Implicit {
+    #[ssz(selector = 2)]
+    A(u8),
+    B(u16),
+    #[ssz(selector = 3)]
+    C(u32),
+}
+
+#[derive(SimpleSerialize)]
+enum OutOfRange {
+    A(u8),
+    #[ssz(selector = 127)]
+    B(u16),
+    C(u32),
+}
+
+#[derive(SimpleSerialize)]
+enum NotAnInteger {
+    #[ssz(selector = "1")]
+    A(u8),
+}
+
+fn main() {}
@@ -0,0 +1,23 @@
+use ssz_rs::prelude::*;
+
+#[derive(SimpleSerialize)]
+#[ssz(serde = "tagged")]
+struct NotAnEnum {
+    a: u8,
+}
+
+#[derive(SimpleSerialize)]
+#[ssz(enum_behaviour = "tag", serde = "tagged")]
+enum Tag {
+    A,
+    B,
+}
+
+#[derive(SimpleSerialize)]
+#[ssz(serde = "adjacent")]
+enum Unknown {
+    A(u8),
+    B(u16),
+}
+
+fn main() {}
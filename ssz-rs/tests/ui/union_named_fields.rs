@@ -0,0 +1,9 @@
+use ssz_rs::prelude::*;
+
+#[derive(SimpleSerialize)]
+enum Message {
+    Ping(u64),
+    Pong { nonce: u64 },
+}
+
+fn main() {}
@@ -0,0 +1,14 @@
+use ssz_rs::prelude::*;
+
+#[derive(SimpleSerialize)]
+enum Late {
+    Some(u8),
+    None,
+}
+
+#[derive(SimpleSerialize)]
+enum OnlyNone {
+    None,
+}
+
+fn main() {}
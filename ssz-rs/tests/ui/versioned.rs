@@ -0,0 +1,43 @@
+use ssz_rs::prelude::*;
+
+#[versioned(derive(Debug))]
+#[derive(SimpleSerialize)]
+struct NoVariants {
+    a: u8,
+}
+
+#[versioned(variants(A, B, A))]
+#[derive(SimpleSerialize)]
+struct DuplicateVariant {
+    a: u8,
+}
+
+#[versioned(variants(A, B))]
+#[derive(SimpleSerialize)]
+struct UnknownVariant {
+    a: u8,
+    #[versioned(only(C))]
+    b: u8,
+}
+
+#[versioned(variants(A, B))]
+#[derive(SimpleSerialize)]
+struct Tuple(u8, u16);
+
+#[versioned(variants(A, B))]
+#[derive(SimpleSerialize)]
+struct UnusedParameter<T> {
+    a: u8,
+    #[versioned(only(B))]
+    b: T,
+}
+
+#[versioned(variants(A, B))]
+#[derive(SimpleSerialize)]
+struct ReservedNames {
+    version: u8,
+    #[versioned(only(B))]
+    from: u16,
+}
+
+fn main() {}